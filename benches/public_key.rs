@@ -0,0 +1,67 @@
+//! Compares verifying beacons against a chain info that has already decoded its public key with
+//! one that has to decode it first, as every verification did before the key was memoised.
+
+use bls_signatures::{PrivateKey, Serialize};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use drand_client_rs::{ChainInfo, Scheme, UnchainedBeacon, UnchainedScheme};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+const ROUND: u64 = 1_000;
+
+fn chain_info_json(private_key: &PrivateKey) -> String {
+    json!({
+        "public_key": hex::encode(private_key.public_key().as_bytes()),
+        "period": 30,
+        "genesis_time": 1595431050,
+        "hash": "01",
+        "groupHash": "02",
+        "schemeID": "pedersen-bls-unchained",
+        "metadata": { "beaconID": "default" },
+    })
+    .to_string()
+}
+
+fn beacon(private_key: &PrivateKey) -> UnchainedBeacon {
+    let message = Sha256::digest(&ROUND.to_be_bytes());
+    let signature = private_key.sign(message).as_bytes();
+    serde_json::from_value(json!({
+        "round": ROUND,
+        "randomness": hex::encode(Sha256::digest(&signature)),
+        "signature": hex::encode(&signature),
+    }))
+    .unwrap()
+}
+
+fn verify(c: &mut Criterion) {
+    let private_key = PrivateKey::new([7u8; 32]);
+    let info_json = chain_info_json(&private_key);
+    let beacon = beacon(&private_key);
+    let scheme = UnchainedScheme {};
+
+    let info: ChainInfo = serde_json::from_str(&info_json).unwrap();
+    scheme.verify(&info, beacon.clone()).unwrap();
+
+    let mut group = c.benchmark_group("verify");
+    group.bench_function("decoded_public_key", |b| {
+        b.iter_batched(
+            || beacon.clone(),
+            |beacon| scheme.verify(&info, beacon),
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("undecoded_public_key", |b| {
+        b.iter_batched(
+            || {
+                let info: ChainInfo = serde_json::from_str(&info_json).unwrap();
+                (info, beacon.clone())
+            },
+            |(info, beacon)| scheme.verify(&info, beacon),
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, verify);
+criterion_main!(benches);
@@ -0,0 +1,57 @@
+//! Fetches and verifies a range of 1000 rounds from canned responses, so the cost measured is the
+//! verification. Compare runs with and without `--features rayon`.
+
+use bls_signatures::{PrivateKey, Serialize};
+use criterion::{criterion_group, criterion_main, Criterion};
+use drand_client_rs::{new_client_with_transport, MockTransport, UnchainedScheme};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+const ROUNDS: u64 = 1_000;
+
+fn relay(private_key: &PrivateKey) -> MockTransport {
+    let info = json!({
+        "public_key": hex::encode(private_key.public_key().as_bytes()),
+        "period": 30,
+        "genesis_time": 1595431050,
+        "hash": "01",
+        "groupHash": "02",
+        "schemeID": "pedersen-bls-unchained",
+        "metadata": { "beaconID": "default" },
+    });
+    (1..=ROUNDS).fold(
+        MockTransport::new().with_body("http://relay/info", &info.to_string()),
+        |relay, round| {
+            let signature = private_key
+                .sign(Sha256::digest(&round.to_be_bytes()))
+                .as_bytes();
+            let beacon = json!({
+                "round": round,
+                "randomness": hex::encode(Sha256::digest(&signature)),
+                "signature": hex::encode(&signature),
+            });
+            relay.with_body(
+                &format!("http://relay/public/{}", round),
+                &beacon.to_string(),
+            )
+        },
+    )
+}
+
+fn range(c: &mut Criterion) {
+    let relay = relay(&PrivateKey::new([7u8; 32]));
+    let client = new_client_with_transport(&UnchainedScheme {}, "http://relay", relay).unwrap();
+
+    let mut group = c.benchmark_group("range");
+    group.sample_size(10);
+    group.bench_function("verify_1000_rounds", |b| {
+        b.iter(|| {
+            let results = client.randomness_range(1, ROUNDS).unwrap();
+            assert!(results.iter().all(Result::is_ok));
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, range);
+criterion_main!(benches);
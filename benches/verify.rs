@@ -0,0 +1,151 @@
+//! Verifies single beacons with each kind of scheme, and a batch of unchained beacons at once
+//! against one at a time, over beacons signed here so no relay is needed.
+
+use bls12_381::hash_to_curve::{ExpandMsgXmd, HashToCurve};
+use bls12_381::{G1Affine, G1Projective, G2Affine, Scalar};
+use bls_signatures::{PrivateKey, Serialize};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use drand_client_rs::bls::G1_DOMAIN;
+use drand_client_rs::{
+    ChainInfo, ChainedBeacon, ChainedScheme, Scheme, UnchainedBeacon, UnchainedScheme,
+    UnchainedSchemeOnG1Rfc9380,
+};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+const ROUND: u64 = 1_000;
+const BATCH: u64 = 100;
+
+fn chain_info(scheme_id: &str, public_key: &[u8]) -> ChainInfo {
+    serde_json::from_value(json!({
+        "public_key": hex::encode(public_key),
+        "period": 30,
+        "genesis_time": 1595431050,
+        "hash": "01",
+        "groupHash": "02",
+        "schemeID": scheme_id,
+        "metadata": { "beaconID": "default" },
+    }))
+    .unwrap()
+}
+
+fn beacon<B: DeserializeOwned>(
+    round: u64,
+    signature: &[u8],
+    previous_signature: Option<&[u8]>,
+) -> B {
+    let mut beacon = json!({
+        "round": round,
+        "randomness": hex::encode(Sha256::digest(signature)),
+        "signature": hex::encode(signature),
+    });
+    if let Some(previous_signature) = previous_signature {
+        beacon["previous_signature"] = Value::String(hex::encode(previous_signature));
+    }
+    serde_json::from_value(beacon).unwrap()
+}
+
+fn chained_beacon(private_key: &PrivateKey) -> ChainedBeacon {
+    let previous_signature = private_key.sign(Sha256::digest(b"previous")).as_bytes();
+    let mut message = previous_signature.clone();
+    message.extend_from_slice(&ROUND.to_be_bytes());
+    let signature = private_key.sign(Sha256::digest(&message)).as_bytes();
+    beacon(ROUND, &signature, Some(&previous_signature))
+}
+
+fn unchained_beacon(private_key: &PrivateKey, round: u64) -> UnchainedBeacon {
+    let signature = private_key
+        .sign(Sha256::digest(&round.to_be_bytes()))
+        .as_bytes();
+    beacon(round, &signature, None)
+}
+
+/// A beacon signed on G1, as quicknet's are, and the chain's public key on G2.
+fn g1_beacon(secret_key: Scalar) -> (Vec<u8>, UnchainedBeacon) {
+    let digest = Sha256::digest(&ROUND.to_be_bytes());
+    let point =
+        <G1Projective as HashToCurve<ExpandMsgXmd<Sha256>>>::hash_to_curve(digest, G1_DOMAIN);
+    let signature = G1Affine::from(point * secret_key).to_compressed();
+    let public_key = G2Affine::from(G2Affine::generator() * secret_key).to_compressed();
+    (public_key.to_vec(), beacon(ROUND, &signature, None))
+}
+
+fn bench_verify<B: Clone, S: Scheme<B>>(
+    c: &mut Criterion,
+    name: &str,
+    scheme: &S,
+    info: &ChainInfo,
+    beacon: &B,
+) {
+    scheme.verify(info, beacon.clone()).unwrap();
+    c.bench_function(name, |b| {
+        b.iter_batched(
+            || beacon.clone(),
+            |beacon| scheme.verify(info, beacon),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+fn verify(c: &mut Criterion) {
+    let private_key = PrivateKey::new([7u8; 32]);
+    let public_key = private_key.public_key().as_bytes();
+
+    let info = chain_info("pedersen-bls-chained", &public_key);
+    bench_verify(
+        c,
+        "verify/chained",
+        &ChainedScheme {},
+        &info,
+        &chained_beacon(&private_key),
+    );
+
+    let info = chain_info("pedersen-bls-unchained", &public_key);
+    let beacon = unchained_beacon(&private_key, ROUND);
+    bench_verify(
+        c,
+        "verify/unchained_g2",
+        &UnchainedScheme {},
+        &info,
+        &beacon,
+    );
+
+    let (g1_public_key, beacon) = g1_beacon(Scalar::from(0x5eed_u64));
+    let info = chain_info("bls-unchained-g1-rfc9380", &g1_public_key);
+    bench_verify(
+        c,
+        "verify/unchained_g1",
+        &UnchainedSchemeOnG1Rfc9380 {},
+        &info,
+        &beacon,
+    );
+}
+
+fn verify_batch(c: &mut Criterion) {
+    let private_key = PrivateKey::new([7u8; 32]);
+    let info = chain_info(
+        "pedersen-bls-unchained",
+        &private_key.public_key().as_bytes(),
+    );
+    let beacons: Vec<UnchainedBeacon> = (1..=BATCH)
+        .map(|round| unchained_beacon(&private_key, round))
+        .collect();
+    let scheme = UnchainedScheme {};
+    scheme.verify_batch(&info, &beacons).unwrap();
+
+    let mut group = c.benchmark_group("verify_100_unchained");
+    group.sample_size(10);
+    group.bench_function("batch", |b| b.iter(|| scheme.verify_batch(&info, &beacons)));
+    group.bench_function("one_at_a_time", |b| {
+        b.iter(|| {
+            beacons
+                .iter()
+                .try_for_each(|beacon| scheme.verify(&info, beacon.clone()).map(|_| ()))
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, verify, verify_batch);
+criterion_main!(benches);
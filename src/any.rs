@@ -0,0 +1,150 @@
+use crate::auto::{chained_scheme_for, unchained_scheme_for};
+use crate::chain_info::ChainInfo;
+use crate::chained::ChainedBeacon;
+use crate::unchained::UnchainedBeacon;
+use crate::{Beacon, Scheme, SchemeError, RANDOMNESS_LEN};
+use core::str::FromStr;
+use serde::{Deserialize, Serialize};
+
+/// A beacon from either kind of chain, for clients of networks only discovered at runtime.
+/// Chained beacons are told apart by their `previous_signature`.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(untagged)]
+pub enum AnyBeacon {
+    Chained(ChainedBeacon),
+    Unchained(UnchainedBeacon),
+}
+
+impl Beacon for AnyBeacon {
+    fn round_number(&self) -> u64 {
+        match self {
+            AnyBeacon::Chained(beacon) => beacon.round_number(),
+            AnyBeacon::Unchained(beacon) => beacon.round_number(),
+        }
+    }
+
+    fn randomness_bytes(&self) -> Result<[u8; RANDOMNESS_LEN], SchemeError> {
+        match self {
+            AnyBeacon::Chained(beacon) => beacon.randomness_bytes(),
+            AnyBeacon::Unchained(beacon) => beacon.randomness_bytes(),
+        }
+    }
+
+    fn signature_bytes(&self) -> &[u8] {
+        match self {
+            AnyBeacon::Chained(beacon) => beacon.signature_bytes(),
+            AnyBeacon::Unchained(beacon) => beacon.signature_bytes(),
+        }
+    }
+}
+
+/// Verifies [`AnyBeacon`]s with whichever of the crate's schemes supports a scheme id, so one
+/// client type can serve every drand network. A beacon of the other kind than the chain's is
+/// invalid.
+#[derive(Clone, Copy)]
+pub enum AnyScheme {
+    Chained(&'static dyn Scheme<ChainedBeacon>),
+    Unchained(&'static dyn Scheme<UnchainedBeacon>),
+}
+
+impl AnyScheme {
+    /// The scheme for `scheme_id`, if the crate supports it.
+    pub fn for_scheme_id(scheme_id: &str) -> Option<Self> {
+        chained_scheme_for(scheme_id)
+            .map(AnyScheme::Chained)
+            .or_else(|| unchained_scheme_for(scheme_id).map(AnyScheme::Unchained))
+    }
+}
+
+/// Parses a scheme id, e.g. from a config file, as [`AnyScheme::for_scheme_id`] would, failing
+/// with [`SchemeError::InvalidScheme`] for ids the crate doesn't support.
+impl FromStr for AnyScheme {
+    type Err = SchemeError;
+
+    fn from_str(scheme_id: &str) -> Result<Self, SchemeError> {
+        AnyScheme::for_scheme_id(scheme_id).ok_or(SchemeError::InvalidScheme)
+    }
+}
+
+impl Scheme<AnyBeacon> for AnyScheme {
+    fn supports(&self, scheme_id: &str) -> bool {
+        match self {
+            AnyScheme::Chained(scheme) => scheme.supports(scheme_id),
+            AnyScheme::Unchained(scheme) => scheme.supports(scheme_id),
+        }
+    }
+
+    fn scheme_id(&self) -> &str {
+        match self {
+            AnyScheme::Chained(scheme) => scheme.scheme_id(),
+            AnyScheme::Unchained(scheme) => scheme.scheme_id(),
+        }
+    }
+
+    fn verify(&self, info: &ChainInfo, beacon: AnyBeacon) -> Result<AnyBeacon, SchemeError> {
+        match (self, beacon) {
+            (AnyScheme::Chained(scheme), AnyBeacon::Chained(beacon)) => {
+                scheme.verify(info, beacon).map(AnyBeacon::Chained)
+            }
+            (AnyScheme::Unchained(scheme), AnyBeacon::Unchained(beacon)) => {
+                scheme.verify(info, beacon).map(AnyBeacon::Unchained)
+            }
+            _ => Err(SchemeError::InvalidBeacon),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::any::{AnyBeacon, AnyScheme};
+    use crate::fixtures;
+    use crate::{Scheme, SchemeError};
+
+    #[test]
+    fn beacons_parse_as_the_kind_their_json_is() {
+        let chained = fixtures::chained_beacon(2, vec![9; 96]);
+        let unchained = fixtures::unchained_beacon(2);
+        assert_eq!(
+            serde_json::from_str::<AnyBeacon>(&fixtures::chained_beacon_json(&chained)).unwrap(),
+            AnyBeacon::Chained(chained)
+        );
+        assert_eq!(
+            serde_json::from_str::<AnyBeacon>(&fixtures::unchained_beacon_json(&unchained))
+                .unwrap(),
+            AnyBeacon::Unchained(unchained)
+        );
+    }
+
+    #[test]
+    fn beacons_verify_only_with_their_chains_scheme() {
+        let chained_info = fixtures::chain_info("pedersen-bls-chained");
+        let unchained_info = fixtures::chain_info("pedersen-bls-unchained");
+        let chained = AnyBeacon::Chained(fixtures::chained_beacon(2, vec![9; 96]));
+        let unchained = AnyBeacon::Unchained(fixtures::unchained_beacon(2));
+
+        let scheme = AnyScheme::for_scheme_id(&chained_info.scheme_id).unwrap();
+        assert_eq!(scheme.scheme_id(), "pedersen-bls-chained");
+        assert!(scheme.verify(&chained_info, chained).is_ok());
+        assert!(scheme.verify(&chained_info, unchained.clone()).is_err());
+
+        let scheme = AnyScheme::for_scheme_id(&unchained_info.scheme_id).unwrap();
+        assert!(scheme.verify(&unchained_info, unchained).is_ok());
+        assert!(AnyScheme::for_scheme_id("not-a-scheme").is_none());
+    }
+
+    #[test]
+    fn scheme_ids_parse_as_their_scheme() {
+        let mut ids = vec!["pedersen-bls-chained", "pedersen-bls-unchained"];
+        if cfg!(feature = "g1") {
+            ids.extend(["bls-unchained-on-g1", "bls-unchained-g1-rfc9380"]);
+        }
+        for id in ids {
+            let scheme: AnyScheme = id.parse().unwrap();
+            assert_eq!(scheme.scheme_id(), id);
+        }
+        assert!(matches!(
+            "not-a-scheme".parse::<AnyScheme>(),
+            Err(SchemeError::InvalidScheme)
+        ));
+    }
+}
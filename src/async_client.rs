@@ -0,0 +1,1023 @@
+use crate::builder::DrandClientBuilder;
+use crate::cache::{BeaconCache, LatestBeacon};
+use crate::chain_info::{ChainInfo, NetworkSummary};
+#[cfg(feature = "chained")]
+use crate::chained::{ChainedBeacon, ChainedScheme};
+use crate::clock::Clock;
+use crate::endpoints::{ApiVersion, Endpoints, Paths};
+pub use crate::http::AsyncHttpTransport;
+use crate::http::{AsyncTransport, HttpError, HttpResponse, Validators};
+use crate::metrics::Metrics;
+#[cfg(any(feature = "chained", feature = "unchained"))]
+use crate::networks;
+use crate::options::{ClientOptions, SchemeChoice};
+use crate::round::reject_future_round;
+use crate::timer;
+#[cfg(feature = "unchained")]
+use crate::unchained::{UnchainedBeacon, UnchainedScheme};
+#[cfg(feature = "g1")]
+use crate::unchained_g1::{UnchainedSchemeOnG1, UnchainedSchemeOnG1Rfc9380};
+use crate::watch::{delay_until, MAX_ATTEMPTS_PER_ROUND, RETRY_INTERVAL};
+#[cfg(all(feature = "chained", feature = "unchained"))]
+use crate::AnyBeacon;
+use crate::DrandClientError::InvalidRound;
+use crate::{
+    chain_info_warnings, check_same_chain, check_scheme, fetch_error, parse_beacon,
+    parse_chain_info, parse_chains, round_at, time_of_round, unknown_metadata, Beacon,
+    BeaconWithContext, DrandClientError, FetchedBeacon, RetryPolicy, Round, Scheme, VerifiedBeacon,
+    Warning, MAX_RANGE_LEN, RANGE_CONCURRENCY,
+};
+use futures::stream::{self, Stream, StreamExt};
+use serde::de::DeserializeOwned;
+use std::num::NonZeroUsize;
+use std::ops::ControlFlow;
+use std::pin::pin;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub struct AsyncDrandClient<B, T = AsyncHttpTransport> {
+    scheme: Arc<dyn Scheme<B>>,
+    transport: T,
+    endpoints: Endpoints,
+    chain_hash: Option<String>,
+    paths: Paths,
+    chain_info: ChainInfo,
+    retry_policy: RetryPolicy,
+    cache: Option<Arc<BeaconCache<B>>>,
+    latest: Option<Arc<LatestBeacon<B>>>,
+    metrics: Arc<dyn Metrics>,
+    clock: Arc<dyn Clock>,
+    future_round_tolerance: Option<Duration>,
+    warnings: Vec<Warning>,
+}
+
+/// Clones share the scheme, cache, metrics and clock, and don't re-fetch the chain info.
+impl<B, T: Clone> Clone for AsyncDrandClient<B, T> {
+    fn clone(&self) -> Self {
+        AsyncDrandClient {
+            scheme: self.scheme.clone(),
+            transport: self.transport.clone(),
+            endpoints: self.endpoints.clone(),
+            chain_hash: self.chain_hash.clone(),
+            paths: self.paths.clone(),
+            chain_info: self.chain_info.clone(),
+            retry_policy: self.retry_policy.clone(),
+            cache: self.cache.clone(),
+            latest: self.latest.clone(),
+            metrics: self.metrics.clone(),
+            clock: self.clock.clone(),
+            future_round_tolerance: self.future_round_tolerance,
+            warnings: self.warnings.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "chained")]
+pub async fn new_chained_client(
+    base_url: &str,
+) -> Result<AsyncDrandClient<ChainedBeacon>, DrandClientError> {
+    new_client(&ChainedScheme {}, base_url).await
+}
+
+#[cfg(feature = "unchained")]
+pub async fn new_unchained_client(
+    base_url: &str,
+) -> Result<AsyncDrandClient<UnchainedBeacon>, DrandClientError> {
+    new_client(&UnchainedScheme {}, base_url).await
+}
+
+#[cfg(feature = "g1")]
+pub async fn new_unchained_g1_client(
+    base_url: &str,
+) -> Result<AsyncDrandClient<UnchainedBeacon>, DrandClientError> {
+    new_client(&UnchainedSchemeOnG1 {}, base_url).await
+}
+
+#[cfg(feature = "g1")]
+pub async fn new_unchained_g1_rfc9380_client(
+    base_url: &str,
+) -> Result<AsyncDrandClient<UnchainedBeacon>, DrandClientError> {
+    new_client(&UnchainedSchemeOnG1Rfc9380 {}, base_url).await
+}
+
+pub async fn new_client<S: Scheme<B> + 'static, B>(
+    scheme: S,
+    base_url: &str,
+) -> Result<AsyncDrandClient<B>, DrandClientError> {
+    DrandClientBuilder::new()
+        .scheme(scheme)
+        .base_url(base_url)
+        .build_async()
+        .await
+}
+
+pub async fn new_client_with_timeout<S: Scheme<B> + 'static, B>(
+    scheme: S,
+    base_url: &str,
+    timeout: Duration,
+) -> Result<AsyncDrandClient<B>, DrandClientError> {
+    DrandClientBuilder::new()
+        .scheme(scheme)
+        .base_url(base_url)
+        .timeout(timeout)
+        .build_async()
+        .await
+}
+
+pub async fn new_client_with_retry<S: Scheme<B> + 'static, B>(
+    scheme: S,
+    base_url: &str,
+    retry_policy: RetryPolicy,
+) -> Result<AsyncDrandClient<B>, DrandClientError> {
+    DrandClientBuilder::new()
+        .scheme(scheme)
+        .base_url(base_url)
+        .retry(retry_policy)
+        .build_async()
+        .await
+}
+
+pub async fn new_client_with_chain_hash<S: Scheme<B> + 'static, B>(
+    scheme: S,
+    base_url: &str,
+    expected_chain_hash: &[u8],
+) -> Result<AsyncDrandClient<B>, DrandClientError> {
+    DrandClientBuilder::new()
+        .scheme(scheme)
+        .base_url(base_url)
+        .expected_chain_hash(expected_chain_hash)
+        .build_async()
+        .await
+}
+
+/// Refuses to verify against any group public key but `public_key`, whatever the relay's `/info`
+/// returns. Pass the chain info to [`DrandClientBuilder::chain_info`] to skip fetching it entirely.
+pub async fn new_client_with_public_key<S: Scheme<B> + 'static, B>(
+    scheme: S,
+    base_url: &str,
+    public_key: &[u8],
+) -> Result<AsyncDrandClient<B>, DrandClientError> {
+    DrandClientBuilder::new()
+        .scheme(scheme)
+        .base_url(base_url)
+        .expected_public_key(public_key)
+        .build_async()
+        .await
+}
+
+/// Targets a single chain on a relay serving several, routing requests via `/{chain_hash}/...`.
+pub async fn new_client_for_chain<S: Scheme<B> + 'static, B>(
+    scheme: S,
+    base_url: &str,
+    chain_hash: &str,
+) -> Result<AsyncDrandClient<B>, DrandClientError> {
+    DrandClientBuilder::new()
+        .scheme(scheme)
+        .base_url(base_url)
+        .chain_hash(chain_hash)
+        .build_async()
+        .await
+}
+
+pub async fn new_client_with_cache<S: Scheme<B> + 'static, B>(
+    scheme: S,
+    base_url: &str,
+    capacity: NonZeroUsize,
+) -> Result<AsyncDrandClient<B>, DrandClientError> {
+    DrandClientBuilder::new()
+        .scheme(scheme)
+        .base_url(base_url)
+        .cache_capacity(capacity)
+        .build_async()
+        .await
+}
+
+pub async fn new_client_with_failover<S: Scheme<B> + 'static, B>(
+    scheme: S,
+    base_urls: &[&str],
+    retry_policy: RetryPolicy,
+) -> Result<AsyncDrandClient<B>, DrandClientError> {
+    base_urls
+        .iter()
+        .fold(
+            DrandClientBuilder::new().scheme(scheme),
+            |builder, base_url| builder.base_url(base_url),
+        )
+        .retry(retry_policy)
+        .build_async()
+        .await
+}
+
+/// Reports each fetch, verification and cache lookup to `metrics`; see [`Metrics`].
+pub async fn new_client_with_metrics<S: Scheme<B> + 'static, B>(
+    scheme: S,
+    base_url: &str,
+    metrics: Arc<dyn Metrics>,
+) -> Result<AsyncDrandClient<B>, DrandClientError> {
+    DrandClientBuilder::new()
+        .scheme(scheme)
+        .base_url(base_url)
+        .metrics(metrics)
+        .build_async()
+        .await
+}
+
+/// Makes every request through `transport`, e.g. to share an existing reqwest client
+/// or route through a proxy. The transport's own timeout applies.
+pub async fn new_client_with_transport<S: Scheme<B> + 'static, B, T: AsyncTransport>(
+    scheme: S,
+    base_url: &str,
+    transport: T,
+) -> Result<AsyncDrandClient<B, T>, DrandClientError> {
+    DrandClientBuilder::new()
+        .scheme(scheme)
+        .base_url(base_url)
+        .build_async_with_transport(transport)
+        .await
+}
+
+pub(crate) async fn new_client_with_transport_and_options<B, T: AsyncTransport>(
+    scheme: SchemeChoice<B>,
+    base_urls: Vec<String>,
+    http_transport: T,
+    mut options: ClientOptions,
+) -> Result<AsyncDrandClient<B, T>, DrandClientError> {
+    let retry_policy = options.retry_policy.clone();
+    let endpoints = Endpoints::new(base_urls);
+    let chain_hash = options.chain_hash_path.as_deref();
+    let fetch_info = |paths: Paths| {
+        let endpoints = &endpoints;
+        let retry_policy = &retry_policy;
+        let http_transport = &http_transport;
+        async move {
+            endpoints
+                .first_success_async(|base_url| {
+                    let url = paths.info_url(base_url, chain_hash);
+                    async move {
+                        retry_policy
+                            .retry_async(|| http_transport.fetch(&url))
+                            .await
+                    }
+                })
+                .await
+                .map_err(DrandClientError::NotResponding)
+                .and_then(|body| Ok((parse_chain_info(&body)?, unknown_metadata(&body))))
+        }
+    };
+    // detecting asks for the v2 info and falls back to v1 if no relay answers it, but there's
+    // no layout to detect if the paths were given. With the chain info supplied there's no need
+    // for the info, so the latest v2 beacon is asked for instead
+    let templates = options.path_templates.take();
+    let (api_version, fetched) = match options.api_version {
+        Some(api_version) => (api_version, None),
+        None if templates.is_some() => (ApiVersion::V1, None),
+        None if options.chain_info.is_some() => {
+            let serves_v2 = endpoints
+                .first_success_async(|base_url| {
+                    let url =
+                        Paths::from(ApiVersion::V2).beacon_url(base_url, chain_hash, "latest");
+                    let retry_policy = &retry_policy;
+                    let http_transport = &http_transport;
+                    async move {
+                        retry_policy
+                            .retry_async(|| http_transport.fetch(&url))
+                            .await
+                    }
+                })
+                .await
+                .is_ok();
+            let api_version = if serves_v2 {
+                ApiVersion::V2
+            } else {
+                ApiVersion::V1
+            };
+            (api_version, None)
+        }
+        None => match fetch_info(Paths::from(ApiVersion::V2)).await {
+            Ok(chain_info) => (ApiVersion::V2, Some(chain_info)),
+            Err(_) => (ApiVersion::V1, None),
+        },
+    };
+    let paths = Paths {
+        api_version,
+        templates,
+    };
+    let chain_picked = options.chain_info.is_some() || options.expected_chain_hash.is_some();
+    let (chain_info, unknown_metadata) = match (options.chain_info.take(), fetched) {
+        (Some(chain_info), _) => {
+            chain_info.validate()?;
+            (chain_info, vec![])
+        }
+        (None, Some(fetched)) => fetched,
+        (None, None) => fetch_info(paths.clone()).await?,
+    };
+    options.validate(&chain_info)?;
+    let scheme = scheme.for_chain(&chain_info)?;
+    check_scheme(scheme.as_ref(), &chain_info)?;
+    let warnings = chain_info_warnings(&chain_info, unknown_metadata, chain_picked);
+    let client = AsyncDrandClient {
+        transport: http_transport,
+        chain_info,
+        scheme,
+        endpoints,
+        chain_hash: options.chain_hash_path,
+        paths,
+        retry_policy,
+        cache: options
+            .cache_capacity
+            .map(|capacity| Arc::new(BeaconCache::new(capacity))),
+        latest: options
+            .conditional_latest
+            .then(|| Arc::new(LatestBeacon::new())),
+        metrics: options.metrics,
+        clock: options.clock,
+        future_round_tolerance: options.future_round_tolerance,
+        warnings,
+    };
+
+    Ok(client)
+}
+
+/// A client for whichever network the relay serves, its scheme selected from the `schemeID`
+/// the relay advertises. For other options, use [`DrandClientBuilder::auto_scheme`].
+#[cfg(all(feature = "chained", feature = "unchained"))]
+pub async fn new_auto_client(
+    base_url: &str,
+) -> Result<AsyncDrandClient<AnyBeacon>, DrandClientError> {
+    DrandClientBuilder::new()
+        .auto_scheme()
+        .base_url(base_url)
+        .build_async()
+        .await
+}
+
+pub async fn fetch_chain_info<T: AsyncTransport>(
+    transport: &T,
+    base_url: &str,
+) -> Result<ChainInfo, DrandClientError> {
+    let url = format!("{}/info", base_url);
+    match transport.fetch(&url).await {
+        Err(e) => Err(DrandClientError::NotResponding(e)),
+        Ok(body) => parse_chain_info(&body),
+    }
+}
+
+/// Lists the hashes of every chain served by the relay at `base_url`.
+pub async fn list_chains<T: AsyncTransport>(
+    transport: &T,
+    base_url: &str,
+) -> Result<Vec<String>, DrandClientError> {
+    let url = format!("{}/chains", base_url);
+    match transport.fetch(&url).await {
+        Err(e) => Err(DrandClientError::NotResponding(e)),
+        Ok(body) => parse_chains(&body),
+    }
+}
+
+impl<B> AsyncDrandClient<B> {
+    /// A client that trusts `chain_info` rather than fetching it from the relay's `/info`, e.g.
+    /// for chain info embedded at build time. It's validated, but pin its hash or public key with
+    /// [`DrandClientBuilder`] to check it against values you already trust.
+    pub async fn from_chain_info<S: Scheme<B> + 'static>(
+        scheme: S,
+        base_url: &str,
+        chain_info: ChainInfo,
+    ) -> Result<Self, DrandClientError> {
+        DrandClientBuilder::new()
+            .scheme(scheme)
+            .base_url(base_url)
+            .chain_info(chain_info)
+            .build_async()
+            .await
+    }
+}
+
+#[cfg(feature = "chained")]
+impl AsyncDrandClient<ChainedBeacon> {
+    /// A client for [`networks::MAINNET`], checking its chain info against the known hash.
+    pub async fn mainnet() -> Result<Self, DrandClientError> {
+        DrandClientBuilder::new()
+            .scheme(&ChainedScheme {})
+            .base_url(networks::MAINNET.base_url)
+            .chain_hash(networks::MAINNET.chain_hash)
+            .build_async()
+            .await
+    }
+}
+
+#[cfg(feature = "unchained")]
+impl AsyncDrandClient<UnchainedBeacon> {
+    /// A client for [`networks::QUICKNET`], checking its chain info against the known hash.
+    #[cfg(feature = "g1")]
+    pub async fn quicknet() -> Result<Self, DrandClientError> {
+        DrandClientBuilder::new()
+            .scheme(&UnchainedSchemeOnG1Rfc9380 {})
+            .base_url(networks::QUICKNET.base_url)
+            .chain_hash(networks::QUICKNET.chain_hash)
+            .build_async()
+            .await
+    }
+
+    /// A client for [`networks::TESTNET_UNCHAINED`], checking its chain info against the known hash.
+    pub async fn testnet_unchained() -> Result<Self, DrandClientError> {
+        DrandClientBuilder::new()
+            .scheme(&UnchainedScheme {})
+            .base_url(networks::TESTNET_UNCHAINED.base_url)
+            .chain_hash(networks::TESTNET_UNCHAINED.chain_hash)
+            .build_async()
+            .await
+    }
+}
+
+impl<B, T> AsyncDrandClient<B, T> {
+    pub fn chain_info(&self) -> &ChainInfo {
+        &self.chain_info
+    }
+
+    /// The relay's `schemeID`, e.g. to log which scheme an auto client settled on.
+    pub fn scheme_id(&self) -> &str {
+        &self.chain_info.scheme_id
+    }
+
+    pub fn network_summary(&self) -> NetworkSummary {
+        self.chain_info.summary()
+    }
+
+    /// The base URL of the relay that most recently responded successfully.
+    pub fn base_url(&self) -> &str {
+        self.endpoints.preferred()
+    }
+
+    /// The URL the beacon tagged `tag` would be fetched from, e.g. `beacon_url("latest")`.
+    pub fn beacon_url(&self, tag: &str) -> String {
+        self.paths
+            .beacon_url(self.base_url(), self.chain_hash.as_deref(), tag)
+    }
+
+    /// The URL the chain info is fetched from.
+    pub fn info_url(&self) -> String {
+        self.paths
+            .info_url(self.base_url(), self.chain_hash.as_deref())
+    }
+
+    /// The HTTP path layout requests are sent with, as configured or detected on construction,
+    /// unless `path_templates` replaced it.
+    pub fn api_version(&self) -> ApiVersion {
+        self.paths.api_version
+    }
+
+    /// What was unexpected about the chain info on construction, e.g. metadata fields the client
+    /// doesn't know, which didn't stop the client being built.
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+}
+
+impl<B, T> AsyncDrandClient<B, T>
+where
+    B: DeserializeOwned + Clone + Beacon,
+    T: AsyncTransport,
+{
+    /// Checks that a relay is reachable and still serving the chain the client was built for, by
+    /// re-fetching the chain info, e.g. for a readiness probe. No beacon is fetched.
+    pub async fn health(&self) -> Result<(), DrandClientError> {
+        let chain_info = self
+            .endpoints
+            .first_success_async(|base_url| {
+                let url = self.paths.info_url(base_url, self.chain_hash.as_deref());
+                async move {
+                    self.retry_policy
+                        .retry_async(|| self.transport.fetch(&url))
+                        .await
+                }
+            })
+            .await
+            .map_err(DrandClientError::NotResponding)
+            .and_then(|body| parse_chain_info(&body))?;
+        check_same_chain(&self.chain_info, &chain_info)
+    }
+
+    pub async fn latest_randomness(&self) -> Result<B, DrandClientError> {
+        self.fetch_latest().await.map(|fetched| fetched.beacon)
+    }
+
+    /// The latest beacon, along with when the relay says it goes stale, i.e. when to poll next.
+    pub async fn latest_randomness_with_expiry(
+        &self,
+    ) -> Result<FetchedBeacon<B>, DrandClientError> {
+        self.fetch_latest().await
+    }
+
+    /// The latest beacon, along with when the round after it is emitted, i.e. how long to sleep
+    /// before polling for the next one.
+    pub async fn latest_randomness_with_timing(&self) -> Result<(B, SystemTime), DrandClientError> {
+        let beacon = self.latest_randomness().await?;
+        let next_round = beacon
+            .round_number()
+            .checked_add(1)
+            .and_then(Round::new)
+            .ok_or(InvalidRound)?;
+        let next_round_at = time_of_round(&self.chain_info, next_round)?;
+        Ok((beacon, UNIX_EPOCH + Duration::from_secs(next_round_at)))
+    }
+
+    /// The round the chain is on according to the client's clock. A clock set before genesis,
+    /// as on devices without a real-time clock, is an error rather than a guess at round 1.
+    pub fn current_round(&self) -> Result<Round, DrandClientError> {
+        let now = self.clock.now_unix();
+        if now < self.chain_info.genesis_time {
+            return Err(DrandClientError::ClockBeforeGenesis);
+        }
+        Ok(round_at(&self.chain_info, now))
+    }
+
+    pub fn time_of_round(&self, round: Round) -> Result<u64, DrandClientError> {
+        time_of_round(&self.chain_info, round)
+    }
+
+    /// The beacon that was current at `unix_secs`, i.e. the latest one emitted at or before it.
+    pub async fn randomness_at_time(&self, unix_secs: u64) -> Result<B, DrandClientError> {
+        if unix_secs < self.chain_info.genesis_time {
+            return Err(InvalidRound);
+        }
+        self.randomness(round_at(&self.chain_info, unix_secs).get())
+            .await
+    }
+
+    pub async fn randomness(&self, round_number: u64) -> Result<B, DrandClientError> {
+        if round_number == 0 {
+            return Err(InvalidRound);
+        }
+        if let Some(beacon) = self.cache.as_ref().and_then(|c| c.get(round_number)) {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(round = round_number, "beacon cache hit");
+            self.metrics.on_cache_hit();
+            return Ok(beacon);
+        }
+        if self.cache.is_some() {
+            self.metrics.on_cache_miss();
+        }
+        self.randomness_with_expiry(round_number)
+            .await
+            .map(|fetched| fetched.beacon)
+    }
+
+    /// `round_number`'s beacon, along with the chain info it was verified against.
+    pub async fn randomness_with_context(
+        &self,
+        round_number: u64,
+    ) -> Result<BeaconWithContext<'_, B>, DrandClientError> {
+        let beacon = self.randomness(round_number).await?;
+        Ok(BeaconWithContext {
+            beacon,
+            chain_info: &self.chain_info,
+        })
+    }
+
+    /// The latest beacon, along with the chain info it was verified against.
+    pub async fn latest_randomness_with_context(
+        &self,
+    ) -> Result<BeaconWithContext<'_, B>, DrandClientError> {
+        let beacon = self.latest_randomness().await?;
+        Ok(BeaconWithContext {
+            beacon,
+            chain_info: &self.chain_info,
+        })
+    }
+
+    /// Fetches `round_number` from the relay, bypassing the cache, along with when the relay
+    /// says the response goes stale.
+    pub async fn randomness_with_expiry(
+        &self,
+        round_number: u64,
+    ) -> Result<FetchedBeacon<B>, DrandClientError> {
+        if round_number == 0 {
+            return Err(InvalidRound);
+        }
+        let fetched = self.fetch_beacon_tag(&format!("{}", round_number)).await?;
+        if let Some(cache) = &self.cache {
+            cache.insert(round_number, fetched.beacon.clone());
+        }
+        Ok(fetched)
+    }
+
+    /// Fetches `round_number` from the relay **without verifying it**, for beacons whose source
+    /// is already trusted, e.g. a gateway that verifies them itself. The round number is still
+    /// checked, but nothing else is, and the beacon isn't cached. Prefer [`Self::randomness`].
+    pub async fn randomness_unverified(&self, round_number: u64) -> Result<B, DrandClientError> {
+        if round_number == 0 {
+            return Err(InvalidRound);
+        }
+        self.fetch_unverified(&format!("{}", round_number))
+            .await
+            .map(|fetched| fetched.beacon)
+    }
+
+    /// Fetches `round_number` from the relay, bypassing the cache, along with how long
+    /// downloading, parsing and verifying it each took, for finding where time goes.
+    pub async fn randomness_timed(
+        &self,
+        round_number: u64,
+    ) -> Result<VerifiedBeacon<B>, DrandClientError> {
+        if round_number == 0 {
+            return Err(InvalidRound);
+        }
+        let timed = self.fetch_timed(&format!("{}", round_number)).await?;
+        if let Some(cache) = &self.cache {
+            cache.insert(round_number, timed.beacon.clone());
+        }
+        Ok(timed)
+    }
+
+    /// The latest beacon, along with where the time went fetching it; see
+    /// [`Self::randomness_timed`].
+    pub async fn latest_randomness_timed(&self) -> Result<VerifiedBeacon<B>, DrandClientError> {
+        self.fetch_timed("latest").await
+    }
+
+    /// The latest beacon, **without verifying it**; see [`Self::randomness_unverified`].
+    pub async fn latest_randomness_unverified(&self) -> Result<B, DrandClientError> {
+        self.fetch_unverified("latest")
+            .await
+            .map(|fetched| fetched.beacon)
+    }
+
+    pub fn clear_cache(&self) {
+        if let Some(cache) = &self.cache {
+            cache.clear();
+        }
+        if let Some(latest) = &self.latest {
+            latest.clear();
+        }
+    }
+
+    async fn fetch_beacon_tag(&self, tag: &str) -> Result<FetchedBeacon<B>, DrandClientError> {
+        let fetched = self.fetch_unverified(tag).await?;
+        self.verify_fetched(fetched)
+    }
+
+    /// Downloads and parses the beacon at `public/{tag}`, reporting failures to the metrics.
+    /// There's no point verifying a beacon for the wrong round, so that's checked first.
+    async fn fetch_unverified(&self, tag: &str) -> Result<FetchedBeacon<B>, DrandClientError> {
+        let downloaded = self.download(tag).await;
+        self.parse_downloaded(tag, downloaded)
+    }
+
+    async fn download(&self, tag: &str) -> Result<HttpResponse, DrandClientError> {
+        self.download_if_modified(tag, None)
+            .await?
+            .ok_or(DrandClientError::NotResponding(HttpError::NotModified))
+    }
+
+    /// Downloads the beacon tagged `tag`, or nothing if the relay says it still matches `validators`.
+    async fn download_if_modified(
+        &self,
+        tag: &str,
+        validators: Option<&Validators>,
+    ) -> Result<Option<HttpResponse>, DrandClientError> {
+        let fetched = self
+            .endpoints
+            .first_success_async(|base_url| {
+                let url = self
+                    .paths
+                    .beacon_url(base_url, self.chain_hash.as_deref(), tag);
+                #[cfg(feature = "tracing")]
+                let span = tracing::debug_span!("fetch_beacon", url = %url, tag);
+                let fetch = async move {
+                    self.retry_policy
+                        .retry_async(|| async {
+                            let res = match validators {
+                                Some(validators) => {
+                                    self.transport.fetch_if_changed(&url, validators).await
+                                }
+                                None => self.transport.fetch_response(&url).await,
+                            };
+                            match res {
+                                Err(HttpError::NotModified) => Ok(None),
+                                res => res.and_then(HttpResponse::json).map(Some),
+                            }
+                        })
+                        .await
+                };
+                #[cfg(feature = "tracing")]
+                let fetch = tracing::Instrument::instrument(fetch, span);
+                fetch
+            })
+            .await;
+        fetched.map_err(fetch_error)
+    }
+
+    /// The latest beacon, asked for only if it's changed since the last one if the client was
+    /// built with `conditional_latest`.
+    async fn fetch_latest(&self) -> Result<FetchedBeacon<B>, DrandClientError> {
+        let Some(latest) = &self.latest else {
+            return self.fetch_beacon_tag("latest").await;
+        };
+        let previous = latest.get();
+        let validators = previous.as_ref().map(|(_, validators)| validators);
+        let downloaded = match self.download_if_modified("latest", validators).await {
+            Ok(Some(res)) => Ok(res),
+            Ok(None) => match previous {
+                // unchanged since the last beacon, which was verified when it was fetched
+                Some((beacon, _)) => {
+                    self.metrics.on_cache_hit();
+                    return Ok(FetchedBeacon {
+                        beacon,
+                        valid_until: None,
+                    });
+                }
+                // a relay answering a request that wasn't conditional with nothing
+                None => Err(DrandClientError::NotResponding(HttpError::NotModified)),
+            },
+            Err(e) => Err(e),
+        };
+        let validators = downloaded.as_ref().map(Validators::of).unwrap_or_default();
+        let fetched = self
+            .parse_downloaded("latest", downloaded)
+            .and_then(|fetched| self.verify_fetched(fetched))?;
+        latest.set(fetched.beacon.clone(), validators);
+        Ok(fetched)
+    }
+
+    /// Parses a beacon from [`Self::download`], reporting either's failure to the metrics.
+    fn parse_downloaded(
+        &self,
+        tag: &str,
+        downloaded: Result<HttpResponse, DrandClientError>,
+    ) -> Result<FetchedBeacon<B>, DrandClientError> {
+        let result = downloaded.and_then(|res| parse_beacon(tag, res));
+        if let Err(e) = &result {
+            self.metrics.on_fetch_error(e);
+        }
+        result
+    }
+
+    /// [`Self::fetch_beacon_tag`], timing each step.
+    async fn fetch_timed(&self, tag: &str) -> Result<VerifiedBeacon<B>, DrandClientError> {
+        let started = timer::now();
+        let downloaded = self.download(tag).await;
+        let fetch_time = timer::elapsed(started);
+        let started = timer::now();
+        let parsed = self.parse_downloaded(tag, downloaded)?;
+        let parse_time = timer::elapsed(started);
+        let started = timer::now();
+        let verified = self.verify_fetched(parsed)?;
+        let verify_time = timer::elapsed(started);
+        Ok(VerifiedBeacon {
+            beacon: verified.beacon,
+            fetch_time,
+            parse_time,
+            verify_time,
+        })
+    }
+
+    /// Verifies a beacon from [`Self::fetch_unverified`], reporting the outcome to the metrics.
+    fn verify_fetched(
+        &self,
+        fetched: FetchedBeacon<B>,
+    ) -> Result<FetchedBeacon<B>, DrandClientError> {
+        if let Some(tolerance) = self.future_round_tolerance {
+            let round = fetched.beacon.round_number();
+            let checked =
+                reject_future_round(&self.chain_info, round, self.clock.now_unix(), tolerance);
+            if let Err(e) = checked {
+                self.metrics.on_fetch_error(&e);
+                return Err(e);
+            }
+        }
+        let started = timer::now();
+        let verified = self.scheme.verify(&self.chain_info, fetched.beacon);
+        self.metrics.on_verify_duration(timer::elapsed(started));
+        let result = verified
+            .map(|beacon| FetchedBeacon {
+                beacon,
+                valid_until: fetched.valid_until,
+            })
+            .map_err(|e| {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(
+                    scheme = %self.chain_info.scheme_id,
+                    error = %e,
+                    "beacon failed verification"
+                );
+                DrandClientError::InvalidBeacon {
+                    reason: e.to_string(),
+                }
+            });
+        match &result {
+            Ok(_) => self.metrics.on_fetch_success(),
+            Err(e) => self.metrics.on_fetch_error(e),
+        }
+        result
+    }
+}
+
+impl<B, T> AsyncDrandClient<B, T>
+where
+    B: DeserializeOwned + Clone + Beacon,
+    T: AsyncTransport,
+{
+    /// Fetches and verifies every round from `start` to `end` inclusive, with up to
+    /// `RANGE_CONCURRENCY` requests in flight. Results are returned in round order, and a
+    /// failure to fetch one round doesn't prevent the others being returned. Ranges of more than
+    /// `MAX_RANGE_LEN` rounds are rejected as [`InvalidRound`].
+    pub async fn randomness_range(
+        &self,
+        start: u64,
+        end: u64,
+    ) -> Result<Vec<Result<B, DrandClientError>>, DrandClientError> {
+        if start == 0 || start > end || end - start >= MAX_RANGE_LEN {
+            return Err(InvalidRound);
+        }
+        let results = stream::iter(start..=end)
+            .map(|round| self.randomness(round))
+            .buffered(RANGE_CONCURRENCY)
+            .collect()
+            .await;
+        Ok(results)
+    }
+
+    /// The latest `n` beacons, newest first, fetching the rounds before the latest one as
+    /// [`Self::randomness_range`] does. Fewer are returned if the chain hasn't emitted `n` yet.
+    pub async fn latest_n(&self, n: u64) -> Result<Vec<B>, DrandClientError> {
+        if n == 0 {
+            return Ok(vec![]);
+        }
+        let latest = self.latest_randomness().await?;
+        let end = latest.round_number();
+        let start = end.saturating_sub(n - 1).max(1);
+        let mut beacons = if start < end {
+            self.randomness_range(start, end - 1)
+                .await?
+                .into_iter()
+                .collect::<Result<Vec<_>, _>>()?
+        } else {
+            vec![]
+        };
+        beacons.push(latest);
+        beacons.reverse();
+        Ok(beacons)
+    }
+}
+
+impl<B, T> AsyncDrandClient<B, T>
+where
+    B: DeserializeOwned + Clone + Beacon,
+    T: AsyncTransport,
+{
+    /// Yields each new beacon as it is emitted, starting with the latest one.
+    pub fn watch(&self) -> impl Stream<Item = Result<B, DrandClientError>> + '_ {
+        stream::unfold(None, move |next_round| async move {
+            let result = match next_round {
+                None => self.latest_randomness().await,
+                Some(round) => self.await_round(round).await,
+            };
+            let next_round = match &result {
+                Ok(beacon) => Some(beacon.round_number() + 1),
+                Err(_) => next_round,
+            };
+            Some((result, next_round))
+        })
+    }
+
+    /// Calls `f` with each new beacon as it's emitted, starting with the latest one, or with why a
+    /// round couldn't be fetched, until `f` breaks, returning what it broke with. See
+    /// [`DrandClient::watch_with`].
+    ///
+    /// [`DrandClient::watch_with`]: crate::DrandClient::watch_with
+    pub async fn watch_with<R>(
+        &self,
+        mut f: impl FnMut(Result<B, DrandClientError>) -> ControlFlow<R>,
+    ) -> R {
+        let mut watch = pin!(self.watch());
+        loop {
+            let result = watch.next().await.expect("watches never end");
+            if let ControlFlow::Break(value) = f(result) {
+                return value;
+            }
+        }
+    }
+
+    async fn await_round(&self, round: u64) -> Result<B, DrandClientError> {
+        if let Ok(emitted_at) = Round::try_from(round).and_then(|round| self.time_of_round(round)) {
+            timer::sleep(delay_until(&*self.clock, emitted_at)).await;
+        }
+
+        let mut attempt = 1;
+        loop {
+            match self.randomness(round).await {
+                Ok(beacon) if beacon.round_number() == round => return Ok(beacon),
+                // the local clock has drifted from the relay's, so resynchronise from its latest round
+                Ok(_) => return self.latest_randomness().await,
+                Err(e) if attempt >= MAX_ATTEMPTS_PER_ROUND => return Err(e),
+                Err(_) => {
+                    timer::sleep(RETRY_INTERVAL).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::async_client::{
+        new_chained_client, new_client_with_transport, new_unchained_client,
+    };
+    use crate::DrandClientError::InvalidRound;
+    use crate::{fixtures, DrandClientError, MockTransport, UnchainedScheme};
+    use std::ops::ControlFlow;
+
+    #[tokio::test]
+    async fn request_chained_randomness_success() -> Result<(), DrandClientError> {
+        let chained_url = "https://api.drand.sh";
+        let client = new_chained_client(chained_url).await?;
+        let randomness = client.latest_randomness().await?;
+        assert!(randomness.round_number > 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn request_unchained_randomness_success() -> Result<(), DrandClientError> {
+        let unchained_url = "https://pl-eu.testnet.drand.sh/7672797f548f3f4748ac4bf3352fc6c6b6468c9ad40ad456a397545c6e2df5bf";
+        let client = new_unchained_client(unchained_url).await?;
+        let randomness = client.latest_randomness().await?;
+        assert!(randomness.round_number > 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn request_genesis_returns_error() -> Result<(), DrandClientError> {
+        let chained_url = "https://api.drand.sh";
+        let client = new_chained_client(chained_url).await?;
+        let result = client.randomness(0).await;
+        assert!(result.is_err());
+        assert!(matches!(result, Err(InvalidRound)));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn mock_relay_serves_verified_beacon() {
+        let info = fixtures::chain_info("pedersen-bls-unchained");
+        let beacon = fixtures::unchained_beacon(3);
+        let transport = MockTransport::new()
+            .with_body("http://relay/info", &fixtures::chain_info_json(&info))
+            .with_body(
+                "http://relay/public/3",
+                &fixtures::unchained_beacon_json(&beacon),
+            );
+        let client = new_client_with_transport(&UnchainedScheme {}, "http://relay", transport)
+            .await
+            .unwrap();
+        assert_eq!(client.randomness(3).await.unwrap(), beacon);
+    }
+
+    #[tokio::test]
+    async fn mock_relay_serving_another_round_is_rejected() {
+        let info = fixtures::chain_info("pedersen-bls-unchained");
+        let transport = MockTransport::new()
+            .with_body("http://relay/info", &fixtures::chain_info_json(&info))
+            .with_body(
+                "http://relay/public/3",
+                &fixtures::unchained_beacon_json(&fixtures::unchained_beacon(9)),
+            );
+        let client = new_client_with_transport(&UnchainedScheme {}, "http://relay", transport)
+            .await
+            .unwrap();
+        assert!(matches!(
+            client.randomness(3).await,
+            Err(DrandClientError::InvalidBeacon { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn watch_with_calls_back_until_told_to_stop() {
+        let info = fixtures::chain_info("pedersen-bls-unchained");
+        let transport = MockTransport::new()
+            .with_body("http://relay/info", &fixtures::chain_info_json(&info))
+            .with_body(
+                "http://relay/public/latest",
+                &fixtures::unchained_beacon_json(&fixtures::unchained_beacon(3)),
+            )
+            .with_body(
+                "http://relay/public/4",
+                &fixtures::unchained_beacon_json(&fixtures::unchained_beacon(4)),
+            );
+        let client = new_client_with_transport(&UnchainedScheme {}, "http://relay", transport)
+            .await
+            .unwrap();
+        let mut rounds = vec![];
+        let last = client
+            .watch_with(|result| {
+                let round = result.unwrap().round_number;
+                rounds.push(round);
+                if round == 4 {
+                    ControlFlow::Break(round)
+                } else {
+                    ControlFlow::Continue(())
+                }
+            })
+            .await;
+        assert_eq!((rounds, last), (vec![3, 4], 4));
+    }
+}
@@ -0,0 +1,56 @@
+use crate::chained::{ChainedBeacon, ChainedScheme};
+use crate::unchained::{UnchainedBeacon, UnchainedScheme};
+#[cfg(feature = "g1")]
+use crate::unchained_g1::{UnchainedSchemeOnG1, UnchainedSchemeOnG1Rfc9380};
+use crate::Scheme;
+
+const CHAINED_SCHEMES: &[&dyn Scheme<ChainedBeacon>] = &[&ChainedScheme {}];
+
+const UNCHAINED_SCHEMES: &[&dyn Scheme<UnchainedBeacon>] = &[
+    &UnchainedScheme {},
+    #[cfg(feature = "g1")]
+    &UnchainedSchemeOnG1 {},
+    #[cfg(feature = "g1")]
+    &UnchainedSchemeOnG1Rfc9380 {},
+];
+
+pub(crate) fn chained_scheme_for(scheme_id: &str) -> Option<&'static dyn Scheme<ChainedBeacon>> {
+    CHAINED_SCHEMES
+        .iter()
+        .copied()
+        .find(|scheme| scheme.supports(scheme_id))
+}
+
+pub(crate) fn unchained_scheme_for(
+    scheme_id: &str,
+) -> Option<&'static dyn Scheme<UnchainedBeacon>> {
+    UNCHAINED_SCHEMES
+        .iter()
+        .copied()
+        .find(|scheme| scheme.supports(scheme_id))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::auto::{chained_scheme_for, unchained_scheme_for};
+
+    #[test]
+    fn every_known_scheme_id_is_registered() {
+        assert!(chained_scheme_for("pedersen-bls-chained").is_some());
+        assert!(unchained_scheme_for("pedersen-bls-unchained").is_some());
+    }
+
+    #[test]
+    #[cfg(feature = "g1")]
+    fn g1_scheme_ids_are_registered() {
+        assert!(unchained_scheme_for("bls-unchained-on-g1").is_some());
+        assert!(unchained_scheme_for("bls-unchained-g1-rfc9380").is_some());
+    }
+
+    #[test]
+    fn unknown_scheme_id_is_not_registered() {
+        assert!(chained_scheme_for("bls-unchained-g1-rfc9380").is_none());
+        assert!(unchained_scheme_for("pedersen-bls-chained").is_none());
+        assert!(unchained_scheme_for("not-a-scheme").is_none());
+    }
+}
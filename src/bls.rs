@@ -1,28 +1,805 @@
+//! The BLS signature verification underlying the schemes, for reuse by related protocols.
+
+// each scheme uses its own subset of these primitives, so which go unused depends on the features
+#![cfg_attr(
+    not(all(feature = "chained", feature = "g1", feature = "tlock")),
+    allow(dead_code, unused_imports)
+)]
+
 use crate::chain_info::ChainInfo;
 use crate::SchemeError;
-use bls_signatures::{hash, verify, PublicKey, Serialize, Signature};
+use alloc::vec::Vec;
+use bls12_381::hash_to_curve::{ExpandMsgXmd, HashToCurve};
+use bls12_381::{
+    multi_miller_loop, G1Affine, G1Projective, G2Affine, G2Prepared, G2Projective, Gt, Scalar,
+};
+use core::fmt;
+#[cfg(feature = "std")]
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+
+/// The domain separation tag drand uses when hashing messages onto G2.
+/// The original `bls-unchained-on-g1` scheme also (incorrectly) used it when hashing onto G1.
+pub const G2_DOMAIN: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_NUL_";
+
+/// The RFC 9380 domain separation tag for hashing messages onto G1, used by `bls-unchained-g1-rfc9380`.
+pub const G1_DOMAIN: &[u8] = b"BLS_SIG_BLS12381G1_XMD:SHA-256_SSWU_RO_NUL_";
+
+/// The group a scheme's signatures are on. Its group public key is on the other one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Group {
+    G1,
+    G2,
+}
+
+/// How a scheme hashes its messages onto the curve. Every drand scheme hashes with RFC 9380's
+/// `hash_to_curve` using `expand_message_xmd` over SHA-256, so they differ only in the group and
+/// the domain separation tag; a new scheme is a new pair of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ciphersuite<'a> {
+    pub signature_group: Group,
+    pub dst: &'a [u8],
+}
+
+/// The ciphersuite of each drand scheme id, or `None` for ids drand doesn't define.
+pub fn ciphersuite(scheme_id: &str) -> Option<Ciphersuite<'static>> {
+    let (signature_group, dst) = if scheme_id.eq_ignore_ascii_case("pedersen-bls-chained")
+        || scheme_id.eq_ignore_ascii_case("pedersen-bls-unchained")
+    {
+        (Group::G2, G2_DOMAIN)
+    } else if scheme_id.eq_ignore_ascii_case("bls-unchained-on-g1") {
+        // the original G1 scheme kept the G2 tag, which drand since fixed in rfc9380
+        (Group::G1, G2_DOMAIN)
+    } else if scheme_id.eq_ignore_ascii_case("bls-unchained-g1-rfc9380") {
+        (Group::G1, G1_DOMAIN)
+    } else {
+        return None;
+    };
+    Some(Ciphersuite {
+        signature_group,
+        dst,
+    })
+}
+
+/// Why some bytes aren't the point on the curve they're meant to be, e.g. a public key or signature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointError {
+    /// Not as long as a compressed or uncompressed point on the expected group.
+    WrongLength { found: usize },
+    /// Not a canonical encoding of a point on the curve.
+    NotOnCurve,
+    /// On the curve, but outside the prime-order subgroup.
+    NotInSubgroup,
+    /// The identity, which no honest key or signature is.
+    Identity,
+}
+
+impl fmt::Display for PointError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PointError::WrongLength { found } => {
+                write!(f, "wrong length for a point: {} bytes", found)
+            }
+            PointError::NotOnCurve => f.write_str("not on the curve"),
+            PointError::NotInSubgroup => f.write_str("not in the prime-order subgroup"),
+            PointError::Identity => f.write_str("the identity"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PointError {}
 
 pub trait BlsVerifiable {
     fn signature(&self) -> &Vec<u8>;
+    fn randomness(&self) -> &Vec<u8>;
     fn to_message(&self) -> Result<Vec<u8>, SchemeError>;
 }
 
-pub(crate) fn bls_verify<B: BlsVerifiable>(info: &ChainInfo, beacon: B) -> Result<B, SchemeError> {
-    let public_key = PublicKey::from_bytes(info.public_key.as_slice())
-        .map_err(|_| SchemeError::InvalidChainInfo)?;
+/// Verifies a beacon hashed onto the curve as `suite` says.
+pub(crate) fn bls_verify_with<B: BlsVerifiable>(
+    info: &ChainInfo,
+    beacon: B,
+    suite: Ciphersuite,
+) -> Result<B, SchemeError> {
+    match suite.signature_group {
+        Group::G1 => bls_verify_on_g1(info, beacon, suite.dst),
+        Group::G2 => bls_verify(info, beacon, suite.dst),
+    }
+}
 
-    let signature = Signature::from_bytes(beacon.signature().as_slice())
-        .map_err(|_| SchemeError::InvalidBeacon)?;
+/// Verifies a beacon as the scheme `scheme_id` says to, which the caller has checked the chain uses.
+pub(crate) fn bls_verify_scheme<B: BlsVerifiable>(
+    scheme_id: &str,
+    info: &ChainInfo,
+    beacon: B,
+) -> Result<B, SchemeError> {
+    let suite = ciphersuite(scheme_id).ok_or(SchemeError::InvalidScheme)?;
+    bls_verify_with(info, beacon, suite)
+}
 
-    let bls_message_bytes = beacon
-        .to_message()
-        .map(|bytes| sha256::digest(bytes.as_slice()))
-        .and_then(|hex_str| hex::decode(hex_str).map_err(|_| SchemeError::InvalidBeacon))?;
+/// Verifies a beacon whose signature lives on G2, with the group public key on G1.
+pub(crate) fn bls_verify<B: BlsVerifiable>(
+    info: &ChainInfo,
+    beacon: B,
+    domain: &[u8],
+) -> Result<B, SchemeError> {
+    verify_randomness(&beacon)?;
+    let public_key = match info.public_key_point() {
+        Ok(point @ PublicKeyPoint::G1(_)) => point,
+        Ok(PublicKeyPoint::G2(_)) => return Err(SchemeError::InvalidChainInfo),
+        Err(e) => return Err(SchemeError::InvalidPublicKey(e)),
+    };
+    verify_with_point(
+        public_key,
+        &message_digest(&beacon)?,
+        beacon.signature(),
+        domain,
+    )?;
+    Ok(beacon)
+}
+
+/// Verifies many beacons whose signatures live on G2 in one multi-pairing, checking that
+/// `e(pk, sum(r_i * H(m_i))) == e(g1, sum(r_i * sig_i))` for fresh random scalars `r_i`. The
+/// scalars mean invalid signatures can't be chosen to cancel each other out, so the check fails
+/// if any one of them would have failed on its own, besides with negligible probability.
+#[cfg(feature = "std")]
+pub(crate) fn bls_verify_batch<B: BlsVerifiable>(
+    info: &ChainInfo,
+    beacons: &[B],
+) -> Result<(), SchemeError> {
+    let public_key = match info.public_key_point() {
+        Ok(PublicKeyPoint::G1(point)) => point,
+        Ok(PublicKeyPoint::G2(_)) => return Err(SchemeError::InvalidChainInfo),
+        Err(e) => return Err(SchemeError::InvalidPublicKey(e)),
+    };
+
+    let mut rng = rand::thread_rng();
+    let mut messages = G2Projective::identity();
+    let mut signatures = G2Projective::identity();
+    for beacon in beacons {
+        verify_randomness(beacon)?;
+        let signature = signature_on_g2(beacon.signature())?;
+        let message = hash_to_g2(&message_digest(beacon)?, G2_DOMAIN);
+
+        let mut wide = [0u8; 64];
+        rng.fill_bytes(&mut wide);
+        let r = Scalar::from_bytes_wide(&wide);
+        messages += message * r;
+        signatures += signature * r;
+    }
+
+    let pairing = multi_miller_loop(&[
+        (&public_key, &G2Prepared::from(G2Affine::from(messages))),
+        (
+            &-G1Affine::generator(),
+            &G2Prepared::from(G2Affine::from(signatures)),
+        ),
+    ])
+    .final_exponentiation();
+
+    if pairing != Gt::identity() {
+        Err(SchemeError::InvalidBeacon)
+    } else {
+        Ok(())
+    }
+}
+
+/// Verifies a beacon whose signature lives on G1, with the group public key on G2.
+pub(crate) fn bls_verify_on_g1<B: BlsVerifiable>(
+    info: &ChainInfo,
+    beacon: B,
+    domain: &[u8],
+) -> Result<B, SchemeError> {
+    verify_randomness(&beacon)?;
+    let public_key = match info.public_key_point() {
+        Ok(point @ PublicKeyPoint::G2(_)) => point,
+        Ok(PublicKeyPoint::G1(_)) => return Err(SchemeError::InvalidChainInfo),
+        Err(e) => return Err(SchemeError::InvalidPublicKey(e)),
+    };
+    verify_with_point(
+        public_key,
+        &message_digest(&beacon)?,
+        beacon.signature(),
+        domain,
+    )?;
+    Ok(beacon)
+}
 
-    let point_on_curve = hash(bls_message_bytes.as_slice());
-    if !verify(&signature, &[point_on_curve], &[public_key]) {
+/// Verifies a BLS signature by a drand group's public key over an arbitrary `message`, e.g. for
+/// protocols built on a drand network. The key and signature are compressed points, with the
+/// signature on the other group to the key's; `message` is hashed onto that group with `dst`,
+/// usually [`G2_DOMAIN`] or [`G1_DOMAIN`]. Beacons sign the SHA-256 digest of their round message.
+///
+/// Fails with [`SchemeError::InvalidPublicKey`] or [`SchemeError::InvalidSignature`] saying
+/// what's wrong if either isn't a valid point, and with [`SchemeError::InvalidBeacon`] if the
+/// signature doesn't verify.
+pub fn verify_signature(
+    public_key: &[u8],
+    message: &[u8],
+    signature: &[u8],
+    dst: &[u8],
+) -> Result<(), SchemeError> {
+    let public_key = parse_public_key(public_key).map_err(SchemeError::InvalidPublicKey)?;
+    verify_with_point(public_key, message, signature, dst)
+}
+
+/// The length of the share index drand prefixes partial signatures with.
+const SHARE_INDEX_LEN: usize = 2;
+
+/// Verifies one node's partial signature for `round` against that node's public key share, e.g.
+/// to find which node's share is at fault when a beacon fails to aggregate. `partial_signature`
+/// is as drand nodes exchange it, the node's big-endian `u16` share index then its signature.
+/// `previous_signature` is the previous round's signature on chained networks, and empty on
+/// unchained ones. Returns the share index.
+pub fn verify_partial(
+    public_key_share: &[u8],
+    round: u64,
+    previous_signature: &[u8],
+    partial_signature: &[u8],
+    dst: &[u8],
+) -> Result<u16, SchemeError> {
+    if partial_signature.len() < SHARE_INDEX_LEN {
+        return Err(SchemeError::InvalidBeacon);
+    }
+    let (index, signature) = partial_signature.split_at(SHARE_INDEX_LEN);
+    let mut message = previous_signature.to_vec();
+    message.extend_from_slice(&round.to_be_bytes());
+    verify_signature(public_key_share, &Sha256::digest(&message), signature, dst)?;
+    Ok(u16::from_be_bytes([index[0], index[1]]))
+}
+
+fn verify_with_point(
+    public_key: PublicKeyPoint,
+    message: &[u8],
+    signature: &[u8],
+    dst: &[u8],
+) -> Result<(), SchemeError> {
+    let pairing = match public_key {
+        PublicKeyPoint::G1(public_key) => {
+            let signature = signature_on_g2(signature)?;
+            let point_on_curve = hash_to_g2(message, dst);
+            multi_miller_loop(&[
+                (&-G1Affine::generator(), &G2Prepared::from(signature)),
+                (&public_key, &G2Prepared::from(point_on_curve)),
+            ])
+        }
+        PublicKeyPoint::G2(public_key) => {
+            let signature = signature_on_g1(signature)?;
+            let point_on_curve = hash_to_g1(message, dst);
+            multi_miller_loop(&[
+                (&signature, &G2Prepared::from(-G2Affine::generator())),
+                (&point_on_curve, &G2Prepared::from(public_key)),
+            ])
+        }
+    }
+    .final_exponentiation();
+
+    if pairing != Gt::identity() {
         Err(SchemeError::InvalidBeacon)
     } else {
-        Ok(beacon)
+        Ok(())
+    }
+}
+
+fn verify_randomness<B: BlsVerifiable>(beacon: &B) -> Result<(), SchemeError> {
+    // drand's randomness is the digest of the compressed signature, however it was served
+    let signature = beacon.signature();
+    let expected_randomness = if is_compressed(signature) {
+        Sha256::digest(signature)
+    } else {
+        let compressed = reencode_point(signature, PointEncoding::Compressed)
+            .map_err(SchemeError::InvalidSignature)?;
+        Sha256::digest(&compressed)
+    };
+    // in constant time, so how much of the randomness matched can't be timed
+    if !bool::from(beacon.randomness().ct_eq(&expected_randomness)) {
+        Err(SchemeError::InvalidBeacon)
+    } else {
+        Ok(())
+    }
+}
+
+/// The digest of an unchained round's message, which is what gets hashed onto the curve.
+pub(crate) fn round_digest(round_number: u64) -> Vec<u8> {
+    Sha256::digest(&round_number.to_be_bytes()).to_vec()
+}
+
+pub(crate) fn hash_to_g1(message: &[u8], domain: &[u8]) -> G1Affine {
+    G1Affine::from(
+        <G1Projective as HashToCurve<ExpandMsgXmd<Sha256>>>::hash_to_curve(message, domain),
+    )
+}
+
+pub(crate) fn hash_to_g2(message: &[u8], domain: &[u8]) -> G2Affine {
+    G2Affine::from(
+        <G2Projective as HashToCurve<ExpandMsgXmd<Sha256>>>::hash_to_curve(message, domain),
+    )
+}
+
+fn message_digest<B: BlsVerifiable>(beacon: &B) -> Result<Vec<u8>, SchemeError> {
+    beacon
+        .to_message()
+        .map(|bytes| Sha256::digest(&bytes).to_vec())
+}
+
+/// A group public key, decoded once so verifying many beacons doesn't decompress it each time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum PublicKeyPoint {
+    G1(G1Affine),
+    G2(G2Affine),
+}
+
+/// Parses a public key, on G1 or G2, that's on the curve and in the prime-order subgroup. The
+/// identity is rejected too, as every signature would verify against it.
+pub(crate) fn parse_public_key(bytes: &[u8]) -> Result<PublicKeyPoint, PointError> {
+    match decode_point(bytes)? {
+        PublicKeyPoint::G1(point) => not_identity_g1(point).map(PublicKeyPoint::G1),
+        PublicKeyPoint::G2(point) => not_identity_g2(point).map(PublicKeyPoint::G2),
+    }
+}
+
+/// Decodes a compressed or uncompressed point on G1 or G2, going by its length and, as a
+/// compressed point on G2 is as long as an uncompressed one on G1, its compression flag.
+fn decode_point(bytes: &[u8]) -> Result<PublicKeyPoint, PointError> {
+    match bytes.len() {
+        G1_COMPRESSED_LEN => decode_g1(bytes).map(PublicKeyPoint::G1),
+        G2_COMPRESSED_LEN if is_compressed(bytes) => decode_g2(bytes).map(PublicKeyPoint::G2),
+        G1_UNCOMPRESSED_LEN => decode_g1(bytes).map(PublicKeyPoint::G1),
+        G2_UNCOMPRESSED_LEN => decode_g2(bytes).map(PublicKeyPoint::G2),
+        found => Err(PointError::WrongLength { found }),
+    }
+}
+
+/// How a point is serialised. drand serves compressed points, but some tooling stores them
+/// uncompressed; both are accepted wherever a point is decoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointEncoding {
+    Compressed,
+    Uncompressed,
+}
+
+/// Re-encodes a point on G1 or G2, e.g. a public key or signature in either encoding, as
+/// `encoding`, for comparing points from tooling that disagrees on it.
+pub fn reencode_point(bytes: &[u8], encoding: PointEncoding) -> Result<Vec<u8>, PointError> {
+    Ok(match (decode_point(bytes)?, encoding) {
+        (PublicKeyPoint::G1(point), PointEncoding::Compressed) => point.to_compressed().to_vec(),
+        (PublicKeyPoint::G1(point), PointEncoding::Uncompressed) => {
+            point.to_uncompressed().to_vec()
+        }
+        (PublicKeyPoint::G2(point), PointEncoding::Compressed) => point.to_compressed().to_vec(),
+        (PublicKeyPoint::G2(point), PointEncoding::Uncompressed) => {
+            point.to_uncompressed().to_vec()
+        }
+    })
+}
+
+/// `bytes` in the compressed encoding drand hashes and compares points in, if they're an
+/// uncompressed point; anything else is left as it is, to be rejected wherever it's verified.
+pub(crate) fn compressed_point(bytes: &[u8]) -> Vec<u8> {
+    if is_compressed(bytes) {
+        return bytes.to_vec();
+    }
+    reencode_point(bytes, PointEncoding::Compressed).unwrap_or_else(|_| bytes.to_vec())
+}
+
+/// Parses a signature before any pairing is attempted: it must be a canonical compressed point of
+/// the right length for its group, on the curve and in the prime-order subgroup. The identity is
+/// rejected too, as no honest group signs it.
+fn signature_on_g1(bytes: &[u8]) -> Result<G1Affine, SchemeError> {
+    decode_g1(bytes)
+        .and_then(not_identity_g1)
+        .map_err(SchemeError::InvalidSignature)
+}
+
+fn signature_on_g2(bytes: &[u8]) -> Result<G2Affine, SchemeError> {
+    decode_g2(bytes)
+        .and_then(not_identity_g2)
+        .map_err(SchemeError::InvalidSignature)
+}
+
+fn not_identity_g1(point: G1Affine) -> Result<G1Affine, PointError> {
+    if bool::from(point.is_identity()) {
+        Err(PointError::Identity)
+    } else {
+        Ok(point)
+    }
+}
+
+fn not_identity_g2(point: G2Affine) -> Result<G2Affine, PointError> {
+    if bool::from(point.is_identity()) {
+        Err(PointError::Identity)
+    } else {
+        Ok(point)
+    }
+}
+
+const G1_COMPRESSED_LEN: usize = 48;
+const G1_UNCOMPRESSED_LEN: usize = 96;
+const G2_COMPRESSED_LEN: usize = 96;
+const G2_UNCOMPRESSED_LEN: usize = 192;
+
+// the most significant bit of either encoding's first byte
+fn is_compressed(bytes: &[u8]) -> bool {
+    bytes.first().is_some_and(|byte| byte & 0x80 != 0)
+}
+
+/// Decodes a compressed or uncompressed point on G1 in the prime-order subgroup, saying why if it
+/// isn't one. A compressed point on G2 is the wrong length, though as long as an uncompressed one.
+pub(crate) fn decode_g1(bytes: &[u8]) -> Result<G1Affine, PointError> {
+    let point: Option<G1Affine> = match bytes.len() {
+        G1_COMPRESSED_LEN => {
+            G1Affine::from_compressed_unchecked(bytes.try_into().unwrap_or(&[0; 48])).into()
+        }
+        G1_UNCOMPRESSED_LEN if !is_compressed(bytes) => {
+            G1Affine::from_uncompressed_unchecked(bytes.try_into().unwrap_or(&[0; 96])).into()
+        }
+        found => return Err(PointError::WrongLength { found }),
+    };
+    // decompressing only finds points on the curve, but uncompressed ones are taken as they are
+    let point = point
+        .filter(|point| bool::from(point.is_on_curve()))
+        .ok_or(PointError::NotOnCurve)?;
+    if bool::from(point.is_torsion_free()) {
+        Ok(point)
+    } else {
+        Err(PointError::NotInSubgroup)
+    }
+}
+
+/// Decodes a compressed or uncompressed point on G2 in the prime-order subgroup, saying why if it
+/// isn't one.
+pub(crate) fn decode_g2(bytes: &[u8]) -> Result<G2Affine, PointError> {
+    let point: Option<G2Affine> = match bytes.len() {
+        G2_COMPRESSED_LEN => {
+            G2Affine::from_compressed_unchecked(bytes.try_into().unwrap_or(&[0; 96])).into()
+        }
+        G2_UNCOMPRESSED_LEN => {
+            G2Affine::from_uncompressed_unchecked(bytes.try_into().unwrap_or(&[0; 192])).into()
+        }
+        found => return Err(PointError::WrongLength { found }),
+    };
+    let point = point
+        .filter(|point| bool::from(point.is_on_curve()))
+        .ok_or(PointError::NotOnCurve)?;
+    if bool::from(point.is_torsion_free()) {
+        Ok(point)
+    } else {
+        Err(PointError::NotInSubgroup)
+    }
+}
+
+pub(crate) fn g1_from_slice(bytes: &[u8]) -> Option<G1Affine> {
+    decode_g1(bytes).ok()
+}
+
+pub(crate) fn g2_from_slice(bytes: &[u8]) -> Option<G2Affine> {
+    decode_g2(bytes).ok()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::bls::{
+        bls_verify_with, ciphersuite, parse_public_key, reencode_point, round_digest,
+        verify_partial, verify_signature, BlsVerifiable, Group, PointEncoding, PointError,
+        PublicKeyPoint, G1_DOMAIN, G2_DOMAIN,
+    };
+    use crate::fixtures;
+    use crate::{
+        ChainInfo, ChainedScheme, Scheme, SchemeError, UnchainedBeacon, UnchainedScheme,
+        UnchainedSchemeOnG1, UnchainedSchemeOnG1Rfc9380,
+    };
+    use bls12_381::{G1Affine, G2Affine};
+    use sha2::{Digest, Sha256};
+
+    /// A beacon for round 2 with `signature`, and the randomness that goes with it, so only the
+    /// signature itself can fail verification.
+    fn beacon_signed(signature: Vec<u8>) -> UnchainedBeacon {
+        UnchainedBeacon {
+            round_number: 2,
+            randomness: Sha256::digest(&signature).to_vec(),
+            signature,
+        }
+    }
+
+    #[test]
+    fn every_drand_scheme_id_has_a_ciphersuite() {
+        for (scheme_id, group, dst) in [
+            ("pedersen-bls-chained", Group::G2, G2_DOMAIN),
+            ("pedersen-bls-unchained", Group::G2, G2_DOMAIN),
+            ("bls-unchained-on-g1", Group::G1, G2_DOMAIN),
+            ("BLS-UNCHAINED-G1-RFC9380", Group::G1, G1_DOMAIN),
+        ] {
+            let suite = ciphersuite(scheme_id).unwrap();
+            assert_eq!(suite.signature_group, group);
+            assert_eq!(suite.dst, dst);
+        }
+        assert!(ciphersuite("bls-unchained-on-g3").is_none());
+    }
+
+    #[test]
+    fn schemes_verify_only_beacons_hashed_with_their_ciphersuite() {
+        let chained = fixtures::chained_beacon(2, vec![9; 96]);
+        assert!(ChainedScheme {}
+            .verify(&fixtures::chain_info("pedersen-bls-chained"), chained)
+            .is_ok());
+        assert!(UnchainedScheme {}
+            .verify(
+                &fixtures::chain_info("pedersen-bls-unchained"),
+                fixtures::unchained_beacon(2)
+            )
+            .is_ok());
+
+        let on_g1 = fixtures::g1_chain_info("bls-unchained-on-g1");
+        let rfc9380 = fixtures::g1_chain_info("bls-unchained-g1-rfc9380");
+        let legacy_tag = fixtures::unchained_g1_beacon(2, G2_DOMAIN);
+        let rfc9380_tag = fixtures::unchained_g1_beacon(2, G1_DOMAIN);
+        assert!(UnchainedSchemeOnG1 {}
+            .verify(&on_g1, legacy_tag.clone())
+            .is_ok());
+        assert!(UnchainedSchemeOnG1 {}
+            .verify(&on_g1, rfc9380_tag.clone())
+            .is_err());
+        assert!(UnchainedSchemeOnG1Rfc9380 {}
+            .verify(&rfc9380, rfc9380_tag)
+            .is_ok());
+        assert!(UnchainedSchemeOnG1Rfc9380 {}
+            .verify(&rfc9380, legacy_tag)
+            .is_err());
+    }
+
+    #[test]
+    fn malformed_signatures_are_rejected_before_pairing() {
+        let info = fixtures::chain_info("pedersen-bls-unchained");
+        let signature = fixtures::unchained_beacon(2).signature;
+        let g1_signature = fixtures::unchained_g1_beacon(2, G1_DOMAIN).signature;
+        // an x coordinate of all ones is larger than the field modulus
+        let mut non_canonical = vec![0xff; 96];
+        non_canonical[0] = 0x9f;
+        let mut uncompressed_flag = signature.clone();
+        uncompressed_flag[0] &= 0x7f;
+
+        for (malformed, expected) in [
+            (
+                signature[..95].to_vec(),
+                PointError::WrongLength { found: 95 },
+            ),
+            (vec![], PointError::WrongLength { found: 0 }),
+            (g1_signature, PointError::WrongLength { found: 48 }),
+            (non_canonical, PointError::NotOnCurve),
+            (uncompressed_flag, PointError::NotOnCurve),
+            (
+                G2Affine::identity().to_compressed().to_vec(),
+                PointError::Identity,
+            ),
+        ] {
+            match (UnchainedScheme {}).verify(&info, beacon_signed(malformed)) {
+                Err(SchemeError::InvalidSignature(e)) => assert_eq!(e, expected),
+                _ => panic!("expected an invalid signature"),
+            }
+        }
+    }
+
+    #[test]
+    fn g2_signatures_are_rejected_on_g1_chains() {
+        let info = fixtures::g1_chain_info("bls-unchained-g1-rfc9380");
+        let g2_signature = fixtures::unchained_beacon(2).signature;
+        for (malformed, expected) in [
+            (g2_signature, PointError::WrongLength { found: 96 }),
+            (
+                G1Affine::identity().to_compressed().to_vec(),
+                PointError::Identity,
+            ),
+        ] {
+            match (UnchainedSchemeOnG1Rfc9380 {}).verify(&info, beacon_signed(malformed)) {
+                Err(SchemeError::InvalidSignature(e)) => assert_eq!(e, expected),
+                _ => panic!("expected an invalid signature"),
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_points_say_what_was_wrong() {
+        assert_eq!(
+            SchemeError::InvalidSignature(PointError::WrongLength { found: 95 }).to_string(),
+            "invalid signature: wrong length for a point: 95 bytes"
+        );
+        assert_eq!(
+            SchemeError::InvalidPublicKey(PointError::NotInSubgroup).to_string(),
+            "invalid public key: not in the prime-order subgroup"
+        );
+    }
+
+    #[test]
+    fn uncompressed_points_are_accepted_and_reencoded() {
+        let info = fixtures::chain_info("pedersen-bls-unchained");
+        let beacon = fixtures::unchained_beacon(2);
+        let uncompressed = reencode_point(&beacon.signature, PointEncoding::Uncompressed).unwrap();
+        assert_eq!(uncompressed.len(), 192);
+        let served_uncompressed = UnchainedBeacon {
+            signature: uncompressed.clone(),
+            ..beacon.clone()
+        };
+        assert!((UnchainedScheme {})
+            .verify(&info, served_uncompressed)
+            .is_ok());
+        assert_eq!(
+            reencode_point(&uncompressed, PointEncoding::Compressed).unwrap(),
+            beacon.signature
+        );
+
+        let g1_key = G1Affine::generator();
+        assert_eq!(
+            parse_public_key(&g1_key.to_uncompressed()),
+            Ok(PublicKeyPoint::G1(g1_key))
+        );
+        assert_eq!(
+            reencode_point(&g1_key.to_uncompressed(), PointEncoding::Compressed).unwrap(),
+            g1_key.to_compressed()
+        );
+        let g2_key = G2Affine::generator();
+        assert_eq!(
+            parse_public_key(&g2_key.to_uncompressed()),
+            Ok(PublicKeyPoint::G2(g2_key))
+        );
+        // uncompressed coordinates aren't checked against the curve by decoding
+        let mut off_curve = g1_key.to_uncompressed();
+        off_curve[95] ^= 1;
+        assert_eq!(parse_public_key(&off_curve), Err(PointError::NotOnCurve));
+    }
+
+    #[test]
+    fn generators_are_valid_public_keys() {
+        assert!(parse_public_key(&G1Affine::generator().to_compressed()).is_ok());
+        assert!(parse_public_key(&G2Affine::generator().to_compressed()).is_ok());
+    }
+
+    #[test]
+    fn identity_and_malformed_public_keys_are_invalid() {
+        assert_eq!(
+            parse_public_key(&G1Affine::identity().to_compressed()),
+            Err(PointError::Identity)
+        );
+        assert_eq!(
+            parse_public_key(&G2Affine::identity().to_compressed()),
+            Err(PointError::Identity)
+        );
+        assert_eq!(parse_public_key(&[0xff; 48]), Err(PointError::NotOnCurve));
+        assert_eq!(
+            parse_public_key(&[1; 32]),
+            Err(PointError::WrongLength { found: 32 })
+        );
+    }
+
+    #[test]
+    fn points_outside_the_subgroup_are_invalid() {
+        // nearly every point on the curve lies outside the prime-order subgroup, so the first
+        // x coordinate that decompresses at all gives one
+        let outside = (1..=u8::MAX)
+            .map(|x| {
+                let mut compressed = [0; 48];
+                compressed[0] = 0x80;
+                compressed[47] = x;
+                compressed
+            })
+            .find(|compressed| {
+                bool::from(G1Affine::from_compressed_unchecked(compressed).is_some())
+            })
+            .unwrap();
+        assert!(!bool::from(
+            G1Affine::from_compressed_unchecked(&outside)
+                .unwrap()
+                .is_torsion_free()
+        ));
+        assert_eq!(parse_public_key(&outside), Err(PointError::NotInSubgroup));
+    }
+
+    #[test]
+    fn partial_signatures_verify_against_their_share() {
+        let share = fixtures::chain_info("pedersen-bls-chained").public_key;
+        let previous = vec![9; 96];
+        let beacon = fixtures::chained_beacon(2, previous.clone());
+        let partial = [&[0, 3][..], &beacon.signature].concat();
+        assert_eq!(
+            verify_partial(&share, 2, &previous, &partial, G2_DOMAIN).unwrap(),
+            3
+        );
+        assert!(verify_partial(&share, 3, &previous, &partial, G2_DOMAIN).is_err());
+        assert!(verify_partial(&share, 2, &previous, &partial[..1], G2_DOMAIN).is_err());
+
+        let unchained = fixtures::unchained_beacon(2);
+        let partial = [&[0, 1][..], &unchained.signature].concat();
+        assert_eq!(
+            verify_partial(&share, 2, &[], &partial, G2_DOMAIN).unwrap(),
+            1
+        );
+    }
+
+    #[test]
+    fn signatures_verify_over_arbitrary_messages() {
+        let info = fixtures::chain_info("pedersen-bls-unchained");
+        let beacon = fixtures::unchained_beacon(2);
+        let message = round_digest(2);
+        assert!(verify_signature(&info.public_key, &message, &beacon.signature, G2_DOMAIN).is_ok());
+        assert!(verify_signature(
+            &info.public_key,
+            &round_digest(3),
+            &beacon.signature,
+            G2_DOMAIN
+        )
+        .is_err());
+
+        let g1_info = fixtures::g1_chain_info("bls-unchained-g1-rfc9380");
+        let g1_beacon = fixtures::unchained_g1_beacon(2, G1_DOMAIN);
+        assert!(verify_signature(
+            &g1_info.public_key,
+            &message,
+            &g1_beacon.signature,
+            G1_DOMAIN
+        )
+        .is_ok());
+        assert!(verify_signature(
+            &g1_info.public_key,
+            &message,
+            &g1_beacon.signature,
+            G2_DOMAIN
+        )
+        .is_err());
+    }
+
+    const SCHEME_IDS: [&str; 4] = [
+        "pedersen-bls-chained",
+        "pedersen-bls-unchained",
+        "bls-unchained-on-g1",
+        "bls-unchained-g1-rfc9380",
+    ];
+
+    fn verifies_only_under<B: BlsVerifiable + Clone>(
+        scheme_id: &str,
+        info: &ChainInfo,
+        beacon: &B,
+    ) {
+        let own = ciphersuite(scheme_id).unwrap();
+        assert!(
+            bls_verify_with(info, beacon.clone(), own).is_ok(),
+            "{} beacon failed to verify",
+            scheme_id
+        );
+        for other in SCHEME_IDS.iter().filter_map(|id| ciphersuite(id)) {
+            if other != own {
+                assert!(bls_verify_with(info, beacon.clone(), other).is_err());
+            }
+        }
+    }
+
+    #[test]
+    fn captured_beacons_verify_under_their_own_ciphersuite_only() {
+        verifies_only_under(
+            "pedersen-bls-chained",
+            &fixtures::mainnet_chain_info(),
+            &fixtures::mainnet_beacon(),
+        );
+        verifies_only_under(
+            "bls-unchained-on-g1",
+            &fixtures::fastnet_chain_info(),
+            &fixtures::fastnet_beacon(),
+        );
+        verifies_only_under(
+            "bls-unchained-g1-rfc9380",
+            &fixtures::quicknet_chain_info(),
+            &fixtures::quicknet_beacon(),
+        );
+
+        // there's no pedersen-bls-unchained vector, but it signs as mainnet does, with the
+        // unchained message the G1 networks sign; only that message tells the two apart
+        let mainnet = fixtures::mainnet_beacon();
+        let as_unchained = UnchainedBeacon {
+            round_number: mainnet.round_number,
+            randomness: mainnet.randomness,
+            signature: mainnet.signature,
+        };
+        let suite = ciphersuite("pedersen-bls-unchained").unwrap();
+        assert!(bls_verify_with(&fixtures::mainnet_chain_info(), as_unchained, suite).is_err());
     }
 }
@@ -0,0 +1,930 @@
+#[cfg(feature = "async")]
+use crate::async_client::{self, AsyncDrandClient, AsyncHttpTransport};
+use crate::chain_info::ChainInfo;
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+use crate::client::{self, DrandClient};
+use crate::clock::Clock;
+use crate::endpoints::{normalize_base_url, ApiVersion, PathTemplates};
+#[cfg(feature = "async")]
+use crate::http::AsyncTransport;
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+use crate::http::HttpTransport;
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+use crate::http::Transport;
+use crate::metrics::Metrics;
+use crate::options::{ClientOptions, SchemeChoice};
+#[cfg(all(feature = "chained", feature = "unchained"))]
+use crate::{AnyBeacon, AnyScheme};
+use crate::{DrandClientError, RateLimiter, RetryPolicy, Scheme};
+use std::net::{IpAddr, SocketAddr};
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+
+/// Configures a [`DrandClient`] or [`AsyncDrandClient`] one option at a time. Only the scheme and
+/// at least one base URL are required; `build` then fetches the chain info and validates it.
+///
+/// ```no_run
+/// use drand_client_rs::{DrandClientBuilder, RetryPolicy, UnchainedScheme};
+/// use std::time::Duration;
+///
+/// let client = DrandClientBuilder::new()
+///     .scheme(&UnchainedScheme {})
+///     .base_url("https://api.drand.sh")
+///     .base_url("https://drand.cloudflare.com")
+///     .timeout(Duration::from_secs(2))
+///     .retry(RetryPolicy {
+///         max_attempts: 3,
+///         base_delay: Duration::from_millis(200),
+///         multiplier: 2.0,
+///         max_delay: Duration::from_secs(2),
+///     })
+///     .build()?;
+/// # Ok::<(), drand_client_rs::DrandClientError>(())
+/// ```
+pub struct DrandClientBuilder<B> {
+    scheme: Option<SchemeChoice<B>>,
+    base_urls: Vec<String>,
+    options: ClientOptions,
+}
+
+impl<B> Default for DrandClientBuilder<B> {
+    fn default() -> Self {
+        DrandClientBuilder {
+            scheme: None,
+            base_urls: vec![],
+            options: ClientOptions::default(),
+        }
+    }
+}
+
+impl<B> DrandClientBuilder<B> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a relay. Calling this more than once adds failover relays, tried in order.
+    pub fn base_url(mut self, base_url: &str) -> Self {
+        self.base_urls.push(base_url.to_string());
+        self
+    }
+
+    pub fn scheme<S: Scheme<B> + 'static>(mut self, scheme: S) -> Self {
+        self.scheme = Some(SchemeChoice::Fixed(Arc::new(scheme)));
+        self
+    }
+
+    /// The timeout for each request as a whole, from connecting to reading the last of the
+    /// response, when the client creates its own transport.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.options.timeout = timeout;
+        self
+    }
+
+    /// Gives up connecting to a relay after `timeout`, when the client creates its own transport,
+    /// so a dead relay is failed over from quickly while slow responses still get the whole
+    /// `timeout`. Only the overall timeout applies by default; ignored in WASM.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.options.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Keeps at most `max` idle connections to each relay for reuse, when the client creates its
+    /// own transport. Defaults to [`DEFAULT_POOL_MAX_IDLE_PER_HOST`], no limit; ignored in WASM,
+    /// where the browser manages connections.
+    ///
+    /// [`DEFAULT_POOL_MAX_IDLE_PER_HOST`]: crate::DEFAULT_POOL_MAX_IDLE_PER_HOST
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.options.pool_max_idle_per_host = max;
+        self
+    }
+
+    /// Probes idle connections every `interval`, when the client creates its own transport, or
+    /// never with `None`. Defaults to [`DEFAULT_TCP_KEEPALIVE`]; ignored in WASM.
+    ///
+    /// [`DEFAULT_TCP_KEEPALIVE`]: crate::DEFAULT_TCP_KEEPALIVE
+    pub fn tcp_keepalive(mut self, interval: Option<Duration>) -> Self {
+        self.options.tcp_keepalive = interval;
+        self
+    }
+
+    /// Connects from `address`, when the client creates its own transport. Binding to
+    /// `Ipv6Addr::UNSPECIFIED` forces IPv6, e.g. in IPv6-only container networks. Ignored in
+    /// WASM.
+    pub fn local_address(mut self, address: IpAddr) -> Self {
+        self.options.local_address = Some(address);
+        self
+    }
+
+    /// Connects to `address` for `domain` rather than resolving it, when the client creates its
+    /// own transport, e.g. where DNS is restricted. The port comes from the base URL, not
+    /// `address`. Ignored in WASM. For a resolver of your own, build a reqwest client with it and
+    /// pass it to `build_with_transport`.
+    pub fn resolve(mut self, domain: &str, address: SocketAddr) -> Self {
+        self.options.resolve.push((domain.to_string(), address));
+        self
+    }
+
+    /// Trusts the PEM encoded root certificate `pem` as well as the system's, when the client
+    /// creates its own transport, e.g. for relays behind an internal CA. An invalid certificate
+    /// fails `build` with [`DrandClientError::InvalidConfig`]. Ignored in WASM, where the
+    /// browser decides.
+    pub fn add_root_certificate(mut self, pem: &[u8]) -> Self {
+        self.options.root_certificates.push(pem.to_vec());
+        self
+    }
+
+    /// Accepts any TLS certificate, even an expired or self-signed one or one for another host,
+    /// when the client creates its own transport. This leaves the connection to the relay open
+    /// to interception, so only use it in development. Ignored in WASM.
+    #[cfg(feature = "danger-accept-invalid-certs")]
+    pub fn danger_accept_invalid_certs(mut self, accept: bool) -> Self {
+        self.options.danger_accept_invalid_certs = accept;
+        self
+    }
+
+    /// Sends requests no faster than `rate_limiter` allows, when the client creates its own
+    /// transport, e.g. to keep a large backfill within a public relay's budget. Clients sharing
+    /// the limiter share its budget.
+    pub fn rate_limiter(mut self, rate_limiter: Arc<RateLimiter>) -> Self {
+        self.options.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Fails requests whose responses are longer than `max_body_size` bytes once decompressed with
+    /// [`HttpError::BodyTooLarge`], when the client creates its own transport. Defaults to
+    /// [`DEFAULT_MAX_BODY_SIZE`].
+    ///
+    /// [`HttpError::BodyTooLarge`]: crate::HttpError::BodyTooLarge
+    /// [`DEFAULT_MAX_BODY_SIZE`]: crate::DEFAULT_MAX_BODY_SIZE
+    pub fn max_body_size(mut self, max_body_size: usize) -> Self {
+        self.options.max_body_size = max_body_size;
+        self
+    }
+
+    pub fn retry(mut self, retry_policy: RetryPolicy) -> Self {
+        self.options.retry_policy = retry_policy;
+        self
+    }
+
+    /// Fails the build unless the relay's chain info hashes to `expected_chain_hash`.
+    pub fn expected_chain_hash(mut self, expected_chain_hash: &[u8]) -> Self {
+        self.options.expected_chain_hash = Some(expected_chain_hash.to_vec());
+        self
+    }
+
+    /// Fails the build unless the chain info's group public key is `public_key`, so beacons are
+    /// only ever verified against a key the caller trusts. Either encoding of the key matches.
+    pub fn expected_public_key(mut self, public_key: &[u8]) -> Self {
+        self.options.expected_public_key = Some(public_key.to_vec());
+        self
+    }
+
+    /// Uses `chain_info` instead of fetching it from the relay's `/info`, e.g. for chains whose
+    /// parameters are known ahead of time. It's still validated, and checked against any pins.
+    pub fn chain_info(mut self, chain_info: ChainInfo) -> Self {
+        self.options.chain_info = Some(chain_info);
+        self
+    }
+
+    /// Targets a single chain on relays serving several, routing requests via `/{chain_hash}/...`
+    /// and expecting the chain info to hash to it.
+    pub fn chain_hash(mut self, chain_hash: &str) -> Self {
+        self.options.chain_hash_path = Some(chain_hash.to_string());
+        self
+    }
+
+    /// Fetches the chain info from `info_path` and beacons from `beacon_path`, relative to each
+    /// base URL, rather than from any of drand's layouts, e.g. `rounds/{round}.json` for a static
+    /// mirror. `{tag}` or `{round}` in `beacon_path` stands for the round number or `latest`, and
+    /// `{chain_hash}` in either for the hash set with `chain_hash`.
+    pub fn path_templates(mut self, info_path: &str, beacon_path: &str) -> Self {
+        self.options.path_templates = Some(PathTemplates::new(info_path, beacon_path));
+        self
+    }
+
+    /// Sends requests with drand's `api_version` path layout, rather than the original v1 one.
+    pub fn api_version(mut self, api_version: ApiVersion) -> Self {
+        self.options.api_version = Some(api_version);
+        self
+    }
+
+    /// Works out which path layout the relays serve on construction, by asking for the v2 chain
+    /// info first and falling back to v1 if none of them answer it. With [`Self::chain_info`] set
+    /// the latest v2 beacon is asked for instead, so the info is never fetched.
+    pub fn detect_api_version(mut self) -> Self {
+        self.options.api_version = None;
+        self
+    }
+
+    /// Keeps up to `capacity` verified beacons in memory, so repeated requests for a round
+    /// don't go to the relay.
+    pub fn cache_capacity(mut self, capacity: NonZeroUsize) -> Self {
+        self.options.cache_capacity = Some(capacity);
+        self
+    }
+
+    /// Asks for `/latest` with `If-Modified-Since` and `If-None-Match` after the first time, so a
+    /// relay whose latest beacon hasn't changed answers `304 Not Modified` and the last one is
+    /// returned as it is, without being downloaded or verified again. Only relays sending
+    /// `Last-Modified` or an `ETag` are asked.
+    pub fn conditional_latest(mut self, enabled: bool) -> Self {
+        self.options.conditional_latest = enabled;
+        self
+    }
+
+    /// Rejects beacons for rounds not emitted until more than `tolerance` after the time on the
+    /// client's clock as [`DrandClientError::InvalidBeacon`], catching a relay, or a client's clock
+    /// or chain info, that's wrong about the chain's progress.
+    pub fn reject_future_rounds(mut self, tolerance: Duration) -> Self {
+        self.options.future_round_tolerance = Some(tolerance);
+        self
+    }
+
+    pub fn metrics(mut self, metrics: Arc<dyn Metrics>) -> Self {
+        self.options.metrics = metrics;
+        self
+    }
+
+    /// Reads the current time from `clock` rather than the system's, e.g. a [`MockClock`] in tests.
+    ///
+    /// [`MockClock`]: crate::MockClock
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.options.clock = clock;
+        self
+    }
+
+    #[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+    pub fn build(self) -> Result<DrandClient<B>, DrandClientError> {
+        let mut client = reqwest::blocking::Client::builder()
+            .pool_max_idle_per_host(self.options.pool_max_idle_per_host)
+            .tcp_keepalive(self.options.tcp_keepalive)
+            .local_address(self.options.local_address);
+        if let Some(timeout) = self.options.connect_timeout {
+            client = client.connect_timeout(timeout);
+        }
+        for (domain, address) in &self.options.resolve {
+            client = client.resolve(domain, *address);
+        }
+        for certificate in self.root_certificates()? {
+            client = client.add_root_certificate(certificate);
+        }
+        #[cfg(feature = "danger-accept-invalid-certs")]
+        {
+            client = client.danger_accept_invalid_certs(self.options.danger_accept_invalid_certs);
+        }
+        let client = client.build().map_err(invalid_http_config)?;
+        let mut transport = HttpTransport::with_client(client).with_timeout(self.options.timeout);
+        transport.rate_limiter = self.options.rate_limiter.clone();
+        transport.max_body_size = self.options.max_body_size;
+        self.build_with_transport(transport)
+    }
+
+    /// Makes every request through `transport`, whose own timeout applies.
+    #[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+    pub fn build_with_transport<T: Transport>(
+        self,
+        transport: T,
+    ) -> Result<DrandClient<B, T>, DrandClientError> {
+        let (scheme, base_urls, options) = self.into_parts()?;
+        client::new_client_with_transport_and_options(scheme, base_urls, transport, options)
+    }
+
+    #[cfg(feature = "async")]
+    pub async fn build_async(self) -> Result<AsyncDrandClient<B>, DrandClientError> {
+        #[cfg(not(target_arch = "wasm32"))]
+        let client = {
+            let mut client = reqwest::Client::builder()
+                .pool_max_idle_per_host(self.options.pool_max_idle_per_host)
+                .tcp_keepalive(self.options.tcp_keepalive)
+                .local_address(self.options.local_address);
+            if let Some(timeout) = self.options.connect_timeout {
+                client = client.connect_timeout(timeout);
+            }
+            for (domain, address) in &self.options.resolve {
+                client = client.resolve(domain, *address);
+            }
+            for certificate in self.root_certificates()? {
+                client = client.add_root_certificate(certificate);
+            }
+            #[cfg(feature = "danger-accept-invalid-certs")]
+            {
+                client =
+                    client.danger_accept_invalid_certs(self.options.danger_accept_invalid_certs);
+            }
+            client.build().map_err(invalid_http_config)?
+        };
+        #[cfg(target_arch = "wasm32")]
+        let client = reqwest::Client::new();
+        let mut transport =
+            AsyncHttpTransport::with_client(client).with_timeout(self.options.timeout);
+        transport.rate_limiter = self.options.rate_limiter.clone();
+        transport.max_body_size = self.options.max_body_size;
+        self.build_async_with_transport(transport).await
+    }
+
+    /// Makes every request through `transport`, whose own timeout applies.
+    #[cfg(feature = "async")]
+    pub async fn build_async_with_transport<T: AsyncTransport>(
+        self,
+        transport: T,
+    ) -> Result<AsyncDrandClient<B, T>, DrandClientError> {
+        let (scheme, base_urls, options) = self.into_parts()?;
+        async_client::new_client_with_transport_and_options(scheme, base_urls, transport, options)
+            .await
+    }
+
+    /// Checks the configuration is consistent without building a client or going to the network,
+    /// e.g. at startup before deploying: that the base URLs are http(s) URLs, the scheme supports a
+    /// supplied chain info's scheme id, the chain hash pins agree, and the timeouts and retry
+    /// policy are ones requests could succeed with. `build` may still fail on the relay's answers.
+    pub fn validate_config(&self) -> Result<(), ConfigError> {
+        let scheme = self.scheme.as_ref().ok_or(ConfigError::NoScheme)?;
+        if self.base_urls.is_empty() {
+            return Err(ConfigError::NoBaseUrl);
+        }
+        for base_url in &self.base_urls {
+            let invalid = |reason: String| ConfigError::InvalidBaseUrl {
+                base_url: base_url.clone(),
+                reason,
+            };
+            let normalized = normalize_base_url(base_url).map_err(|e| invalid(reason_of(e)))?;
+            reqwest::Url::parse(&normalized).map_err(|e| invalid(e.to_string()))?;
+        }
+
+        let options = &self.options;
+        let chain_hash = check_options(options)?;
+
+        if let Some(chain_info) = &options.chain_info {
+            let scheme =
+                scheme
+                    .for_chain(chain_info)
+                    .map_err(|_| ConfigError::UnsupportedScheme {
+                        scheme_id: chain_info.scheme_id.clone(),
+                    })?;
+            if !scheme.supports(&chain_info.scheme_id) {
+                return Err(ConfigError::SchemeMismatch {
+                    expected: scheme.scheme_id().to_string(),
+                    found: chain_info.scheme_id.clone(),
+                });
+            }
+            let invalid = |e| ConfigError::InvalidChainInfo {
+                reason: reason_of(e),
+            };
+            chain_info.validate().map_err(invalid)?;
+            options.validate(chain_info).map_err(invalid)?;
+            if chain_hash.is_some_and(|chain_hash| chain_hash != chain_info.chain_hash) {
+                return Err(ConfigError::InvalidChainInfo {
+                    reason: "chain hash does not match the chain hash set".to_string(),
+                });
+            }
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        self.root_certificates()
+            .map_err(|e| ConfigError::InvalidRootCertificate {
+                reason: reason_of(e),
+            })?;
+        Ok(())
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn root_certificates(&self) -> Result<Vec<reqwest::Certificate>, DrandClientError> {
+        self.options
+            .root_certificates
+            .iter()
+            .map(|pem| reqwest::Certificate::from_pem(pem).map_err(invalid_http_config))
+            .collect()
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn into_parts(
+        mut self,
+    ) -> Result<(SchemeChoice<B>, Vec<String>, ClientOptions), DrandClientError> {
+        let scheme = self.scheme.ok_or(DrandClientError::InvalidConfig {
+            reason: "no scheme was set".to_string(),
+        })?;
+        if self.base_urls.is_empty() {
+            return Err(DrandClientError::InvalidConfig {
+                reason: "no base url was set".to_string(),
+            });
+        }
+        let base_urls = self
+            .base_urls
+            .iter()
+            .map(|base_url| normalize_base_url(base_url))
+            .collect::<Result<Vec<_>, _>>()?;
+        for base_url in &base_urls {
+            reqwest::Url::parse(base_url).map_err(|e| DrandClientError::InvalidConfig {
+                reason: format!("invalid base url {:?}: {}", base_url, e),
+            })?;
+        }
+        let chain_hash =
+            check_options(&self.options).map_err(|e| DrandClientError::InvalidConfig {
+                reason: e.to_string(),
+            })?;
+        if chain_hash.is_some() {
+            self.options.expected_chain_hash = chain_hash;
+        }
+        Ok((scheme, base_urls, self.options))
+    }
+}
+
+/// The checks on the options `build` and `validate_config` share, so one fails exactly when the
+/// other does. Returns the chain hash set, decoded.
+fn check_options(options: &ClientOptions) -> Result<Option<Vec<u8>>, ConfigError> {
+    if options.timeout.is_zero() {
+        return Err(ConfigError::ZeroTimeout { timeout: "timeout" });
+    }
+    if options
+        .connect_timeout
+        .is_some_and(|timeout| timeout.is_zero())
+    {
+        return Err(ConfigError::ZeroTimeout {
+            timeout: "connect timeout",
+        });
+    }
+    let retry = &options.retry_policy;
+    let reason = if retry.max_attempts == 0 {
+        Some("max_attempts is 0, so no request would be sent")
+    } else if !retry.multiplier.is_finite() || retry.multiplier < 1.0 {
+        Some("the multiplier must be a finite number of at least 1")
+    } else if retry.max_delay < retry.base_delay {
+        Some("max_delay is shorter than base_delay")
+    } else {
+        None
+    };
+    if let Some(reason) = reason {
+        return Err(ConfigError::InvalidRetry {
+            reason: reason.to_string(),
+        });
+    }
+
+    let chain_hash = match &options.chain_hash_path {
+        Some(chain_hash) => {
+            Some(
+                hex::decode(chain_hash).map_err(|e| ConfigError::InvalidChainHash {
+                    reason: e.to_string(),
+                })?,
+            )
+        }
+        None => None,
+    };
+    for hash in chain_hash.iter().chain(&options.expected_chain_hash) {
+        if hash.len() != CHAIN_HASH_LEN {
+            return Err(ConfigError::InvalidChainHash {
+                reason: format!("{} bytes long rather than {}", hash.len(), CHAIN_HASH_LEN),
+            });
+        }
+    }
+    if let (Some(chain_hash), Some(expected)) = (&chain_hash, &options.expected_chain_hash) {
+        if chain_hash != expected {
+            return Err(ConfigError::InvalidChainHash {
+                reason: "chain hash and expected chain hash differ".to_string(),
+            });
+        }
+    }
+    if let Some(templates) = &options.path_templates {
+        templates
+            .validate(options.chain_hash_path.as_deref())
+            .map_err(|e| ConfigError::InvalidPathTemplates {
+                reason: reason_of(e),
+            })?;
+    }
+    Ok(chain_hash)
+}
+
+const CHAIN_HASH_LEN: usize = 32;
+
+/// What [`DrandClientBuilder::validate_config`] found wrong with a configuration.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ConfigError {
+    #[error("no scheme was set")]
+    NoScheme,
+    #[error("no base url was set")]
+    NoBaseUrl,
+    #[error("invalid base url {base_url:?}: {reason}")]
+    InvalidBaseUrl { base_url: String, reason: String },
+    /// The supplied chain info is for a scheme none of the crate's schemes support, for a builder
+    /// choosing one with `auto_scheme`.
+    #[error("the chain's scheme {scheme_id} is unsupported")]
+    UnsupportedScheme { scheme_id: String },
+    /// The supplied chain info is for a scheme the client's scheme doesn't support.
+    #[error("the client's scheme is {expected}, but the chain's is {found}")]
+    SchemeMismatch { expected: String, found: String },
+    /// The chain hash or expected chain hash isn't one, or they differ.
+    #[error("invalid chain hash: {reason}")]
+    InvalidChainHash { reason: String },
+    /// The supplied chain info is invalid or doesn't match the pins.
+    #[error("invalid chain info: {reason}")]
+    InvalidChainInfo { reason: String },
+    #[error("invalid path templates: {reason}")]
+    InvalidPathTemplates { reason: String },
+    /// A zero timeout would fail every request.
+    #[error("the {timeout} is zero")]
+    ZeroTimeout { timeout: &'static str },
+    #[error("invalid retry policy: {reason}")]
+    InvalidRetry { reason: String },
+    #[error("invalid root certificate: {reason}")]
+    InvalidRootCertificate { reason: String },
+}
+
+/// The reason in an error the builder's other checks fail with, without its prefix.
+fn reason_of(e: DrandClientError) -> String {
+    match e {
+        DrandClientError::InvalidChainInfo { reason }
+        | DrandClientError::InvalidConfig { reason } => reason,
+        e => e.to_string(),
+    }
+}
+
+#[cfg(all(feature = "chained", feature = "unchained"))]
+impl DrandClientBuilder<AnyBeacon> {
+    /// Verifies beacons with whichever of the crate's schemes supports the chain info's
+    /// `schemeID`, as [`new_auto_client`] does, for networks only discovered at runtime. Replaces
+    /// any scheme set; a chain no scheme supports fails the build with
+    /// [`DrandClientError::UnsupportedScheme`].
+    ///
+    /// [`new_auto_client`]: crate::new_auto_client
+    pub fn auto_scheme(mut self) -> Self {
+        self.scheme = Some(SchemeChoice::BySchemeId(|scheme_id| {
+            AnyScheme::for_scheme_id(scheme_id)
+                .map(|scheme| Arc::new(scheme) as Arc<dyn Scheme<AnyBeacon>>)
+        }));
+        self
+    }
+}
+
+/// Building a reqwest client only fails on a misconfiguration, e.g. a TLS backend that can't load.
+#[cfg(not(target_arch = "wasm32"))]
+fn invalid_http_config(e: reqwest::Error) -> DrandClientError {
+    DrandClientError::InvalidConfig {
+        reason: e.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::fixtures;
+    use crate::{
+        AnyBeacon, ChainInfo, ConfigError, DrandClientBuilder, DrandClientError, MockTransport,
+        RetryPolicy, UnchainedBeacon, UnchainedScheme,
+    };
+    use std::io::{Read, Write};
+    use std::net::{Ipv4Addr, TcpListener};
+    use std::num::NonZeroUsize;
+    use std::thread;
+    use std::time::Duration;
+
+    fn mock_relay() -> MockTransport {
+        let beacon = fixtures::unchained_beacon(3);
+        let info = fixtures::chain_info("pedersen-bls-unchained");
+        MockTransport::new()
+            .with_body("http://relay/info", &fixtures::chain_info_json(&info))
+            .with_body(
+                "http://relay/public/3",
+                &fixtures::unchained_beacon_json(&beacon),
+            )
+    }
+
+    #[test]
+    fn configured_builder_builds_a_working_client() {
+        let client = DrandClientBuilder::new()
+            .scheme(&UnchainedScheme {})
+            .base_url("http://relay")
+            .cache_capacity(NonZeroUsize::new(8).unwrap())
+            .build_with_transport(mock_relay())
+            .unwrap();
+        assert_eq!(client.randomness(3).unwrap(), fixtures::unchained_beacon(3));
+        assert!(client.cache.is_some());
+    }
+
+    #[test]
+    fn auto_scheme_builds_with_the_other_options() {
+        let client = DrandClientBuilder::new()
+            .auto_scheme()
+            .base_url("http://relay")
+            .cache_capacity(NonZeroUsize::new(8).unwrap())
+            .build_with_transport(mock_relay())
+            .unwrap();
+        assert_eq!(client.scheme_id(), "pedersen-bls-unchained");
+        assert_eq!(
+            client.randomness(3).unwrap(),
+            AnyBeacon::Unchained(fixtures::unchained_beacon(3))
+        );
+        assert!(client.cache.is_some());
+
+        let mut unsupported = fixtures::chain_info("pedersen-bls-unchained");
+        unsupported.scheme_id = "not-a-scheme".to_string();
+        assert_eq!(
+            DrandClientBuilder::new()
+                .auto_scheme()
+                .base_url("http://relay")
+                .chain_info(unsupported.clone())
+                .validate_config(),
+            Err(ConfigError::UnsupportedScheme {
+                scheme_id: "not-a-scheme".to_string()
+            })
+        );
+        assert!(matches!(
+            DrandClientBuilder::new()
+                .auto_scheme()
+                .base_url("http://relay")
+                .chain_info(unsupported)
+                .build_with_transport(mock_relay()),
+            Err(DrandClientError::UnsupportedScheme)
+        ));
+    }
+
+    #[test]
+    fn built_transport_uses_the_pool_settings() {
+        let builder = DrandClientBuilder::<crate::UnchainedBeacon>::new()
+            .scheme(&UnchainedScheme {})
+            .base_url("http://127.0.0.1:1")
+            .pool_max_idle_per_host(4)
+            .tcp_keepalive(None);
+        assert_eq!(builder.options.pool_max_idle_per_host, 4);
+        assert_eq!(builder.options.tcp_keepalive, None);
+        assert!(matches!(
+            builder.build(),
+            Err(DrandClientError::NotResponding(_))
+        ));
+    }
+
+    #[test]
+    fn resolve_overrides_dns_for_the_built_transport() {
+        let relay = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = relay.local_addr().unwrap();
+        let info = fixtures::chain_info_json(&fixtures::chain_info("pedersen-bls-unchained"));
+        let server = thread::spawn(move || {
+            let (mut stream, _) = relay.accept().unwrap();
+            let mut request = [0; 1024];
+            let _ = stream.read(&mut request).unwrap();
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                info.len(),
+                info
+            )
+            .unwrap();
+        });
+        let client = DrandClientBuilder::new()
+            .scheme(&UnchainedScheme {})
+            .base_url(&format!("http://relay.invalid:{}", address.port()))
+            .local_address(Ipv4Addr::LOCALHOST.into())
+            .resolve("relay.invalid", address)
+            .build();
+        server.join().unwrap();
+        assert!(client.is_ok());
+    }
+
+    #[test]
+    fn invalid_root_certificates_are_invalid_config() {
+        let client = DrandClientBuilder::new()
+            .scheme(&UnchainedScheme {})
+            .base_url("http://127.0.0.1:1")
+            .add_root_certificate(b"-----BEGIN CERTIFICATE-----\nnot a certificate\n")
+            .build();
+        assert!(matches!(
+            client,
+            Err(DrandClientError::InvalidConfig { .. })
+        ));
+    }
+
+    #[test]
+    fn slow_responses_outlast_the_connect_timeout() {
+        let relay = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = relay.local_addr().unwrap();
+        let info = fixtures::chain_info_json(&fixtures::chain_info("pedersen-bls-unchained"));
+        let server = thread::spawn(move || {
+            let (mut stream, _) = relay.accept().unwrap();
+            let mut request = [0; 1024];
+            let _ = stream.read(&mut request).unwrap();
+            thread::sleep(Duration::from_millis(300));
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                info.len(),
+                info
+            )
+            .unwrap();
+        });
+        let builder = DrandClientBuilder::new()
+            .scheme(&UnchainedScheme {})
+            .base_url(&format!("http://{}", address))
+            .connect_timeout(Duration::from_millis(50))
+            .timeout(Duration::from_secs(5));
+        assert_eq!(
+            builder.options.connect_timeout,
+            Some(Duration::from_millis(50))
+        );
+        let client = builder.build();
+        server.join().unwrap();
+        assert!(client.is_ok());
+    }
+
+    #[test]
+    fn scheme_and_base_url_are_required() {
+        let missing_scheme = DrandClientBuilder::<crate::UnchainedBeacon>::new()
+            .base_url("http://relay")
+            .build_with_transport(mock_relay());
+        assert!(matches!(
+            missing_scheme,
+            Err(DrandClientError::InvalidConfig { .. })
+        ));
+
+        let missing_base_url = DrandClientBuilder::new()
+            .scheme(&UnchainedScheme {})
+            .build_with_transport(mock_relay());
+        assert!(matches!(
+            missing_base_url,
+            Err(DrandClientError::InvalidConfig { .. })
+        ));
+    }
+
+    #[test]
+    fn misconfiguration_is_found_without_the_network() {
+        let info = fixtures::chain_info("pedersen-bls-unchained");
+        let info = ChainInfo::new(
+            &info.scheme_id,
+            info.public_key,
+            info.group_hash,
+            info.genesis_time,
+            info.period_seconds,
+            &info.metadata.beacon_id,
+        );
+        let builder = || {
+            DrandClientBuilder::<UnchainedBeacon>::new()
+                .scheme(&UnchainedScheme {})
+                .base_url("https://relay.invalid/")
+                .expected_chain_hash(&info.chain_hash)
+        };
+        assert_eq!(builder().validate_config(), Ok(()));
+        assert_eq!(
+            builder()
+                .chain_info(info.clone())
+                .chain_hash(&hex::encode(&info.chain_hash))
+                .validate_config(),
+            Ok(())
+        );
+
+        assert_eq!(
+            DrandClientBuilder::<UnchainedBeacon>::new()
+                .base_url("https://relay.invalid")
+                .validate_config(),
+            Err(ConfigError::NoScheme)
+        );
+        assert!(matches!(
+            builder().base_url("relay.invalid").validate_config(),
+            Err(ConfigError::InvalidBaseUrl { base_url, .. }) if base_url == "relay.invalid"
+        ));
+        assert!(matches!(
+            builder()
+                .base_url("https://relay:notaport")
+                .validate_config(),
+            Err(ConfigError::InvalidBaseUrl { .. })
+        ));
+        assert!(matches!(
+            DrandClientBuilder::<UnchainedBeacon>::new()
+                .scheme(&UnchainedScheme {})
+                .base_url("https://relay.invalid")
+                .chain_info(fixtures::chain_info("pedersen-bls-chained"))
+                .validate_config(),
+            Err(ConfigError::SchemeMismatch { .. })
+        ));
+        assert!(matches!(
+            builder().chain_hash("abcd").validate_config(),
+            Err(ConfigError::InvalidChainHash { .. })
+        ));
+        assert_eq!(
+            builder().timeout(Duration::ZERO).validate_config(),
+            Err(ConfigError::ZeroTimeout { timeout: "timeout" })
+        );
+        assert!(matches!(
+            builder()
+                .retry(RetryPolicy {
+                    max_attempts: 3,
+                    base_delay: Duration::from_secs(2),
+                    multiplier: 2.0,
+                    max_delay: Duration::from_secs(1),
+                })
+                .validate_config(),
+            Err(ConfigError::InvalidRetry { .. })
+        ));
+        assert!(matches!(
+            builder()
+                .path_templates("info.json", "latest.json")
+                .validate_config(),
+            Err(ConfigError::InvalidPathTemplates { .. })
+        ));
+    }
+
+    #[test]
+    fn build_fails_where_validate_config_does() {
+        let builder = || {
+            DrandClientBuilder::new()
+                .scheme(&UnchainedScheme {})
+                .base_url("http://relay")
+        };
+        let misconfigured = [
+            builder().timeout(Duration::ZERO),
+            builder().connect_timeout(Duration::ZERO),
+            builder().retry(RetryPolicy {
+                max_attempts: 0,
+                ..RetryPolicy::default()
+            }),
+            builder().expected_chain_hash(&[1; 31]),
+            builder().chain_hash("abcd"),
+            builder().base_url("http://relay:notaport"),
+        ];
+        for builder in misconfigured {
+            assert!(builder.validate_config().is_err());
+            assert!(matches!(
+                builder.build_with_transport(mock_relay()),
+                Err(DrandClientError::InvalidConfig { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn unexpected_chain_hash_is_rejected() {
+        let result = DrandClientBuilder::new()
+            .scheme(&UnchainedScheme {})
+            .base_url("http://relay")
+            .expected_chain_hash(&[0; 32])
+            .build_with_transport(mock_relay());
+        assert!(matches!(
+            result,
+            Err(DrandClientError::InvalidChainInfo { .. })
+        ));
+    }
+
+    #[test]
+    fn pinned_public_key_is_checked_against_the_fetched_one() {
+        let info = fixtures::chain_info("pedersen-bls-unchained");
+        let client = DrandClientBuilder::new()
+            .scheme(&UnchainedScheme {})
+            .base_url("http://relay")
+            .expected_public_key(&info.public_key)
+            .build_with_transport(mock_relay());
+        assert!(client.is_ok());
+
+        let other = fixtures::g1_chain_info("bls-unchained-g1-rfc9380");
+        let result = DrandClientBuilder::new()
+            .scheme(&UnchainedScheme {})
+            .base_url("http://relay")
+            .expected_public_key(&other.public_key)
+            .build_with_transport(mock_relay());
+        assert!(matches!(
+            result,
+            Err(DrandClientError::InvalidChainInfo { .. })
+        ));
+    }
+
+    #[test]
+    fn supplied_chain_info_is_used_without_fetching_it() {
+        let info = fixtures::chain_info("pedersen-bls-unchained");
+        let info = ChainInfo::new(
+            &info.scheme_id,
+            info.public_key,
+            info.group_hash,
+            info.genesis_time,
+            info.period_seconds,
+            &info.metadata.beacon_id,
+        );
+        let relay_without_info = MockTransport::new().with_body(
+            "http://relay/public/3",
+            &fixtures::unchained_beacon_json(&fixtures::unchained_beacon(3)),
+        );
+        let client = DrandClientBuilder::new()
+            .scheme(&UnchainedScheme {})
+            .base_url("http://relay")
+            .chain_info(info.clone())
+            .expected_chain_hash(&info.chain_hash)
+            .build_with_transport(relay_without_info)
+            .unwrap();
+        assert_eq!(client.chain_info(), &info);
+        assert_eq!(client.randomness(3).unwrap(), fixtures::unchained_beacon(3));
+    }
+
+    #[test]
+    fn base_urls_are_normalised_before_fetching() {
+        let client = DrandClientBuilder::new()
+            .scheme(&UnchainedScheme {})
+            .base_url("http://relay/")
+            .build_with_transport(mock_relay())
+            .unwrap();
+        assert_eq!(client.base_url(), "http://relay");
+        assert_eq!(client.randomness(3).unwrap(), fixtures::unchained_beacon(3));
+
+        let missing_scheme = DrandClientBuilder::new()
+            .scheme(&UnchainedScheme {})
+            .base_url("relay")
+            .build_with_transport(mock_relay());
+        assert!(matches!(
+            missing_scheme,
+            Err(DrandClientError::InvalidChainInfo { .. })
+        ));
+    }
+}
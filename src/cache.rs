@@ -0,0 +1,102 @@
+use crate::http::Validators;
+use lru::LruCache;
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
+
+/// Verified beacons keyed by round number, evicting the least recently used once full.
+pub(crate) struct BeaconCache<B> {
+    entries: Mutex<LruCache<u64, B>>,
+}
+
+impl<B> BeaconCache<B> {
+    pub(crate) fn new(capacity: NonZeroUsize) -> Self {
+        BeaconCache {
+            entries: Mutex::new(LruCache::new(capacity)),
+        }
+    }
+
+    pub(crate) fn insert(&self, round_number: u64, beacon: B) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.put(round_number, beacon);
+        }
+    }
+
+    pub(crate) fn clear(&self) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.clear();
+        }
+    }
+}
+
+impl<B: Clone> BeaconCache<B> {
+    pub(crate) fn get(&self, round_number: u64) -> Option<B> {
+        self.entries.lock().ok()?.get(&round_number).cloned()
+    }
+}
+
+/// The beacon `/latest` last returned and what the relay served it with to tell if it's changed,
+/// for asking for `/latest` only if it has.
+pub(crate) struct LatestBeacon<B> {
+    latest: Mutex<Option<(B, Validators)>>,
+}
+
+impl<B> LatestBeacon<B> {
+    pub(crate) fn new() -> Self {
+        LatestBeacon {
+            latest: Mutex::new(None),
+        }
+    }
+
+    /// Remembers `beacon`, or forgets the last one if the relay gave no way to tell if it changed.
+    pub(crate) fn set(&self, beacon: B, validators: Validators) {
+        if let Ok(mut latest) = self.latest.lock() {
+            *latest = (!validators.is_empty()).then_some((beacon, validators));
+        }
+    }
+
+    pub(crate) fn clear(&self) {
+        if let Ok(mut latest) = self.latest.lock() {
+            *latest = None;
+        }
+    }
+}
+
+impl<B: Clone> LatestBeacon<B> {
+    pub(crate) fn get(&self) -> Option<(B, Validators)> {
+        self.latest.lock().ok()?.clone()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::cache::BeaconCache;
+    use std::num::NonZeroUsize;
+
+    #[test]
+    fn cached_rounds_are_returned() {
+        let cache = BeaconCache::new(NonZeroUsize::new(2).unwrap());
+        cache.insert(1, "one");
+        assert_eq!(cache.get(1), Some("one"));
+        assert_eq!(cache.get(2), None);
+    }
+
+    #[test]
+    fn least_recently_used_round_is_evicted() {
+        let cache = BeaconCache::new(NonZeroUsize::new(2).unwrap());
+        cache.insert(1, "one");
+        cache.insert(2, "two");
+        cache.get(1);
+        cache.insert(3, "three");
+        assert_eq!(cache.get(1), Some("one"));
+        assert_eq!(cache.get(2), None);
+        assert_eq!(cache.get(3), Some("three"));
+    }
+
+    #[test]
+    fn clear_removes_all_rounds() {
+        let cache = BeaconCache::new(NonZeroUsize::new(2).unwrap());
+        cache.insert(1, "one");
+        cache.clear();
+        assert_eq!(cache.get(1), None);
+    }
+}
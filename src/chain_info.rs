@@ -1,23 +1,381 @@
-use serde::Deserialize;
+use crate::bls::{self, PointError, PublicKeyPoint};
+#[cfg(feature = "std")]
+use crate::DrandClientError;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::time::Duration;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+#[cfg(feature = "std")]
+use std::sync::OnceLock;
+#[cfg(feature = "std")]
+use std::time::SystemTime;
 
-#[derive(Deserialize, Debug, PartialEq, Clone)]
+const DEFAULT_SCHEME_ID: &str = "pedersen-bls-chained";
+const DEFAULT_BEACON_ID: &str = "default";
+
+/// Serialises in the JSON shape drand's relays serve it in at `/info`. As well as that shape,
+/// which the drand CLI's `get chain-info` prints too, it parses the camel-cased and snake-cased
+/// field names other drand tooling writes, e.g. `genesisTime` or `group_hash`.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct ChainInfo {
-    #[serde(alias = "schemeID")]
+    #[serde(
+        rename(serialize = "schemeID"),
+        alias = "schemeID",
+        alias = "schemeId",
+        alias = "scheme"
+    )]
     pub scheme_id: String,
-    #[serde(with = "hex")]
+    #[serde(with = "hex", alias = "publicKey")]
     pub public_key: Vec<u8>,
-    #[serde(with = "hex", alias = "hash")]
+    #[serde(
+        with = "hex",
+        rename(serialize = "hash"),
+        alias = "hash",
+        alias = "chainHash"
+    )]
     pub chain_hash: Vec<u8>,
-    #[serde(with = "hex", alias = "groupHash")]
+    #[serde(with = "hex", rename(serialize = "groupHash"), alias = "groupHash")]
     pub group_hash: Vec<u8>,
+    #[serde(alias = "genesisTime")]
     pub genesis_time: u64,
-    #[serde(alias = "period")]
+    #[serde(rename(serialize = "period"), alias = "period")]
     pub period_seconds: usize,
     pub metadata: ChainInfoMetadata,
+    #[serde(skip)]
+    pub(crate) parsed_public_key: ParsedPublicKey,
 }
 
-#[derive(Deserialize, Debug, PartialEq, Clone)]
+/// Every name `beacon_id` is parsed from, as in its serde attributes, so none of them is warned
+/// about as unknown metadata. Keep the two in step.
+#[cfg(any(feature = "async", feature = "blocking"))]
+pub(crate) const BEACON_ID_FIELDS: [&str; 3] = ["beacon_id", "beaconID", "beaconId"];
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct ChainInfoMetadata {
-    #[serde(alias = "beaconID")]
+    #[serde(rename(serialize = "beaconID"), alias = "beaconID", alias = "beaconId")]
+    pub beacon_id: String,
+}
+
+/// What a client is connected to, for logging and dashboards. Displays as a single line.
+#[derive(Debug, PartialEq, Clone)]
+pub struct NetworkSummary {
+    pub scheme_id: String,
+    pub period: Duration,
+    pub genesis_time: u64,
+    /// Hex encoded.
+    pub chain_hash: String,
     pub beacon_id: String,
 }
+
+impl fmt::Display for NetworkSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({}) every {}s since {}, chain hash {}",
+            self.beacon_id,
+            self.scheme_id,
+            self.period.as_secs(),
+            self.genesis_time,
+            self.chain_hash
+        )
+    }
+}
+
+/// The decoded `public_key`, alongside the bytes it was decoded from in case they've since been
+/// changed. It's derived from the other fields, so is ignored when comparing chain infos.
+/// Without `std` there's no `OnceLock` to keep it in, so the key is decoded on every use.
+#[derive(Default, Clone)]
+pub(crate) struct ParsedPublicKey(
+    #[cfg(feature = "std")] OnceLock<(Vec<u8>, Result<PublicKeyPoint, PointError>)>,
+);
+
+impl PartialEq for ParsedPublicKey {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl fmt::Debug for ParsedPublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("..")
+    }
+}
+
+impl fmt::Display for ChainInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.summary().fmt(f)
+    }
+}
+
+impl ChainInfo {
+    /// Chain info from parameters known ahead of time, rather than fetched from a relay's `/info`.
+    /// The chain hash is derived from them as drand nodes derive it.
+    pub fn new(
+        scheme_id: &str,
+        public_key: Vec<u8>,
+        group_hash: Vec<u8>,
+        genesis_time: u64,
+        period_seconds: usize,
+        beacon_id: &str,
+    ) -> Self {
+        let mut chain_info = ChainInfo {
+            scheme_id: scheme_id.into(),
+            public_key,
+            chain_hash: Vec::new(),
+            group_hash,
+            genesis_time,
+            period_seconds,
+            metadata: ChainInfoMetadata {
+                beacon_id: beacon_id.into(),
+            },
+            parsed_public_key: ParsedPublicKey::default(),
+        };
+        chain_info.chain_hash = chain_info.compute_hash();
+        chain_info
+    }
+
+    /// Rejects parameters no real chain has, which would otherwise break the round and time
+    /// arithmetic downstream, e.g. a zero period dividing by zero.
+    #[cfg(feature = "std")]
+    pub fn validate(&self) -> Result<(), DrandClientError> {
+        let reason = if self.period_seconds == 0 {
+            "period is zero"
+        } else if self.genesis_time == 0 {
+            "genesis time is zero"
+        } else if self.public_key.is_empty() {
+            "public key is empty"
+        } else if let Err(e) = self.public_key_point() {
+            return Err(DrandClientError::InvalidChainInfo {
+                reason: format!("invalid public key: {}", e),
+            });
+        } else if self.scheme_id.is_empty() {
+            "scheme id is empty"
+        } else {
+            return Ok(());
+        };
+        Err(DrandClientError::InvalidChainInfo {
+            reason: reason.to_string(),
+        })
+    }
+
+    /// The public key as a group element, or why it isn't a valid one. It's decoded the
+    /// first time it's needed and reused after that.
+    #[cfg(feature = "std")]
+    pub(crate) fn public_key_point(&self) -> Result<PublicKeyPoint, PointError> {
+        let (bytes, point) = self.parsed_public_key.0.get_or_init(|| {
+            (
+                self.public_key.clone(),
+                bls::parse_public_key(&self.public_key),
+            )
+        });
+        if *bytes == self.public_key {
+            *point
+        } else {
+            bls::parse_public_key(&self.public_key)
+        }
+    }
+
+    #[cfg(not(feature = "std"))]
+    pub(crate) fn public_key_point(&self) -> Result<PublicKeyPoint, PointError> {
+        bls::parse_public_key(&self.public_key)
+    }
+
+    pub fn summary(&self) -> NetworkSummary {
+        NetworkSummary {
+            scheme_id: self.scheme_id.clone(),
+            period: self.period_duration(),
+            genesis_time: self.genesis_time,
+            chain_hash: hex::encode(&self.chain_hash),
+            beacon_id: self.metadata.beacon_id.clone(),
+        }
+    }
+
+    /// The chain info as `/info` serves it, e.g. to save and later pass to `from_chain_info`.
+    #[cfg(feature = "std")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("chain info always serialises")
+    }
+
+    /// The time between rounds.
+    pub fn period_duration(&self) -> Duration {
+        Duration::from_secs(self.period_seconds as u64)
+    }
+
+    /// When round 1 was emitted.
+    #[cfg(feature = "std")]
+    pub fn genesis_time_systemtime(&self) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(self.genesis_time)
+    }
+
+    /// Recomputes the chain hash from the group parameters in the same way drand nodes derive it.
+    pub fn compute_hash(&self) -> Vec<u8> {
+        let mut bytes: Vec<u8> = Vec::new();
+        bytes.extend_from_slice(&(self.period_seconds as u32).to_be_bytes());
+        bytes.extend_from_slice(&(self.genesis_time as i64).to_be_bytes());
+        bytes.extend_from_slice(&self.public_key);
+        bytes.extend_from_slice(&self.group_hash);
+        if self.scheme_id != DEFAULT_SCHEME_ID {
+            bytes.extend_from_slice(self.scheme_id.as_bytes());
+        }
+        if !self.metadata.beacon_id.is_empty() && self.metadata.beacon_id != DEFAULT_BEACON_ID {
+            bytes.extend_from_slice(self.metadata.beacon_id.as_bytes());
+        }
+        Sha256::digest(&bytes).to_vec()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::bls::PointError;
+    use crate::chain_info::{ChainInfo, ChainInfoMetadata};
+    use crate::fixtures;
+    use crate::DrandClientError;
+    use bls12_381::G1Affine;
+    use std::time::{Duration, SystemTime};
+
+    fn mainnet_chain_info() -> ChainInfo {
+        ChainInfo {
+            scheme_id: "pedersen-bls-chained".to_string(),
+            public_key: hex::decode("868f005eb8e6e4ca0a47c8a77ceaa5309a47978a7c71bc5cce96366b5d7a569937c529eeda66c7293784a9402801af31").unwrap(),
+            chain_hash: hex::decode("8990e7a9aaed2ffed73dbd7092123d6f289930540d7651336225dc172e51b2ce").unwrap(),
+            group_hash: hex::decode("176f93498eac9ca337150b46d21dd58673ea4e3581185f869672e59fa4cb390a").unwrap(),
+            genesis_time: 1595431050,
+            period_seconds: 30,
+            metadata: ChainInfoMetadata {
+                beacon_id: "default".to_string(),
+            },
+            parsed_public_key: Default::default(),
+        }
+    }
+
+    #[test]
+    fn computed_hash_matches_mainnet() {
+        let info = mainnet_chain_info();
+        assert_eq!(info.compute_hash(), info.chain_hash);
+    }
+
+    #[test]
+    fn computed_hash_changes_with_parameters() {
+        let mut info = mainnet_chain_info();
+        info.period_seconds = 3;
+        assert_ne!(info.compute_hash(), info.chain_hash);
+    }
+
+    #[test]
+    fn summary_describes_the_network() {
+        let summary = mainnet_chain_info().summary();
+        assert_eq!(summary.scheme_id, "pedersen-bls-chained");
+        assert_eq!(summary.period, Duration::from_secs(30));
+        assert_eq!(
+            summary.to_string(),
+            "default (pedersen-bls-chained) every 30s since 1595431050, chain hash \
+             8990e7a9aaed2ffed73dbd7092123d6f289930540d7651336225dc172e51b2ce"
+        );
+    }
+
+    #[test]
+    fn chain_info_serialises_to_drand_json() {
+        let info = mainnet_chain_info();
+        let json: serde_json::Value = serde_json::from_str(&info.to_json()).unwrap();
+        let expected: serde_json::Value =
+            serde_json::from_str(&fixtures::chain_info_json(&info)).unwrap();
+        assert_eq!(json, expected);
+        assert_eq!(
+            serde_json::from_str::<ChainInfo>(&info.to_json()).unwrap(),
+            info
+        );
+        assert_eq!(info.to_string(), info.summary().to_string());
+    }
+
+    #[test]
+    fn drand_cli_chain_info_parses_and_hashes_to_its_chain_hash() {
+        let info: ChainInfo = serde_json::from_str(fixtures::MAINNET_INFO_JSON).unwrap();
+        assert_eq!(info, mainnet_chain_info());
+        assert!(info.validate().is_ok());
+        assert_eq!(info.compute_hash(), info.chain_hash);
+    }
+
+    #[test]
+    fn other_casings_of_the_field_names_parse() {
+        let info = mainnet_chain_info();
+        let camel = serde_json::json!({
+            "publicKey": hex::encode(&info.public_key),
+            "period": info.period_seconds,
+            "genesisTime": info.genesis_time,
+            "chainHash": hex::encode(&info.chain_hash),
+            "groupHash": hex::encode(&info.group_hash),
+            "schemeId": info.scheme_id,
+            "metadata": { "beaconId": info.metadata.beacon_id },
+        });
+        assert_eq!(serde_json::from_value::<ChainInfo>(camel).unwrap(), info);
+        let snake = serde_json::json!({
+            "public_key": hex::encode(&info.public_key),
+            "period_seconds": info.period_seconds,
+            "genesis_time": info.genesis_time,
+            "chain_hash": hex::encode(&info.chain_hash),
+            "group_hash": hex::encode(&info.group_hash),
+            "scheme": info.scheme_id,
+            "metadata": { "beacon_id": info.metadata.beacon_id },
+        });
+        assert_eq!(serde_json::from_value::<ChainInfo>(snake).unwrap(), info);
+    }
+
+    #[test]
+    fn mainnet_chain_info_is_valid() {
+        assert!(mainnet_chain_info().validate().is_ok());
+    }
+
+    #[test]
+    fn nonsensical_parameters_are_invalid() {
+        let invalid = [
+            ChainInfo {
+                period_seconds: 0,
+                ..mainnet_chain_info()
+            },
+            ChainInfo {
+                genesis_time: 0,
+                ..mainnet_chain_info()
+            },
+            ChainInfo {
+                public_key: vec![],
+                ..mainnet_chain_info()
+            },
+            ChainInfo {
+                public_key: G1Affine::identity().to_compressed().to_vec(),
+                ..mainnet_chain_info()
+            },
+            ChainInfo {
+                scheme_id: String::new(),
+                ..mainnet_chain_info()
+            },
+        ];
+        for info in invalid {
+            assert!(matches!(
+                info.validate(),
+                Err(DrandClientError::InvalidChainInfo { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn decoded_public_key_follows_changes_to_its_bytes() {
+        let mut info = mainnet_chain_info();
+        assert!(info.public_key_point().is_ok());
+        info.public_key = G1Affine::identity().to_compressed().to_vec();
+        assert_eq!(info.public_key_point(), Err(PointError::Identity));
+    }
+
+    #[test]
+    fn times_are_exposed_as_typed_values() {
+        let info = mainnet_chain_info();
+        assert_eq!(info.period_duration(), Duration::from_secs(30));
+        assert_eq!(
+            info.genesis_time_systemtime()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap(),
+            Duration::from_secs(1595431050)
+        );
+    }
+}
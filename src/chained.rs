@@ -1,12 +1,17 @@
 use crate::bls::BlsVerifiable;
 use crate::chain_info::ChainInfo;
-use crate::{bls, Scheme, SchemeError};
-use serde::Deserialize;
-use std::io::Write;
+use crate::{bls, Beacon, Scheme, SchemeError, RANDOMNESS_LEN};
+#[cfg(feature = "std")]
+use alloc::string::String;
+use alloc::vec::Vec;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Debug, PartialEq, Clone)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(try_from = "ChainedBeaconJson")]
 pub struct ChainedBeacon {
-    #[serde(alias = "round")]
+    #[serde(rename(serialize = "round"), alias = "round")]
     pub round_number: u64,
     #[serde(with = "hex")]
     pub randomness: Vec<u8>,
@@ -16,13 +21,91 @@ pub struct ChainedBeacon {
     pub previous_signature: Vec<u8>,
 }
 
+// unchained beacons are the same but for `previous_signature`, so its absence gets its own error
+// rather than serde's generic missing field one
+#[derive(Deserialize)]
+struct ChainedBeaconJson {
+    #[serde(alias = "round")]
+    round_number: u64,
+    #[serde(with = "hex")]
+    randomness: Vec<u8>,
+    #[serde(with = "hex")]
+    signature: Vec<u8>,
+    #[serde(default, with = "hex_option", alias = "previousSignature")]
+    previous_signature: Option<Vec<u8>>,
+}
+
+impl TryFrom<ChainedBeaconJson> for ChainedBeacon {
+    type Error = &'static str;
+
+    fn try_from(json: ChainedBeaconJson) -> Result<Self, Self::Error> {
+        Ok(ChainedBeacon {
+            round_number: json.round_number,
+            randomness: json.randomness,
+            signature: json.signature,
+            previous_signature: json
+                .previous_signature
+                .ok_or("expected chained beacon with previous_signature but field was missing")?,
+        })
+    }
+}
+
+mod hex_option {
+    use alloc::vec::Vec;
+    use serde::{Deserialize, Deserializer};
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Vec<u8>>, D::Error> {
+        #[derive(Deserialize)]
+        struct Hex(#[serde(with = "hex")] Vec<u8>);
+        Option::<Hex>::deserialize(deserializer).map(|hex| hex.map(|Hex(bytes)| bytes))
+    }
+}
+
 pub struct ChainedScheme {}
 
+impl ChainedScheme {
+    /// Verifies every beacon in `beacons` and checks that each one links back to the beacon
+    /// before it, i.e. that the rounds are consecutive and each `previous_signature` is the
+    /// prior beacon's `signature`, in whichever encoding either was served. The first beacon's own
+    /// link is only checked by its signature. With the `rayon` feature the signatures are verified
+    /// in parallel once the links are.
+    pub fn verify_chain(
+        &self,
+        info: &ChainInfo,
+        beacons: &[ChainedBeacon],
+    ) -> Result<(), SchemeError> {
+        let linked = beacons.windows(2).all(|pair| {
+            pair[0].round_number.checked_add(1) == Some(pair[1].round_number)
+                && bls::compressed_point(&pair[1].previous_signature)
+                    == bls::compressed_point(&pair[0].signature)
+        });
+        if !linked {
+            return Err(SchemeError::InvalidBeacon);
+        }
+
+        #[cfg(feature = "rayon")]
+        let verified = beacons
+            .par_iter()
+            .try_for_each(|beacon| self.verify(info, beacon.clone()).map(|_| ()));
+        #[cfg(not(feature = "rayon"))]
+        let verified = beacons
+            .iter()
+            .try_for_each(|beacon| self.verify(info, beacon.clone()).map(|_| ()));
+        verified
+    }
+}
+
 impl Scheme<ChainedBeacon> for ChainedScheme {
     fn supports(&self, scheme_id: &str) -> bool {
         scheme_id.eq_ignore_ascii_case("pedersen-bls-chained")
     }
 
+    fn scheme_id(&self) -> &str {
+        "pedersen-bls-chained"
+    }
+
     fn verify(
         &self,
         info: &ChainInfo,
@@ -31,26 +114,183 @@ impl Scheme<ChainedBeacon> for ChainedScheme {
         if !self.supports(&info.scheme_id) {
             Err(SchemeError::InvalidScheme)
         } else {
-            bls::bls_verify(info, beacon)
+            bls::bls_verify_scheme(self.scheme_id(), info, beacon)
         }
     }
 }
 
+impl ChainedBeacon {
+    /// The beacon in the JSON shape drand's relays serve it in.
+    #[cfg(feature = "std")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("beacons always serialise")
+    }
+}
+
+impl Beacon for ChainedBeacon {
+    fn round_number(&self) -> u64 {
+        self.round_number
+    }
+
+    fn randomness_bytes(&self) -> Result<[u8; RANDOMNESS_LEN], SchemeError> {
+        self.randomness
+            .as_slice()
+            .try_into()
+            .map_err(|_| SchemeError::InvalidBeacon)
+    }
+
+    fn signature_bytes(&self) -> &[u8] {
+        &self.signature
+    }
+}
+
 impl BlsVerifiable for ChainedBeacon {
     fn signature(&self) -> &Vec<u8> {
         &self.signature
     }
 
+    fn randomness(&self) -> &Vec<u8> {
+        &self.randomness
+    }
+
     fn to_message(&self) -> Result<Vec<u8>, SchemeError> {
-        let mut bytes: Vec<u8> = vec![];
+        // drand signs the compressed previous signature, however it was served
+        let mut bytes = bls::compressed_point(&self.previous_signature);
+        bytes.extend_from_slice(&self.round_number.to_be_bytes());
+        Ok(bytes)
+    }
+}
 
-        if bytes.write_all(self.previous_signature.as_slice()).is_err() {
-            return Err(SchemeError::InvalidBeacon);
-        }
-        if bytes.write_all(&self.round_number.to_be_bytes()).is_err() {
-            Err(SchemeError::InvalidBeacon)
-        } else {
-            Ok(bytes)
-        }
+#[cfg(test)]
+mod test {
+    use crate::bls::{reencode_point, PointEncoding};
+    use crate::chain_info::ChainInfo;
+    use crate::chained::{ChainedBeacon, ChainedScheme};
+    use crate::fixtures;
+    use crate::Scheme;
+
+    #[test]
+    fn valid_beacon_verifies() {
+        let info = fixtures::chain_info("pedersen-bls-chained");
+        let beacon = fixtures::chained_beacon(2, vec![9; 96]);
+        assert!(ChainedScheme {}.verify(&info, beacon).is_ok());
+    }
+
+    #[test]
+    fn linked_beacons_verify_as_a_chain() {
+        let info = fixtures::chain_info("pedersen-bls-chained");
+        let first = fixtures::chained_beacon(2, vec![9; 96]);
+        let second = fixtures::chained_beacon(3, first.signature.clone());
+        let third = fixtures::chained_beacon(4, second.signature.clone());
+        assert!(ChainedScheme {}
+            .verify_chain(&info, &[first, second, third])
+            .is_ok());
+    }
+
+    #[test]
+    fn uncompressed_signatures_link_and_verify() {
+        let info = fixtures::chain_info("pedersen-bls-chained");
+        let first = fixtures::chained_beacon(2, vec![9; 96]);
+        let uncompressed = reencode_point(&first.signature, PointEncoding::Uncompressed).unwrap();
+        // signed over the compressed previous signature, as drand signs it
+        let second = ChainedBeacon {
+            previous_signature: uncompressed.clone(),
+            ..fixtures::chained_beacon(3, first.signature.clone())
+        };
+        assert!(ChainedScheme {}.verify(&info, second.clone()).is_ok());
+        assert!(ChainedScheme {}
+            .verify_chain(&info, &[first.clone(), second])
+            .is_ok());
+
+        let served_uncompressed = ChainedBeacon {
+            signature: uncompressed,
+            ..first.clone()
+        };
+        let third = fixtures::chained_beacon(3, first.signature.clone());
+        assert!(ChainedScheme {}
+            .verify_chain(&info, &[served_uncompressed, third])
+            .is_ok());
+    }
+
+    #[test]
+    fn broken_link_is_rejected() {
+        let info = fixtures::chain_info("pedersen-bls-chained");
+        let first = fixtures::chained_beacon(2, vec![9; 96]);
+        let forged = fixtures::chained_beacon(3, vec![8; 96]);
+        assert!(ChainedScheme {}
+            .verify_chain(&info, &[first.clone(), forged])
+            .is_err());
+
+        let skipped = fixtures::chained_beacon(4, first.signature.clone());
+        assert!(ChainedScheme {}
+            .verify_chain(&info, &[first, skipped])
+            .is_err());
+
+        let last = fixtures::chained_beacon(u64::MAX, vec![9; 96]);
+        let wrapped = fixtures::chained_beacon(0, last.signature.clone());
+        assert!(ChainedScheme {}
+            .verify_chain(&info, &[last, wrapped])
+            .is_err());
+    }
+
+    #[test]
+    fn tampered_randomness_is_rejected() {
+        let info = fixtures::chain_info("pedersen-bls-chained");
+        let mut beacon = fixtures::chained_beacon(2, vec![9; 96]);
+        beacon.randomness[0] ^= 0x01;
+        assert!(ChainedScheme {}.verify(&info, beacon).is_err());
+    }
+
+    #[test]
+    fn unchained_json_is_rejected_with_a_clear_reason() {
+        let unchained = fixtures::unchained_beacon_json(&fixtures::unchained_beacon(2));
+        let err = serde_json::from_str::<ChainedBeacon>(&unchained).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("expected chained beacon with previous_signature but field was missing"));
+    }
+
+    #[test]
+    fn beacon_serialises_to_drand_json() {
+        let beacon = fixtures::chained_beacon(2, vec![9; 96]);
+        let json: serde_json::Value = serde_json::from_str(&beacon.to_json()).unwrap();
+        let expected: serde_json::Value =
+            serde_json::from_str(&fixtures::chained_beacon_json(&beacon)).unwrap();
+        assert_eq!(json, expected);
+        assert_eq!(
+            serde_json::from_str::<ChainedBeacon>(&beacon.to_json()).unwrap(),
+            beacon
+        );
+    }
+
+    #[test]
+    fn relay_served_mainnet_beacon_parses_and_verifies() {
+        let info: ChainInfo = serde_json::from_str(fixtures::MAINNET_INFO_JSON).unwrap();
+        let beacon: ChainedBeacon =
+            serde_json::from_str(fixtures::MAINNET_ROUND_72785_JSON).unwrap();
+        assert_eq!(beacon.round_number, 72785);
+        assert!(ChainedScheme {}.verify(&info, beacon.clone()).is_ok());
+
+        let camel =
+            fixtures::MAINNET_ROUND_72785_JSON.replace("previous_signature", "previousSignature");
+        assert_eq!(
+            serde_json::from_str::<ChainedBeacon>(&camel).unwrap(),
+            beacon
+        );
+    }
+
+    #[test]
+    fn camel_cased_previous_signature_parses() {
+        let beacon = fixtures::chained_beacon(2, vec![9; 96]);
+        let camel = serde_json::json!({
+            "round": beacon.round_number,
+            "randomness": hex::encode(&beacon.randomness),
+            "signature": hex::encode(&beacon.signature),
+            "previousSignature": hex::encode(&beacon.previous_signature),
+        });
+        assert_eq!(
+            serde_json::from_value::<ChainedBeacon>(camel).unwrap(),
+            beacon
+        );
     }
 }
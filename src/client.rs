@@ -0,0 +1,2196 @@
+use crate::builder::DrandClientBuilder;
+use crate::cache::{BeaconCache, LatestBeacon};
+use crate::chain_info::{ChainInfo, NetworkSummary};
+#[cfg(feature = "chained")]
+use crate::chained::{ChainedBeacon, ChainedScheme};
+use crate::clock::Clock;
+use crate::endpoints::{ApiVersion, Endpoints, Paths};
+use crate::history::History;
+use crate::http::{HttpError, HttpResponse, HttpTransport, Transport, Validators};
+use crate::metrics::Metrics;
+#[cfg(any(feature = "chained", feature = "unchained"))]
+use crate::networks;
+use crate::options::{ClientOptions, SchemeChoice};
+use crate::round::reject_future_round;
+use crate::timer;
+#[cfg(feature = "unchained")]
+use crate::unchained::{UnchainedBeacon, UnchainedScheme};
+#[cfg(feature = "g1")]
+use crate::unchained_g1::{UnchainedSchemeOnG1, UnchainedSchemeOnG1Rfc9380};
+use crate::watch::Watch;
+#[cfg(all(feature = "chained", feature = "unchained"))]
+use crate::AnyBeacon;
+use crate::DrandClientError::InvalidRound;
+use crate::{
+    chain_info_warnings, check_same_chain, check_scheme, fetch_error, parse_beacon,
+    parse_chain_info, round_at, time_of_round, unknown_metadata, Beacon, BeaconWithContext,
+    DrandClientError, FetchedBeacon, Quorum, RetryPolicy, Round, Scheme, VerifiedBeacon, Warning,
+    MAX_RANGE_LEN, RANDOMNESS_LEN, RANGE_CONCURRENCY,
+};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use serde::de::DeserializeOwned;
+use std::num::NonZeroUsize;
+use std::ops::ControlFlow;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub struct DrandClient<B, T = HttpTransport> {
+    pub(crate) scheme: Arc<dyn Scheme<B>>,
+    pub(crate) transport: T,
+    pub(crate) endpoints: Endpoints,
+    pub(crate) chain_hash: Option<String>,
+    pub(crate) paths: Paths,
+    pub(crate) chain_info: ChainInfo,
+    pub(crate) retry_policy: RetryPolicy,
+    pub(crate) cache: Option<Arc<BeaconCache<B>>>,
+    pub(crate) latest: Option<Arc<LatestBeacon<B>>>,
+    pub(crate) metrics: Arc<dyn Metrics>,
+    pub(crate) clock: Arc<dyn Clock>,
+    pub(crate) future_round_tolerance: Option<Duration>,
+    pub(crate) warnings: Vec<Warning>,
+}
+
+/// Clones share the scheme, cache, metrics and clock, and don't re-fetch the chain info.
+impl<B, T: Clone> Clone for DrandClient<B, T> {
+    fn clone(&self) -> Self {
+        DrandClient {
+            scheme: self.scheme.clone(),
+            transport: self.transport.clone(),
+            endpoints: self.endpoints.clone(),
+            chain_hash: self.chain_hash.clone(),
+            paths: self.paths.clone(),
+            chain_info: self.chain_info.clone(),
+            retry_policy: self.retry_policy.clone(),
+            cache: self.cache.clone(),
+            latest: self.latest.clone(),
+            metrics: self.metrics.clone(),
+            clock: self.clock.clone(),
+            future_round_tolerance: self.future_round_tolerance,
+            warnings: self.warnings.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "chained")]
+pub fn new_chained_client(base_url: &str) -> Result<DrandClient<ChainedBeacon>, DrandClientError> {
+    new_client(&ChainedScheme {}, base_url)
+}
+
+#[cfg(feature = "unchained")]
+pub fn new_unchained_client(
+    base_url: &str,
+) -> Result<DrandClient<UnchainedBeacon>, DrandClientError> {
+    new_client(&UnchainedScheme {}, base_url)
+}
+
+#[cfg(feature = "g1")]
+pub fn new_unchained_g1_client(
+    base_url: &str,
+) -> Result<DrandClient<UnchainedBeacon>, DrandClientError> {
+    new_client(&UnchainedSchemeOnG1 {}, base_url)
+}
+
+#[cfg(feature = "g1")]
+pub fn new_unchained_g1_rfc9380_client(
+    base_url: &str,
+) -> Result<DrandClient<UnchainedBeacon>, DrandClientError> {
+    new_client(&UnchainedSchemeOnG1Rfc9380 {}, base_url)
+}
+
+pub fn new_client<S: Scheme<B> + 'static, B>(
+    scheme: S,
+    base_url: &str,
+) -> Result<DrandClient<B>, DrandClientError> {
+    DrandClientBuilder::new()
+        .scheme(scheme)
+        .base_url(base_url)
+        .build()
+}
+
+pub fn new_client_with_timeout<S: Scheme<B> + 'static, B>(
+    scheme: S,
+    base_url: &str,
+    timeout: Duration,
+) -> Result<DrandClient<B>, DrandClientError> {
+    DrandClientBuilder::new()
+        .scheme(scheme)
+        .base_url(base_url)
+        .timeout(timeout)
+        .build()
+}
+
+pub fn new_client_with_retry<S: Scheme<B> + 'static, B>(
+    scheme: S,
+    base_url: &str,
+    retry_policy: RetryPolicy,
+) -> Result<DrandClient<B>, DrandClientError> {
+    DrandClientBuilder::new()
+        .scheme(scheme)
+        .base_url(base_url)
+        .retry(retry_policy)
+        .build()
+}
+
+pub fn new_client_with_chain_hash<S: Scheme<B> + 'static, B>(
+    scheme: S,
+    base_url: &str,
+    expected_chain_hash: &[u8],
+) -> Result<DrandClient<B>, DrandClientError> {
+    DrandClientBuilder::new()
+        .scheme(scheme)
+        .base_url(base_url)
+        .expected_chain_hash(expected_chain_hash)
+        .build()
+}
+
+/// Refuses to verify against any group public key but `public_key`, whatever the relay's `/info`
+/// returns. Pass the chain info to [`DrandClientBuilder::chain_info`] to skip fetching it entirely.
+pub fn new_client_with_public_key<S: Scheme<B> + 'static, B>(
+    scheme: S,
+    base_url: &str,
+    public_key: &[u8],
+) -> Result<DrandClient<B>, DrandClientError> {
+    DrandClientBuilder::new()
+        .scheme(scheme)
+        .base_url(base_url)
+        .expected_public_key(public_key)
+        .build()
+}
+
+/// Targets a single chain on a relay serving several, routing requests via `/{chain_hash}/...`.
+pub fn new_client_for_chain<S: Scheme<B> + 'static, B>(
+    scheme: S,
+    base_url: &str,
+    chain_hash: &str,
+) -> Result<DrandClient<B>, DrandClientError> {
+    DrandClientBuilder::new()
+        .scheme(scheme)
+        .base_url(base_url)
+        .chain_hash(chain_hash)
+        .build()
+}
+
+pub fn new_client_with_cache<S: Scheme<B> + 'static, B>(
+    scheme: S,
+    base_url: &str,
+    capacity: NonZeroUsize,
+) -> Result<DrandClient<B>, DrandClientError> {
+    DrandClientBuilder::new()
+        .scheme(scheme)
+        .base_url(base_url)
+        .cache_capacity(capacity)
+        .build()
+}
+
+pub fn new_client_with_failover<S: Scheme<B> + 'static, B>(
+    scheme: S,
+    base_urls: &[&str],
+    retry_policy: RetryPolicy,
+) -> Result<DrandClient<B>, DrandClientError> {
+    base_urls
+        .iter()
+        .fold(
+            DrandClientBuilder::new().scheme(scheme),
+            |builder, base_url| builder.base_url(base_url),
+        )
+        .retry(retry_policy)
+        .build()
+}
+
+/// Reports each fetch, verification and cache lookup to `metrics`; see [`Metrics`].
+pub fn new_client_with_metrics<S: Scheme<B> + 'static, B>(
+    scheme: S,
+    base_url: &str,
+    metrics: Arc<dyn Metrics>,
+) -> Result<DrandClient<B>, DrandClientError> {
+    DrandClientBuilder::new()
+        .scheme(scheme)
+        .base_url(base_url)
+        .metrics(metrics)
+        .build()
+}
+
+/// Makes every request through `transport`, e.g. to share an existing reqwest client
+/// or route through a proxy. The transport's own timeout applies.
+pub fn new_client_with_transport<S: Scheme<B> + 'static, B, T: Transport>(
+    scheme: S,
+    base_url: &str,
+    transport: T,
+) -> Result<DrandClient<B, T>, DrandClientError> {
+    DrandClientBuilder::new()
+        .scheme(scheme)
+        .base_url(base_url)
+        .build_with_transport(transport)
+}
+
+pub(crate) fn new_client_with_transport_and_options<B, T: Transport>(
+    scheme: SchemeChoice<B>,
+    base_urls: Vec<String>,
+    http_transport: T,
+    mut options: ClientOptions,
+) -> Result<DrandClient<B, T>, DrandClientError> {
+    let retry_policy = options.retry_policy.clone();
+    let endpoints = Endpoints::new(base_urls);
+    let chain_hash = options.chain_hash_path.as_deref();
+    let fetch_info = |paths: Paths| {
+        endpoints
+            .first_success(|base_url| {
+                let url = paths.info_url(base_url, chain_hash);
+                retry_policy.retry(|| http_transport.fetch(&url))
+            })
+            .map_err(DrandClientError::NotResponding)
+            .and_then(|body| Ok((parse_chain_info(&body)?, unknown_metadata(&body))))
+    };
+    // detecting asks for the v2 info and falls back to v1 if no relay answers it, but there's
+    // no layout to detect if the paths were given. With the chain info supplied there's no need
+    // for the info, so the latest v2 beacon is asked for instead
+    let templates = options.path_templates.take();
+    let (api_version, fetched) = match options.api_version {
+        Some(api_version) => (api_version, None),
+        None if templates.is_some() => (ApiVersion::V1, None),
+        None if options.chain_info.is_some() => {
+            let serves_v2 = endpoints
+                .first_success(|base_url| {
+                    let url =
+                        Paths::from(ApiVersion::V2).beacon_url(base_url, chain_hash, "latest");
+                    retry_policy.retry(|| http_transport.fetch(&url))
+                })
+                .is_ok();
+            let api_version = if serves_v2 {
+                ApiVersion::V2
+            } else {
+                ApiVersion::V1
+            };
+            (api_version, None)
+        }
+        None => match fetch_info(Paths::from(ApiVersion::V2)) {
+            Ok(chain_info) => (ApiVersion::V2, Some(chain_info)),
+            Err(_) => (ApiVersion::V1, None),
+        },
+    };
+    let paths = Paths {
+        api_version,
+        templates,
+    };
+    let chain_picked = options.chain_info.is_some() || options.expected_chain_hash.is_some();
+    let (chain_info, unknown_metadata) = match (options.chain_info.take(), fetched) {
+        (Some(chain_info), _) => {
+            chain_info.validate()?;
+            (chain_info, vec![])
+        }
+        (None, Some(fetched)) => fetched,
+        (None, None) => fetch_info(paths.clone())?,
+    };
+    options.validate(&chain_info)?;
+    let scheme = scheme.for_chain(&chain_info)?;
+    check_scheme(scheme.as_ref(), &chain_info)?;
+    let warnings = chain_info_warnings(&chain_info, unknown_metadata, chain_picked);
+    let client = DrandClient {
+        transport: http_transport,
+        chain_info,
+        scheme,
+        endpoints,
+        chain_hash: options.chain_hash_path,
+        paths,
+        retry_policy,
+        cache: options
+            .cache_capacity
+            .map(|capacity| Arc::new(BeaconCache::new(capacity))),
+        latest: options
+            .conditional_latest
+            .then(|| Arc::new(LatestBeacon::new())),
+        metrics: options.metrics,
+        clock: options.clock,
+        future_round_tolerance: options.future_round_tolerance,
+        warnings,
+    };
+
+    Ok(client)
+}
+
+/// A round either served from the cache or downloaded but not yet verified.
+enum Lookup<B> {
+    Cached(B),
+    Fetched(u64, FetchedBeacon<B>),
+}
+
+/// A client for whichever network the relay serves, its scheme selected from the `schemeID`
+/// the relay advertises. For other options, use [`DrandClientBuilder::auto_scheme`].
+#[cfg(all(feature = "chained", feature = "unchained"))]
+pub fn new_auto_client(base_url: &str) -> Result<DrandClient<AnyBeacon>, DrandClientError> {
+    DrandClientBuilder::new()
+        .auto_scheme()
+        .base_url(base_url)
+        .build()
+}
+
+impl<B> DrandClient<B> {
+    /// A client that trusts `chain_info` rather than fetching it from the relay's `/info`, e.g.
+    /// for chain info embedded at build time. It's validated, but pin its hash or public key with
+    /// [`DrandClientBuilder`] to check it against values you already trust.
+    pub fn from_chain_info<S: Scheme<B> + 'static>(
+        scheme: S,
+        base_url: &str,
+        chain_info: ChainInfo,
+    ) -> Result<Self, DrandClientError> {
+        DrandClientBuilder::new()
+            .scheme(scheme)
+            .base_url(base_url)
+            .chain_info(chain_info)
+            .build()
+    }
+}
+
+#[cfg(feature = "chained")]
+impl DrandClient<ChainedBeacon> {
+    /// A client for [`networks::MAINNET`], checking its chain info against the known hash.
+    pub fn mainnet() -> Result<Self, DrandClientError> {
+        DrandClientBuilder::new()
+            .scheme(&ChainedScheme {})
+            .base_url(networks::MAINNET.base_url)
+            .chain_hash(networks::MAINNET.chain_hash)
+            .build()
+    }
+}
+
+#[cfg(feature = "unchained")]
+impl DrandClient<UnchainedBeacon> {
+    /// A client for [`networks::QUICKNET`], checking its chain info against the known hash.
+    #[cfg(feature = "g1")]
+    pub fn quicknet() -> Result<Self, DrandClientError> {
+        DrandClientBuilder::new()
+            .scheme(&UnchainedSchemeOnG1Rfc9380 {})
+            .base_url(networks::QUICKNET.base_url)
+            .chain_hash(networks::QUICKNET.chain_hash)
+            .build()
+    }
+
+    /// A client for [`networks::TESTNET_UNCHAINED`], checking its chain info against the known hash.
+    pub fn testnet_unchained() -> Result<Self, DrandClientError> {
+        DrandClientBuilder::new()
+            .scheme(&UnchainedScheme {})
+            .base_url(networks::TESTNET_UNCHAINED.base_url)
+            .chain_hash(networks::TESTNET_UNCHAINED.chain_hash)
+            .build()
+    }
+}
+
+impl<B, T> DrandClient<B, T> {
+    pub fn chain_info(&self) -> &ChainInfo {
+        &self.chain_info
+    }
+
+    /// The relay's `schemeID`, e.g. to log which scheme an auto client settled on.
+    pub fn scheme_id(&self) -> &str {
+        &self.chain_info.scheme_id
+    }
+
+    pub fn network_summary(&self) -> NetworkSummary {
+        self.chain_info.summary()
+    }
+
+    /// The base URL of the relay that most recently responded successfully.
+    pub fn base_url(&self) -> &str {
+        self.endpoints.preferred()
+    }
+
+    /// The URL the beacon tagged `tag` would be fetched from, e.g. `beacon_url("latest")`.
+    pub fn beacon_url(&self, tag: &str) -> String {
+        self.paths
+            .beacon_url(self.base_url(), self.chain_hash.as_deref(), tag)
+    }
+
+    /// The URL the chain info is fetched from.
+    pub fn info_url(&self) -> String {
+        self.paths
+            .info_url(self.base_url(), self.chain_hash.as_deref())
+    }
+
+    /// The HTTP path layout requests are sent with, as configured or detected on construction,
+    /// unless `path_templates` replaced it.
+    pub fn api_version(&self) -> ApiVersion {
+        self.paths.api_version
+    }
+
+    /// What was unexpected about the chain info on construction, e.g. metadata fields the client
+    /// doesn't know, which didn't stop the client being built.
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+}
+
+impl<B, T> DrandClient<B, T>
+where
+    B: DeserializeOwned + Clone + Beacon,
+    T: Transport,
+{
+    /// Checks that a relay is reachable and still serving the chain the client was built for, by
+    /// re-fetching the chain info, e.g. for a readiness probe. No beacon is fetched.
+    pub fn health(&self) -> Result<(), DrandClientError> {
+        let chain_info = self
+            .endpoints
+            .first_success(|base_url| {
+                let url = self.paths.info_url(base_url, self.chain_hash.as_deref());
+                self.retry_policy.retry(|| self.transport.fetch(&url))
+            })
+            .map_err(DrandClientError::NotResponding)
+            .and_then(|body| parse_chain_info(&body))?;
+        check_same_chain(&self.chain_info, &chain_info)
+    }
+
+    pub fn latest_randomness(&self) -> Result<B, DrandClientError> {
+        self.fetch_latest().map(|fetched| fetched.beacon)
+    }
+
+    /// The latest beacon, along with when the relay says it goes stale, i.e. when to poll next.
+    pub fn latest_randomness_with_expiry(&self) -> Result<FetchedBeacon<B>, DrandClientError> {
+        self.fetch_latest()
+    }
+
+    /// The latest beacon, along with when the round after it is emitted, i.e. how long to sleep
+    /// before polling for the next one.
+    pub fn latest_randomness_with_timing(&self) -> Result<(B, SystemTime), DrandClientError> {
+        let beacon = self.latest_randomness()?;
+        let next_round = beacon
+            .round_number()
+            .checked_add(1)
+            .and_then(Round::new)
+            .ok_or(InvalidRound)?;
+        let next_round_at = time_of_round(&self.chain_info, next_round)?;
+        Ok((beacon, UNIX_EPOCH + Duration::from_secs(next_round_at)))
+    }
+
+    /// The round the chain is on according to the client's clock. A clock set before genesis,
+    /// as on devices without a real-time clock, is an error rather than a guess at round 1.
+    pub fn current_round(&self) -> Result<Round, DrandClientError> {
+        let now = self.clock.now_unix();
+        if now < self.chain_info.genesis_time {
+            return Err(DrandClientError::ClockBeforeGenesis);
+        }
+        Ok(round_at(&self.chain_info, now))
+    }
+
+    pub fn time_of_round(&self, round: Round) -> Result<u64, DrandClientError> {
+        time_of_round(&self.chain_info, round)
+    }
+
+    /// The beacon that was current at `unix_secs`, i.e. the latest one emitted at or before it.
+    pub fn randomness_at_time(&self, unix_secs: u64) -> Result<B, DrandClientError> {
+        if unix_secs < self.chain_info.genesis_time {
+            return Err(InvalidRound);
+        }
+        self.randomness(round_at(&self.chain_info, unix_secs).get())
+    }
+
+    pub fn randomness(&self, round_number: u64) -> Result<B, DrandClientError> {
+        self.lookup(round_number)
+            .and_then(|lookup| self.complete(lookup))
+    }
+
+    /// `round_number`'s beacon, along with the chain info it was verified against.
+    pub fn randomness_with_context(
+        &self,
+        round_number: u64,
+    ) -> Result<BeaconWithContext<'_, B>, DrandClientError> {
+        let beacon = self.randomness(round_number)?;
+        Ok(BeaconWithContext {
+            beacon,
+            chain_info: &self.chain_info,
+        })
+    }
+
+    /// The latest beacon, along with the chain info it was verified against.
+    pub fn latest_randomness_with_context(
+        &self,
+    ) -> Result<BeaconWithContext<'_, B>, DrandClientError> {
+        let beacon = self.latest_randomness()?;
+        Ok(BeaconWithContext {
+            beacon,
+            chain_info: &self.chain_info,
+        })
+    }
+
+    /// Fetches `round_number` from the relay, bypassing the cache, along with when the relay
+    /// says the response goes stale.
+    pub fn randomness_with_expiry(
+        &self,
+        round_number: u64,
+    ) -> Result<FetchedBeacon<B>, DrandClientError> {
+        if round_number == 0 {
+            return Err(InvalidRound);
+        }
+        let fetched = self
+            .fetch_unverified(&format!("{}", round_number))
+            .and_then(|fetched| self.verify_fetched(fetched))?;
+        if let Some(cache) = &self.cache {
+            cache.insert(round_number, fetched.beacon.clone());
+        }
+        Ok(fetched)
+    }
+
+    /// Fetches `round_number` from every relay the client was built with rather than failing
+    /// over between them, verifying each, and returns the beacon only if at least
+    /// `min_agreement` relays served identical randomness, e.g. to detect a single compromised
+    /// relay. Relays are asked one after another, and the cache is bypassed.
+    pub fn randomness_quorum(
+        &self,
+        round_number: u64,
+        min_agreement: usize,
+    ) -> Result<Quorum<B>, DrandClientError> {
+        if round_number == 0 {
+            return Err(InvalidRound);
+        }
+        if min_agreement == 0 {
+            return Err(DrandClientError::InvalidConfig {
+                reason: "a quorum needs at least one relay to agree".to_string(),
+            });
+        }
+        let tag = round_number.to_string();
+        let mut failed = vec![];
+        let mut groups: Vec<([u8; RANDOMNESS_LEN], B, Vec<String>)> = vec![];
+        for base_url in self.endpoints.all() {
+            let verified = self
+                .fetch_from(base_url, &tag)
+                .map(|beacon| (beacon.randomness_bytes(), beacon));
+            match verified {
+                Ok((Ok(randomness), beacon)) => {
+                    match groups.iter_mut().find(|(r, _, _)| *r == randomness) {
+                        Some((_, _, relays)) => relays.push(base_url.clone()),
+                        None => groups.push((randomness, beacon, vec![base_url.clone()])),
+                    }
+                }
+                _ => failed.push(base_url.clone()),
+            }
+        }
+
+        // reversed so ties go to the randomness the earliest relay served
+        let most_agreed = groups
+            .iter()
+            .enumerate()
+            .rev()
+            .max_by_key(|(_, (_, _, relays))| relays.len())
+            .map(|(index, _)| index);
+        let (beacon, agreeing) = match most_agreed {
+            Some(index) => {
+                let (_, beacon, agreeing) = groups.swap_remove(index);
+                (Some(beacon), agreeing)
+            }
+            None => (None, vec![]),
+        };
+        let mut disagreeing: Vec<String> = groups
+            .into_iter()
+            .flat_map(|(_, _, relays)| relays)
+            .collect();
+        disagreeing.sort_by_key(|base_url| self.endpoints.all().iter().position(|b| b == base_url));
+        match beacon {
+            Some(beacon) if agreeing.len() >= min_agreement => Ok(Quorum {
+                beacon,
+                agreeing,
+                disagreeing,
+                failed,
+            }),
+            _ => Err(DrandClientError::NoQuorum {
+                agreeing: agreeing.len(),
+                required: min_agreement,
+                disagreeing,
+                failed,
+            }),
+        }
+    }
+
+    /// Fetches and verifies the beacon tagged `tag` from `base_url` alone.
+    fn fetch_from(&self, base_url: &str, tag: &str) -> Result<B, DrandClientError> {
+        let url = self
+            .paths
+            .beacon_url(base_url, self.chain_hash.as_deref(), tag);
+        let downloaded = self
+            .retry_policy
+            .retry(|| {
+                self.transport
+                    .fetch_response(&url)
+                    .and_then(HttpResponse::json)
+            })
+            .map_err(fetch_error);
+        self.parse_downloaded(tag, downloaded)
+            .and_then(|fetched| self.verify_fetched(fetched))
+            .map(|fetched| fetched.beacon)
+    }
+
+    /// Fetches `round_number` from the relay **without verifying it**, for beacons whose source
+    /// is already trusted, e.g. a gateway that verifies them itself. The round number is still
+    /// checked, but nothing else is, and the beacon isn't cached. Prefer [`Self::randomness`].
+    pub fn randomness_unverified(&self, round_number: u64) -> Result<B, DrandClientError> {
+        if round_number == 0 {
+            return Err(InvalidRound);
+        }
+        self.fetch_unverified(&format!("{}", round_number))
+            .map(|fetched| fetched.beacon)
+    }
+
+    /// Fetches `round_number` from the relay, bypassing the cache, along with how long
+    /// downloading, parsing and verifying it each took, for finding where time goes.
+    pub fn randomness_timed(
+        &self,
+        round_number: u64,
+    ) -> Result<VerifiedBeacon<B>, DrandClientError> {
+        if round_number == 0 {
+            return Err(InvalidRound);
+        }
+        let timed = self.fetch_timed(&format!("{}", round_number))?;
+        if let Some(cache) = &self.cache {
+            cache.insert(round_number, timed.beacon.clone());
+        }
+        Ok(timed)
+    }
+
+    /// The latest beacon, along with where the time went fetching it; see
+    /// [`Self::randomness_timed`].
+    pub fn latest_randomness_timed(&self) -> Result<VerifiedBeacon<B>, DrandClientError> {
+        self.fetch_timed("latest")
+    }
+
+    /// The latest beacon, **without verifying it**; see [`Self::randomness_unverified`].
+    pub fn latest_randomness_unverified(&self) -> Result<B, DrandClientError> {
+        self.fetch_unverified("latest")
+            .map(|fetched| fetched.beacon)
+    }
+
+    pub fn clear_cache(&self) {
+        if let Some(cache) = &self.cache {
+            cache.clear();
+        }
+        if let Some(latest) = &self.latest {
+            latest.clear();
+        }
+    }
+
+    /// The cheap half of [`Self::randomness`]: serves the round from the cache or downloads it,
+    /// leaving any verification to [`Self::complete`].
+    fn lookup(&self, round_number: u64) -> Result<Lookup<B>, DrandClientError> {
+        if round_number == 0 {
+            return Err(InvalidRound);
+        }
+        if let Some(beacon) = self.cache.as_ref().and_then(|c| c.get(round_number)) {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(round = round_number, "beacon cache hit");
+            self.metrics.on_cache_hit();
+            return Ok(Lookup::Cached(beacon));
+        }
+        if self.cache.is_some() {
+            self.metrics.on_cache_miss();
+        }
+        self.fetch_unverified(&format!("{}", round_number))
+            .map(|fetched| Lookup::Fetched(round_number, fetched))
+    }
+
+    fn complete(&self, lookup: Lookup<B>) -> Result<B, DrandClientError> {
+        match lookup {
+            Lookup::Cached(beacon) => Ok(beacon),
+            Lookup::Fetched(round_number, fetched) => {
+                let fetched = self.verify_fetched(fetched)?;
+                if let Some(cache) = &self.cache {
+                    cache.insert(round_number, fetched.beacon.clone());
+                }
+                Ok(fetched.beacon)
+            }
+        }
+    }
+
+    fn fetch_beacon_tag(&self, tag: &str) -> Result<FetchedBeacon<B>, DrandClientError> {
+        self.fetch_unverified(tag)
+            .and_then(|fetched| self.verify_fetched(fetched))
+    }
+
+    /// Downloads and parses the beacon at `public/{tag}`, reporting failures to the metrics.
+    /// There's no point verifying a beacon for the wrong round, so that's checked first.
+    fn fetch_unverified(&self, tag: &str) -> Result<FetchedBeacon<B>, DrandClientError> {
+        let downloaded = self.download(tag);
+        self.parse_downloaded(tag, downloaded)
+    }
+
+    fn download(&self, tag: &str) -> Result<HttpResponse, DrandClientError> {
+        self.download_if_modified(tag, None)?
+            .ok_or(DrandClientError::NotResponding(HttpError::NotModified))
+    }
+
+    /// Downloads the beacon tagged `tag`, or nothing if the relay says it still matches `validators`.
+    fn download_if_modified(
+        &self,
+        tag: &str,
+        validators: Option<&Validators>,
+    ) -> Result<Option<HttpResponse>, DrandClientError> {
+        let fetched = self.endpoints.first_success(|base_url| {
+            let url = self
+                .paths
+                .beacon_url(base_url, self.chain_hash.as_deref(), tag);
+            #[cfg(feature = "tracing")]
+            let _span = tracing::debug_span!("fetch_beacon", url = %url, tag).entered();
+            self.retry_policy.retry(|| {
+                let res = match validators {
+                    Some(validators) => self.transport.fetch_if_changed(&url, validators),
+                    None => self.transport.fetch_response(&url),
+                };
+                match res {
+                    Err(HttpError::NotModified) => Ok(None),
+                    res => res.and_then(HttpResponse::json).map(Some),
+                }
+            })
+        });
+        fetched.map_err(fetch_error)
+    }
+
+    /// The latest beacon, asked for only if it's changed since the last one if the client was
+    /// built with `conditional_latest`.
+    fn fetch_latest(&self) -> Result<FetchedBeacon<B>, DrandClientError> {
+        let Some(latest) = &self.latest else {
+            return self.fetch_beacon_tag("latest");
+        };
+        let previous = latest.get();
+        let validators = previous.as_ref().map(|(_, validators)| validators);
+        let downloaded = match self.download_if_modified("latest", validators) {
+            Ok(Some(res)) => Ok(res),
+            Ok(None) => match previous {
+                // unchanged since the last beacon, which was verified when it was fetched
+                Some((beacon, _)) => {
+                    self.metrics.on_cache_hit();
+                    return Ok(FetchedBeacon {
+                        beacon,
+                        valid_until: None,
+                    });
+                }
+                // a relay answering a request that wasn't conditional with nothing
+                None => Err(DrandClientError::NotResponding(HttpError::NotModified)),
+            },
+            Err(e) => Err(e),
+        };
+        let validators = downloaded.as_ref().map(Validators::of).unwrap_or_default();
+        let fetched = self
+            .parse_downloaded("latest", downloaded)
+            .and_then(|fetched| self.verify_fetched(fetched))?;
+        latest.set(fetched.beacon.clone(), validators);
+        Ok(fetched)
+    }
+
+    /// Parses a beacon from [`Self::download`], reporting either's failure to the metrics.
+    fn parse_downloaded(
+        &self,
+        tag: &str,
+        downloaded: Result<HttpResponse, DrandClientError>,
+    ) -> Result<FetchedBeacon<B>, DrandClientError> {
+        let result = downloaded.and_then(|res| parse_beacon(tag, res));
+        if let Err(e) = &result {
+            self.metrics.on_fetch_error(e);
+        }
+        result
+    }
+
+    /// [`Self::fetch_beacon_tag`], timing each step.
+    fn fetch_timed(&self, tag: &str) -> Result<VerifiedBeacon<B>, DrandClientError> {
+        let started = timer::now();
+        let downloaded = self.download(tag);
+        let fetch_time = timer::elapsed(started);
+        let started = timer::now();
+        let parsed = self.parse_downloaded(tag, downloaded)?;
+        let parse_time = timer::elapsed(started);
+        let started = timer::now();
+        let verified = self.verify_fetched(parsed)?;
+        let verify_time = timer::elapsed(started);
+        Ok(VerifiedBeacon {
+            beacon: verified.beacon,
+            fetch_time,
+            parse_time,
+            verify_time,
+        })
+    }
+
+    /// Verifies a beacon from [`Self::fetch_unverified`], reporting the outcome to the metrics.
+    fn verify_fetched(
+        &self,
+        fetched: FetchedBeacon<B>,
+    ) -> Result<FetchedBeacon<B>, DrandClientError> {
+        if let Some(tolerance) = self.future_round_tolerance {
+            let round = fetched.beacon.round_number();
+            let checked =
+                reject_future_round(&self.chain_info, round, self.clock.now_unix(), tolerance);
+            if let Err(e) = checked {
+                self.metrics.on_fetch_error(&e);
+                return Err(e);
+            }
+        }
+        let started = timer::now();
+        let verified = self.scheme.verify(&self.chain_info, fetched.beacon);
+        self.metrics.on_verify_duration(timer::elapsed(started));
+        let result = verified
+            .map(|beacon| FetchedBeacon {
+                beacon,
+                valid_until: fetched.valid_until,
+            })
+            .map_err(|e| {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(
+                    scheme = %self.chain_info.scheme_id,
+                    error = %e,
+                    "beacon failed verification"
+                );
+                DrandClientError::InvalidBeacon {
+                    reason: e.to_string(),
+                }
+            });
+        match &result {
+            Ok(_) => self.metrics.on_fetch_success(),
+            Err(e) => self.metrics.on_fetch_error(e),
+        }
+        result
+    }
+}
+
+impl<B, T> DrandClient<B, T>
+where
+    B: DeserializeOwned + Clone + Beacon,
+    T: Transport,
+{
+    pub fn watch(&self) -> Watch<'_, B, T> {
+        Watch::new(self)
+    }
+
+    /// Calls `f` with each new beacon as it's emitted, starting with the latest one, or with why a
+    /// round couldn't be fetched, e.g. to persist or publish beacons as they arrive. Watching goes
+    /// on until `f` breaks, whatever the errors, and returns what `f` broke with.
+    pub fn watch_with<R>(&self, f: impl FnMut(Result<B, DrandClientError>) -> ControlFlow<R>) -> R {
+        match self.watch().try_for_each(f) {
+            ControlFlow::Break(value) => value,
+            ControlFlow::Continue(()) => unreachable!("watches never end"),
+        }
+    }
+
+    /// Every beacon from round 1 onwards, fetched lazily; see [`History`].
+    pub fn history(&self) -> History<'_, B, T> {
+        History::new(self)
+    }
+}
+
+impl<B, T> DrandClient<B, T>
+where
+    B: DeserializeOwned + Clone + Beacon + Send,
+    T: Transport + Sync,
+{
+    /// Fetches and verifies every round from `start` to `end` inclusive, with up to
+    /// `RANGE_CONCURRENCY` requests in flight. Results are returned in round order, and a
+    /// failure to fetch one round doesn't prevent the others being returned. Ranges of more than
+    /// `MAX_RANGE_LEN` rounds are rejected as [`InvalidRound`].
+    /// With the `rayon` feature the rounds are downloaded first and then verified across rayon's
+    /// thread pool, rather than on the few threads doing the downloading.
+    pub fn randomness_range(
+        &self,
+        start: u64,
+        end: u64,
+    ) -> Result<Vec<Result<B, DrandClientError>>, DrandClientError> {
+        if start == 0 || start > end || end - start >= MAX_RANGE_LEN {
+            return Err(InvalidRound);
+        }
+        let len = end - start + 1;
+
+        // each worker takes the next round not yet taken, so a slow round holds up only its worker
+        let next = AtomicU64::new(0);
+        let slots: Vec<_> = (0..len).map(|_| Mutex::new(None)).collect();
+        thread::scope(|scope| {
+            for _ in 0..RANGE_CONCURRENCY.min(len as usize) {
+                scope.spawn(|| loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    if index >= len {
+                        break;
+                    }
+                    let lookup = self.lookup(start + index);
+                    #[cfg(not(feature = "rayon"))]
+                    let lookup = lookup.and_then(|lookup| self.complete(lookup));
+                    *slots[index as usize].lock().expect("range slot poisoned") = Some(lookup);
+                });
+            }
+        });
+        let lookups: Vec<_> = slots
+            .into_iter()
+            .map(|slot| {
+                slot.into_inner()
+                    .expect("range slot poisoned")
+                    .expect("every round in the range is looked up")
+            })
+            .collect();
+
+        #[cfg(feature = "rayon")]
+        let lookups = lookups
+            .into_par_iter()
+            .map(|lookup| lookup.and_then(|lookup| self.complete(lookup)))
+            .collect();
+        Ok(lookups)
+    }
+
+    /// The latest `n` beacons, newest first, fetching the rounds before the latest one as
+    /// [`Self::randomness_range`] does. Fewer are returned if the chain hasn't emitted `n` yet.
+    pub fn latest_n(&self, n: u64) -> Result<Vec<B>, DrandClientError> {
+        if n == 0 {
+            return Ok(vec![]);
+        }
+        let latest = self.latest_randomness()?;
+        let end = latest.round_number();
+        let start = end.saturating_sub(n - 1).max(1);
+        let mut beacons = if start < end {
+            self.randomness_range(start, end - 1)?
+                .into_iter()
+                .collect::<Result<Vec<_>, _>>()?
+        } else {
+            vec![]
+        };
+        beacons.push(latest);
+        beacons.reverse();
+        Ok(beacons)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::chained::ChainedScheme;
+    use crate::client::new_client_with_transport_and_options;
+    use crate::fixtures;
+    use crate::http::{HttpResponse, HttpTransport, Transport, Validators};
+    use crate::options::{ClientOptions, SchemeChoice};
+    use crate::DrandClientError::InvalidRound;
+    use crate::{
+        new_auto_client, new_chained_client, new_client_with_timeout, new_client_with_transport,
+        new_unchained_client, new_unchained_g1_rfc9380_client, AnyBeacon, AnyScheme, ApiVersion,
+        ChainInfo, ChainedBeacon, DrandClient, DrandClientBuilder, DrandClientError, HttpError,
+        Metrics, MockClock, MockTransport, Scheme, SchemeError, UnchainedBeacon, UnchainedScheme,
+        Warning, MAX_RANGE_LEN,
+    };
+    use reqwest::blocking::Client;
+    use reqwest::StatusCode;
+    use std::num::NonZeroUsize;
+    use std::ops::ControlFlow;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn request_chained_randomness_success() -> Result<(), DrandClientError> {
+        let chained_url = "https://api.drand.sh";
+        let client = new_chained_client(chained_url)?;
+        let randomness = client.latest_randomness()?;
+        assert!(randomness.round_number > 0);
+        Ok(())
+    }
+
+    #[test]
+    fn request_unchained_randomness_success() -> Result<(), DrandClientError> {
+        let unchained_url = "https://pl-eu.testnet.drand.sh/7672797f548f3f4748ac4bf3352fc6c6b6468c9ad40ad456a397545c6e2df5bf";
+        let client = new_unchained_client(unchained_url)?;
+        let randomness = client.latest_randomness()?;
+        assert!(randomness.round_number > 0);
+        Ok(())
+    }
+
+    #[test]
+    fn request_quicknet_randomness_success() -> Result<(), DrandClientError> {
+        let quicknet_url =
+            "https://api.drand.sh/52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971";
+        let client = new_unchained_g1_rfc9380_client(quicknet_url)?;
+        let randomness = client.latest_randomness()?;
+        assert!(randomness.round_number > 0);
+        Ok(())
+    }
+
+    #[test]
+    fn request_well_known_networks_success() -> Result<(), DrandClientError> {
+        assert!(DrandClient::mainnet()?.latest_randomness()?.round_number > 0);
+        assert!(DrandClient::quicknet()?.latest_randomness()?.round_number > 0);
+        assert!(
+            DrandClient::testnet_unchained()?
+                .latest_randomness()?
+                .round_number
+                > 0
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn request_auto_randomness_success() -> Result<(), DrandClientError> {
+        let unchained_url = "https://pl-eu.testnet.drand.sh/7672797f548f3f4748ac4bf3352fc6c6b6468c9ad40ad456a397545c6e2df5bf";
+        let client = new_auto_client(unchained_url)?;
+        assert_eq!(client.scheme_id(), "pedersen-bls-unchained");
+        match client.latest_randomness()? {
+            AnyBeacon::Unchained(beacon) => assert!(beacon.round_number > 0),
+            AnyBeacon::Chained(_) => panic!("expected an unchained beacon"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn request_unchained_randomness_wrong_client_error() -> Result<(), DrandClientError> {
+        let unchained_url = "https://pl-eu.testnet.drand.sh/7672797f548f3f4748ac4bf3352fc6c6b6468c9ad40ad456a397545c6e2df5bf";
+        let result = new_chained_client(unchained_url);
+        assert!(matches!(
+            result,
+            Err(DrandClientError::SchemeMismatch { .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn request_chained_randomness_wrong_client_error() -> Result<(), DrandClientError> {
+        let chained_url = "https://api.drand.sh";
+        let result = new_unchained_client(chained_url);
+        assert!(matches!(
+            result,
+            Err(DrandClientError::SchemeMismatch { .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn request_genesis_returns_error() -> Result<(), DrandClientError> {
+        let chained_url = "https://api.drand.sh";
+        let client = new_chained_client(chained_url);
+        let result = client?.randomness(0);
+        assert!(result.is_err());
+        assert!(matches!(result, Err(InvalidRound)));
+        Ok(())
+    }
+
+    #[test]
+    fn network_is_described_by_the_chain_info() {
+        let info = fixtures::chain_info("pedersen-bls-unchained");
+        let client = fixtures::unreachable_client(&UnchainedScheme {}, info.clone());
+        assert_eq!(client.scheme_id(), "pedersen-bls-unchained");
+        assert_eq!(client.network_summary(), info.summary());
+        let any =
+            fixtures::unreachable_client(&AnyScheme::Unchained(&UnchainedScheme {}), info.clone());
+        assert_eq!(any.network_summary(), info.summary());
+    }
+
+    #[test]
+    fn urls_are_built_without_fetching() {
+        let info = fixtures::chain_info("pedersen-bls-unchained");
+        let mut client = fixtures::unreachable_client(&UnchainedScheme {}, info);
+        assert_eq!(client.info_url(), "http://127.0.0.1:1/info");
+        assert_eq!(
+            client.beacon_url("latest"),
+            "http://127.0.0.1:1/public/latest"
+        );
+
+        client.chain_hash = Some("abcd".to_string());
+        assert_eq!(client.info_url(), "http://127.0.0.1:1/abcd/info");
+        assert_eq!(client.beacon_url("7"), "http://127.0.0.1:1/abcd/public/7");
+    }
+
+    #[test]
+    fn range_reports_each_round_in_order() {
+        let info = fixtures::chain_info("pedersen-bls-unchained");
+        let client = fixtures::unreachable_client(&UnchainedScheme {}, info);
+        let results = client.randomness_range(1, 20).unwrap();
+        assert_eq!(results.len(), 20);
+        assert!(results
+            .iter()
+            .all(|r| matches!(r, Err(DrandClientError::NotResponding(_)))));
+    }
+
+    #[test]
+    fn range_must_start_after_genesis_and_be_ascending() {
+        let info = fixtures::chain_info("pedersen-bls-unchained");
+        let client = fixtures::unreachable_client(&UnchainedScheme {}, info);
+        assert!(matches!(client.randomness_range(0, 5), Err(InvalidRound)));
+        assert!(matches!(client.randomness_range(5, 4), Err(InvalidRound)));
+        assert!(matches!(
+            client.randomness_range(1, MAX_RANGE_LEN + 1),
+            Err(InvalidRound)
+        ));
+        assert!(matches!(
+            client.randomness_range(1, u64::MAX),
+            Err(InvalidRound)
+        ));
+
+        let last_rounds = client.randomness_range(u64::MAX - 2, u64::MAX).unwrap();
+        assert_eq!(last_rounds.len(), 3);
+    }
+
+    #[test]
+    fn request_unreachable_relay_times_out() {
+        let unroutable_url = "http://10.255.255.1";
+        let result = new_client_with_timeout(
+            &ChainedScheme {},
+            unroutable_url,
+            Duration::from_millis(100),
+        );
+        assert!(matches!(result, Err(DrandClientError::NotResponding(_))));
+    }
+
+    #[test]
+    fn injected_transport_routes_through_its_proxy() {
+        let client = Client::builder()
+            .proxy(reqwest::Proxy::all("http://127.0.0.1:1").unwrap())
+            .build()
+            .unwrap();
+        let result = new_client_with_transport(
+            &ChainedScheme {},
+            "http://api.drand.sh",
+            HttpTransport::with_client(client),
+        );
+        assert!(matches!(
+            result,
+            Err(DrandClientError::NotResponding(
+                HttpError::ConnectionFailed(_)
+            ))
+        ));
+    }
+
+    fn mock_relay(scheme_id: &str) -> MockTransport {
+        let info = fixtures::chain_info(scheme_id);
+        MockTransport::new().with_body("http://relay/info", &fixtures::chain_info_json(&info))
+    }
+
+    #[test]
+    fn unexpected_chain_info_is_warned_about() {
+        let mut info = fixtures::chain_info("pedersen-bls-unchained");
+        info.metadata.beacon_id = "evmnet".to_string();
+        let mut json: serde_json::Value =
+            serde_json::from_str(&fixtures::chain_info_json(&info)).unwrap();
+        json["metadata"]["region"] = "eu".into();
+        let transport = MockTransport::new().with_body("http://relay/info", &json.to_string());
+        let client =
+            new_client_with_transport(&UnchainedScheme {}, "http://relay", transport).unwrap();
+        assert_eq!(
+            client.warnings(),
+            [
+                Warning::UnknownMetadata {
+                    field: "region".to_string()
+                },
+                Warning::NonDefaultBeacon {
+                    beacon_id: "evmnet".to_string()
+                },
+            ]
+        );
+
+        let picked = DrandClientBuilder::new()
+            .scheme(&UnchainedScheme {})
+            .base_url("http://relay")
+            .chain_info(info)
+            .build_with_transport(MockTransport::new())
+            .unwrap();
+        assert!(picked.warnings().is_empty());
+        let default = new_client_with_transport(
+            &UnchainedScheme {},
+            "http://relay",
+            mock_relay("pedersen-bls-unchained"),
+        )
+        .unwrap();
+        assert!(default.warnings().is_empty());
+    }
+
+    #[test]
+    fn auto_clients_warn_about_unknown_metadata() {
+        let info = fixtures::chain_info("pedersen-bls-unchained");
+        let mut json: serde_json::Value =
+            serde_json::from_str(&fixtures::chain_info_json(&info)).unwrap();
+        json["metadata"]["region"] = "eu".into();
+        let transport = MockTransport::new().with_body("http://relay/info", &json.to_string());
+        let client = DrandClientBuilder::new()
+            .auto_scheme()
+            .base_url("http://relay")
+            .build_with_transport(transport)
+            .unwrap();
+        assert_eq!(
+            client.warnings(),
+            [Warning::UnknownMetadata {
+                field: "region".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn mock_relay_serves_verified_chained_beacon() {
+        let beacon = fixtures::chained_beacon(3, vec![9; 96]);
+        let transport = mock_relay("pedersen-bls-chained").with_body(
+            "http://relay/public/3",
+            &fixtures::chained_beacon_json(&beacon),
+        );
+        let client =
+            new_client_with_transport(&ChainedScheme {}, "http://relay", transport).unwrap();
+        assert_eq!(client.randomness(3).unwrap(), beacon);
+    }
+
+    #[test]
+    fn unchained_beacons_are_invalid_for_chained_clients() {
+        let beacon = fixtures::unchained_beacon(3);
+        let transport = mock_relay("pedersen-bls-chained").with_body(
+            "http://relay/public/3",
+            &fixtures::unchained_beacon_json(&beacon),
+        );
+        let client =
+            new_client_with_transport(&ChainedScheme {}, "http://relay", transport).unwrap();
+        match client.randomness(3) {
+            Err(DrandClientError::InvalidBeacon { reason }) => assert!(reason
+                .contains("expected chained beacon with previous_signature but field was missing")),
+            _ => panic!("expected an invalid beacon"),
+        }
+    }
+
+    #[test]
+    fn quorum_tells_conflicting_relays_from_failed_ones() {
+        // accepts any beacon, so relays can serve verified but conflicting randomness
+        struct AcceptAnything;
+        impl Scheme<UnchainedBeacon> for AcceptAnything {
+            fn supports(&self, _: &str) -> bool {
+                true
+            }
+            fn verify(
+                &self,
+                _: &ChainInfo,
+                beacon: UnchainedBeacon,
+            ) -> Result<UnchainedBeacon, SchemeError> {
+                Ok(beacon)
+            }
+        }
+
+        let info = fixtures::chain_info("pedersen-bls-unchained");
+        let beacon = fixtures::unchained_beacon(3);
+        let mut conflicting = beacon.clone();
+        conflicting.randomness[0] ^= 0x01;
+        let mut transport =
+            MockTransport::new().with_body("http://a/info", &fixtures::chain_info_json(&info));
+        for (relay, served) in [
+            ("http://a", &beacon),
+            ("http://b", &conflicting),
+            ("http://c", &beacon),
+        ] {
+            transport = transport.with_body(
+                &format!("{}/public/3", relay),
+                &fixtures::unchained_beacon_json(served),
+            );
+        }
+        let client = DrandClientBuilder::new()
+            .scheme(&AcceptAnything)
+            .base_url("http://a")
+            .base_url("http://b")
+            .base_url("http://c")
+            .base_url("http://d")
+            .build_with_transport(transport)
+            .unwrap();
+
+        let quorum = client.randomness_quorum(3, 2).unwrap();
+        assert_eq!(quorum.beacon, beacon);
+        assert_eq!(quorum.agreeing, vec!["http://a", "http://c"]);
+        assert_eq!(quorum.disagreeing, vec!["http://b"]);
+        assert_eq!(quorum.failed, vec!["http://d"]);
+    }
+
+    #[test]
+    fn quorum_needs_enough_relays_to_serve_the_same_beacon() {
+        let info = fixtures::chain_info("pedersen-bls-unchained");
+        let beacon = fixtures::unchained_beacon(3);
+        let mut tampered = beacon.clone();
+        tampered.randomness[0] ^= 0x01;
+        let mut transport = MockTransport::new();
+        for relay in ["http://a", "http://b", "http://c"] {
+            let served = if relay == "http://c" {
+                &tampered
+            } else {
+                &beacon
+            };
+            transport = transport
+                .with_body(
+                    &format!("{}/info", relay),
+                    &fixtures::chain_info_json(&info),
+                )
+                .with_body(
+                    &format!("{}/public/3", relay),
+                    &fixtures::unchained_beacon_json(served),
+                );
+        }
+        let client = DrandClientBuilder::new()
+            .scheme(&UnchainedScheme {})
+            .base_url("http://a")
+            .base_url("http://b")
+            .base_url("http://c")
+            .build_with_transport(transport)
+            .unwrap();
+
+        let quorum = client.randomness_quorum(3, 2).unwrap();
+        assert_eq!(quorum.beacon, beacon);
+        assert_eq!(quorum.agreeing, vec!["http://a", "http://b"]);
+        assert!(quorum.disagreeing.is_empty());
+        assert_eq!(quorum.failed, vec!["http://c"]);
+
+        match client.randomness_quorum(3, 3) {
+            Err(DrandClientError::NoQuorum {
+                agreeing: 2,
+                required: 3,
+                disagreeing,
+                failed,
+            }) => {
+                assert!(disagreeing.is_empty());
+                assert_eq!(failed, vec!["http://c"]);
+            }
+            _ => panic!("expected no quorum"),
+        }
+        assert!(matches!(
+            client.randomness_quorum(3, 0),
+            Err(DrandClientError::InvalidConfig { .. })
+        ));
+    }
+
+    #[test]
+    fn path_templates_reach_static_mirrors() {
+        let info = fixtures::chain_info("pedersen-bls-unchained");
+        let beacon = fixtures::unchained_beacon(3);
+        let transport = MockTransport::new()
+            .with_body(
+                "http://mirror/drand/info.json",
+                &fixtures::chain_info_json(&info),
+            )
+            .with_body(
+                "http://mirror/drand/rounds/3.json",
+                &fixtures::unchained_beacon_json(&beacon),
+            );
+        let client = DrandClientBuilder::new()
+            .scheme(&UnchainedScheme {})
+            .base_url("http://mirror")
+            .path_templates("/drand/info.json", "/drand/rounds/{round}.json")
+            .detect_api_version()
+            .build_with_transport(transport)
+            .unwrap();
+        assert_eq!(client.randomness(3).unwrap(), beacon);
+        assert_eq!(
+            client.beacon_url("latest"),
+            "http://mirror/drand/rounds/latest.json"
+        );
+        assert_eq!(
+            client.transport.requests(),
+            vec![
+                "http://mirror/drand/info.json",
+                "http://mirror/drand/rounds/3.json"
+            ]
+        );
+
+        let unplaced = DrandClientBuilder::new()
+            .scheme(&UnchainedScheme {})
+            .base_url("http://mirror")
+            .path_templates("info.json", "latest.json")
+            .build_with_transport(MockTransport::new());
+        assert!(matches!(
+            unplaced,
+            Err(DrandClientError::InvalidConfig { .. })
+        ));
+    }
+
+    #[test]
+    fn detected_v2_relays_are_reached_under_v2() {
+        let info = fixtures::chain_info("pedersen-bls-chained");
+        let beacon = fixtures::chained_beacon(3, vec![9; 96]);
+        let transport = MockTransport::new()
+            .with_body(
+                "http://relay/v2/beacons/default/info",
+                &fixtures::chain_info_json(&info),
+            )
+            .with_body(
+                "http://relay/v2/beacons/default/rounds/3",
+                &fixtures::chained_beacon_json(&beacon),
+            );
+        let client = DrandClientBuilder::new()
+            .scheme(&ChainedScheme {})
+            .base_url("http://relay")
+            .detect_api_version()
+            .build_with_transport(transport)
+            .unwrap();
+        assert_eq!(client.api_version(), ApiVersion::V2);
+        assert_eq!(client.randomness(3).unwrap(), beacon);
+        assert_eq!(
+            client.transport.requests(),
+            vec![
+                "http://relay/v2/beacons/default/info",
+                "http://relay/v2/beacons/default/rounds/3"
+            ]
+        );
+    }
+
+    #[test]
+    fn detection_falls_back_to_v1() {
+        let client: DrandClient<ChainedBeacon, _> = DrandClientBuilder::new()
+            .scheme(&ChainedScheme {})
+            .base_url("http://relay")
+            .detect_api_version()
+            .build_with_transport(mock_relay("pedersen-bls-chained"))
+            .unwrap();
+        assert_eq!(client.api_version(), ApiVersion::V1);
+        assert_eq!(client.beacon_url("latest"), "http://relay/public/latest");
+    }
+
+    #[test]
+    fn supplied_chain_info_is_not_fetched_to_detect_the_api_version() {
+        let info = fixtures::chain_info("pedersen-bls-unchained");
+        let info = ChainInfo::new(
+            &info.scheme_id,
+            info.public_key,
+            info.group_hash,
+            info.genesis_time,
+            info.period_seconds,
+            &info.metadata.beacon_id,
+        );
+        let beacon = fixtures::unchained_beacon(3);
+        let v2_relay = MockTransport::new().with_body(
+            "http://relay/v2/beacons/default/rounds/latest",
+            &fixtures::unchained_beacon_json(&beacon),
+        );
+        let client = DrandClientBuilder::new()
+            .scheme(&UnchainedScheme {})
+            .base_url("http://relay")
+            .chain_info(info.clone())
+            .detect_api_version()
+            .build_with_transport(v2_relay)
+            .unwrap();
+        assert_eq!(client.api_version(), ApiVersion::V2);
+        assert_eq!(
+            client.transport.requests(),
+            vec!["http://relay/v2/beacons/default/rounds/latest"]
+        );
+
+        let v1_relay = MockTransport::new();
+        let client = DrandClientBuilder::new()
+            .scheme(&UnchainedScheme {})
+            .base_url("http://relay")
+            .chain_info(info)
+            .detect_api_version()
+            .build_with_transport(v1_relay)
+            .unwrap();
+        assert_eq!(client.api_version(), ApiVersion::V1);
+        assert!(client
+            .transport
+            .requests()
+            .iter()
+            .all(|url| !url.ends_with("info")));
+    }
+
+    #[test]
+    fn randomness_at_time_fetches_the_round_current_then() {
+        let info = fixtures::chain_info("pedersen-bls-unchained");
+        let beacon = fixtures::unchained_beacon(3);
+        let transport = mock_relay("pedersen-bls-unchained").with_body(
+            "http://relay/public/3",
+            &fixtures::unchained_beacon_json(&beacon),
+        );
+        let client =
+            new_client_with_transport(&UnchainedScheme {}, "http://relay", transport).unwrap();
+        let round_three = info.genesis_time + 2 * info.period_seconds as u64;
+        assert_eq!(client.randomness_at_time(round_three).unwrap(), beacon);
+        assert_eq!(client.randomness_at_time(round_three + 29).unwrap(), beacon);
+        assert!(matches!(
+            client.randomness_at_time(info.genesis_time - 1),
+            Err(InvalidRound)
+        ));
+    }
+
+    #[test]
+    fn current_round_follows_the_clock() {
+        let info = fixtures::chain_info("pedersen-bls-unchained");
+        let clock = Arc::new(MockClock::new(
+            info.genesis_time + 2 * info.period_seconds as u64,
+        ));
+        let client = DrandClientBuilder::new()
+            .scheme(&UnchainedScheme {})
+            .base_url("http://relay")
+            .clock(clock.clone())
+            .build_with_transport(mock_relay("pedersen-bls-unchained"))
+            .unwrap();
+        assert_eq!(client.current_round().unwrap().get(), 3);
+        clock.advance(info.period_seconds as u64);
+        assert_eq!(client.current_round().unwrap().get(), 4);
+    }
+
+    #[test]
+    fn future_rounds_are_rejected_beyond_the_tolerance() {
+        let info = fixtures::chain_info("pedersen-bls-unchained");
+        let relay = mock_relay("pedersen-bls-unchained").with_body(
+            "http://relay/public/5",
+            &fixtures::unchained_beacon_json(&fixtures::unchained_beacon(5)),
+        );
+        let client = DrandClientBuilder::new()
+            .scheme(&UnchainedScheme {})
+            .base_url("http://relay")
+            .clock(Arc::new(MockClock::new(info.genesis_time)))
+            .reject_future_rounds(Duration::from_secs(3 * info.period_seconds as u64))
+            .build_with_transport(relay)
+            .unwrap();
+        assert!(matches!(
+            client.randomness(5),
+            Err(DrandClientError::InvalidBeacon { .. })
+        ));
+
+        let client = DrandClientBuilder::new()
+            .scheme(&UnchainedScheme {})
+            .base_url("http://relay")
+            .clock(Arc::new(MockClock::new(info.genesis_time)))
+            .reject_future_rounds(Duration::from_secs(4 * info.period_seconds as u64))
+            .build_with_transport(client.transport)
+            .unwrap();
+        assert_eq!(client.randomness(5).unwrap().round_number, 5);
+    }
+
+    #[test]
+    fn clock_before_genesis_has_no_current_round() {
+        let info = fixtures::chain_info("pedersen-bls-unchained");
+        let client = DrandClientBuilder::new()
+            .scheme(&UnchainedScheme {})
+            .base_url("http://relay")
+            .clock(Arc::new(MockClock::new(info.genesis_time - 1)))
+            .build_with_transport(mock_relay("pedersen-bls-unchained"))
+            .unwrap();
+        assert!(matches!(
+            client.current_round(),
+            Err(DrandClientError::ClockBeforeGenesis)
+        ));
+    }
+
+    #[test]
+    fn client_from_chain_info_does_not_fetch_it() {
+        let info = fixtures::chain_info("pedersen-bls-unchained");
+        let client =
+            DrandClient::from_chain_info(&UnchainedScheme {}, "http://127.0.0.1:1", info.clone())
+                .unwrap();
+        assert_eq!(client.chain_info(), &info);
+
+        let mut invalid = info;
+        invalid.period_seconds = 0;
+        assert!(matches!(
+            DrandClient::from_chain_info(&UnchainedScheme {}, "http://127.0.0.1:1", invalid),
+            Err(DrandClientError::InvalidChainInfo { .. })
+        ));
+    }
+
+    #[test]
+    fn watch_with_calls_back_until_told_to_stop() {
+        let relay = mock_relay("pedersen-bls-unchained")
+            .with_body(
+                "http://relay/public/latest",
+                &fixtures::unchained_beacon_json(&fixtures::unchained_beacon(3)),
+            )
+            .with_body(
+                "http://relay/public/4",
+                &fixtures::unchained_beacon_json(&fixtures::unchained_beacon(4)),
+            );
+        let client = new_client_with_transport(&UnchainedScheme {}, "http://relay", relay).unwrap();
+        let mut rounds = vec![];
+        let last = client.watch_with(|result| {
+            let round = result.unwrap().round_number;
+            rounds.push(round);
+            if round == 4 {
+                ControlFlow::Break(round)
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+        assert_eq!((rounds, last), (vec![3, 4], 4));
+
+        let relay_without_latest = mock_relay("pedersen-bls-unchained");
+        let client =
+            new_client_with_transport(&UnchainedScheme {}, "http://relay", relay_without_latest)
+                .unwrap();
+        let error = client.watch_with(|result| match result {
+            Ok(_) => ControlFlow::Continue(()),
+            Err(e) => ControlFlow::Break(e),
+        });
+        assert!(matches!(error, DrandClientError::RoundNotAvailable));
+    }
+
+    #[test]
+    fn mock_relay_serves_verified_unchained_beacon() {
+        let beacon = fixtures::unchained_beacon(3);
+        let transport = mock_relay("pedersen-bls-unchained").with_body(
+            "http://relay/public/latest",
+            &fixtures::unchained_beacon_json(&beacon),
+        );
+        let client =
+            new_client_with_transport(&UnchainedScheme {}, "http://relay", transport).unwrap();
+        assert_eq!(client.latest_randomness().unwrap(), beacon);
+        assert_eq!(
+            client.transport.requests(),
+            vec!["http://relay/info", "http://relay/public/latest"]
+        );
+    }
+
+    #[test]
+    fn mock_relay_range_is_verified_in_round_order() {
+        let mut forged = fixtures::unchained_beacon(2);
+        forged.round_number = 3;
+        let transport = (1..=5)
+            .filter(|round| *round != 3)
+            .fold(mock_relay("pedersen-bls-unchained"), |relay, round| {
+                relay.with_body(
+                    &format!("http://relay/public/{}", round),
+                    &fixtures::unchained_beacon_json(&fixtures::unchained_beacon(round)),
+                )
+            })
+            .with_body(
+                "http://relay/public/3",
+                &fixtures::unchained_beacon_json(&forged),
+            );
+        let client =
+            new_client_with_transport(&UnchainedScheme {}, "http://relay", transport).unwrap();
+        let results = client.randomness_range(1, 5).unwrap();
+        for (round, result) in (1..=5).zip(results) {
+            match result {
+                Ok(beacon) => assert_eq!(beacon, fixtures::unchained_beacon(round)),
+                Err(e) => {
+                    assert_eq!(round, 3);
+                    assert!(matches!(e, DrandClientError::InvalidBeacon { .. }));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn latest_randomness_comes_with_when_the_next_round_is_due() {
+        let info = fixtures::chain_info("pedersen-bls-unchained");
+        let beacon = fixtures::unchained_beacon(3);
+        let transport = mock_relay("pedersen-bls-unchained").with_body(
+            "http://relay/public/latest",
+            &fixtures::unchained_beacon_json(&beacon),
+        );
+        let client =
+            new_client_with_transport(&UnchainedScheme {}, "http://relay", transport).unwrap();
+        let (latest, next_round_at) = client.latest_randomness_with_timing().unwrap();
+        assert_eq!(latest, beacon);
+        assert_eq!(
+            next_round_at,
+            info.genesis_time_systemtime() + 3 * info.period_duration()
+        );
+    }
+
+    #[test]
+    fn mock_relay_latest_n_is_newest_first_and_stops_at_genesis() {
+        let transport = (1..=3)
+            .fold(mock_relay("pedersen-bls-unchained"), |relay, round| {
+                relay.with_body(
+                    &format!("http://relay/public/{}", round),
+                    &fixtures::unchained_beacon_json(&fixtures::unchained_beacon(round)),
+                )
+            })
+            .with_body(
+                "http://relay/public/latest",
+                &fixtures::unchained_beacon_json(&fixtures::unchained_beacon(3)),
+            );
+        let client =
+            new_client_with_transport(&UnchainedScheme {}, "http://relay", transport).unwrap();
+        let rounds = |n| {
+            client
+                .latest_n(n)
+                .unwrap()
+                .iter()
+                .map(|beacon| beacon.round_number)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(rounds(0), Vec::<u64>::new());
+        assert_eq!(rounds(1), vec![3]);
+        assert_eq!(rounds(2), vec![3, 2]);
+        assert_eq!(rounds(10), vec![3, 2, 1]);
+    }
+
+    #[test]
+    fn client_can_be_moved_into_a_thread() {
+        let beacon = fixtures::unchained_beacon(3);
+        let transport = mock_relay("pedersen-bls-unchained").with_body(
+            "http://relay/public/3",
+            &fixtures::unchained_beacon_json(&beacon),
+        );
+        let base_url = String::from("http://relay");
+        let client = new_client_with_transport(UnchainedScheme {}, &base_url, transport).unwrap();
+        drop(base_url);
+
+        let fetched = std::thread::spawn(move || client.randomness(3))
+            .join()
+            .unwrap();
+        assert_eq!(fetched.unwrap(), beacon);
+    }
+
+    #[test]
+    fn clones_share_the_cache_without_refetching_chain_info() {
+        let beacon = fixtures::unchained_beacon(3);
+        let transport = mock_relay("pedersen-bls-unchained").with_body(
+            "http://relay/public/3",
+            &fixtures::unchained_beacon_json(&beacon),
+        );
+        let client = DrandClientBuilder::new()
+            .scheme(UnchainedScheme {})
+            .base_url("http://relay")
+            .cache_capacity(NonZeroUsize::new(4).unwrap())
+            .build_with_transport(transport)
+            .unwrap();
+
+        let workers: Vec<_> = (0..4)
+            .map(|_| {
+                let client = client.clone();
+                std::thread::spawn(move || client.randomness(3).unwrap())
+            })
+            .collect();
+        for worker in workers {
+            assert_eq!(worker.join().unwrap(), beacon);
+        }
+        assert_eq!(client.randomness(3).unwrap(), beacon);
+
+        let requests = client.transport.requests();
+        assert_eq!(requests.iter().filter(|r| r.ends_with("/info")).count(), 1);
+        assert!(client.cache.as_ref().unwrap().get(3).is_some());
+    }
+
+    #[test]
+    fn mock_relay_missing_round_is_not_available() {
+        let transport = mock_relay("pedersen-bls-unchained");
+        let client =
+            new_client_with_transport(&UnchainedScheme {}, "http://relay", transport).unwrap();
+        assert!(matches!(
+            client.randomness(3),
+            Err(DrandClientError::RoundNotAvailable)
+        ));
+    }
+
+    #[test]
+    fn beacons_come_with_the_chain_they_were_verified_against() {
+        let beacon = fixtures::unchained_beacon(3);
+        let transport = mock_relay("pedersen-bls-unchained").with_body(
+            "http://relay/public/3",
+            &fixtures::unchained_beacon_json(&beacon),
+        );
+        let client =
+            new_client_with_transport(&UnchainedScheme {}, "http://relay", transport).unwrap();
+        let with_context = client.randomness_with_context(3).unwrap();
+        assert_eq!(with_context.beacon, beacon);
+        assert_eq!(with_context.chain_info, client.chain_info());
+        assert!(client.randomness_with_context(0).is_err());
+    }
+
+    #[test]
+    fn timed_fetches_report_each_step() {
+        let beacon = fixtures::unchained_beacon(3);
+        let transport = mock_relay("pedersen-bls-unchained").with_body(
+            "http://relay/public/3",
+            &fixtures::unchained_beacon_json(&beacon),
+        );
+        let client =
+            new_client_with_transport(&UnchainedScheme {}, "http://relay", transport).unwrap();
+        let timed = client.randomness_timed(3).unwrap();
+        assert_eq!(timed.beacon, beacon);
+        // a pairing check takes far longer than the clock's resolution
+        assert!(timed.verify_time > Duration::ZERO);
+        assert!(matches!(
+            client.randomness_timed(4),
+            Err(DrandClientError::RoundNotAvailable)
+        ));
+    }
+
+    #[test]
+    fn unverified_fetches_skip_only_the_signature_check() {
+        let mut forged = fixtures::unchained_beacon(3);
+        forged.signature[5] ^= 0x01;
+        let transport = mock_relay("pedersen-bls-unchained")
+            .with_body(
+                "http://relay/public/3",
+                &fixtures::unchained_beacon_json(&forged),
+            )
+            .with_body(
+                "http://relay/public/4",
+                &fixtures::unchained_beacon_json(&forged),
+            )
+            .with_body(
+                "http://relay/public/latest",
+                &fixtures::unchained_beacon_json(&forged),
+            );
+        let client =
+            new_client_with_transport(&UnchainedScheme {}, "http://relay", transport).unwrap();
+        assert_eq!(client.randomness_unverified(3).unwrap(), forged);
+        assert_eq!(client.latest_randomness_unverified().unwrap(), forged);
+        assert!(matches!(
+            client.randomness_unverified(4),
+            Err(DrandClientError::InvalidBeacon { .. })
+        ));
+        assert!(matches!(
+            client.randomness(3),
+            Err(DrandClientError::InvalidBeacon { .. })
+        ));
+    }
+
+    #[test]
+    fn mock_relay_serving_another_round_is_rejected() {
+        let other_round = fixtures::unchained_beacon(9);
+        let transport = mock_relay("pedersen-bls-unchained")
+            .with_body(
+                "http://relay/public/3",
+                &fixtures::unchained_beacon_json(&other_round),
+            )
+            .with_body(
+                "http://relay/public/latest",
+                &fixtures::unchained_beacon_json(&other_round),
+            );
+        let client =
+            new_client_with_transport(&UnchainedScheme {}, "http://relay", transport).unwrap();
+        assert!(matches!(
+            client.randomness(3),
+            Err(DrandClientError::InvalidBeacon { .. })
+        ));
+        assert!(matches!(
+            client.randomness_with_expiry(3),
+            Err(DrandClientError::InvalidBeacon { .. })
+        ));
+        assert_eq!(client.latest_randomness().unwrap(), other_round);
+    }
+
+    #[test]
+    fn mock_relay_forged_or_malformed_beacons_are_invalid() {
+        let mut forged = fixtures::unchained_beacon(3);
+        forged.round_number = 4;
+        let transport = mock_relay("pedersen-bls-unchained")
+            .with_body(
+                "http://relay/public/4",
+                &fixtures::unchained_beacon_json(&forged),
+            )
+            .with_body("http://relay/public/5", "<html>rate limited</html>");
+        let client =
+            new_client_with_transport(&UnchainedScheme {}, "http://relay", transport).unwrap();
+        assert!(matches!(
+            client.randomness(4),
+            Err(DrandClientError::InvalidBeacon { .. })
+        ));
+        assert!(matches!(
+            client.randomness(5),
+            Err(DrandClientError::InvalidBeacon { .. })
+        ));
+    }
+
+    #[test]
+    fn mock_relay_server_errors_mean_not_responding() {
+        let transport = mock_relay("pedersen-bls-unchained")
+            .with_status("http://relay/public/3", StatusCode::SERVICE_UNAVAILABLE);
+        let client =
+            new_client_with_transport(&UnchainedScheme {}, "http://relay", transport).unwrap();
+        assert!(matches!(
+            client.randomness(3),
+            Err(DrandClientError::NotResponding(HttpError::ServerError(503)))
+        ));
+    }
+
+    #[test]
+    fn health_checks_the_relay_still_serves_the_chain() {
+        let client = new_client_with_transport(
+            &UnchainedScheme {},
+            "http://relay",
+            mock_relay("pedersen-bls-unchained"),
+        )
+        .unwrap();
+        assert!(client.health().is_ok());
+
+        let mut other = fixtures::chain_info("pedersen-bls-unchained");
+        other.chain_hash = vec![7; 32];
+        let repointed = DrandClientBuilder::new()
+            .scheme(&UnchainedScheme {})
+            .base_url("http://relay")
+            .chain_info(other)
+            .build_with_transport(mock_relay("pedersen-bls-unchained"))
+            .unwrap();
+        assert!(matches!(
+            repointed.health(),
+            Err(DrandClientError::InvalidChainInfo { .. })
+        ));
+
+        let info = fixtures::chain_info("pedersen-bls-unchained");
+        let unreachable = fixtures::unreachable_client(&UnchainedScheme {}, info);
+        assert!(matches!(
+            unreachable.health(),
+            Err(DrandClientError::NotResponding(_))
+        ));
+    }
+
+    #[test]
+    fn mismatched_scheme_fails_construction() {
+        let result = new_client_with_transport(
+            &ChainedScheme {},
+            "http://relay",
+            mock_relay("pedersen-bls-unchained"),
+        );
+        match result {
+            Err(DrandClientError::SchemeMismatch { expected, found }) => {
+                assert_eq!(expected, "pedersen-bls-chained");
+                assert_eq!(found, "pedersen-bls-unchained");
+            }
+            _ => panic!("expected a scheme mismatch"),
+        }
+    }
+
+    /// Serves `rounds` as the latest round, one after another, then the last of them for good.
+    struct AdvancingRelay {
+        relay: MockTransport,
+        rounds: Vec<u64>,
+        served: AtomicUsize,
+    }
+
+    impl Transport for AdvancingRelay {
+        fn fetch(&self, url: &str) -> Result<String, HttpError> {
+            if url != "http://relay/public/latest" {
+                return self.relay.fetch(url);
+            }
+            let served = self.served.fetch_add(1, Ordering::SeqCst);
+            let round = self.rounds[served.min(self.rounds.len() - 1)];
+            Ok(fixtures::unchained_beacon_json(
+                &fixtures::unchained_beacon(round),
+            ))
+        }
+    }
+
+    /// Serves round 3 as the latest round, last modified at `modified_at` and tagged `etag`,
+    /// answering conditional requests for it with a `304` once they're for `modified_at` or later
+    /// or match `etag`.
+    struct ConditionalRelay {
+        relay: MockTransport,
+        modified_at: Option<SystemTime>,
+        etag: Option<&'static str>,
+        not_modified: AtomicUsize,
+    }
+
+    impl Transport for ConditionalRelay {
+        fn fetch(&self, url: &str) -> Result<String, HttpError> {
+            self.fetch_response(url).map(|res| res.body)
+        }
+
+        fn fetch_response(&self, url: &str) -> Result<HttpResponse, HttpError> {
+            if url != "http://relay/public/latest" {
+                return self.relay.fetch_response(url);
+            }
+            Ok(HttpResponse {
+                body: fixtures::unchained_beacon_json(&fixtures::unchained_beacon(3)),
+                valid_until: None,
+                content_type: None,
+                last_modified: self.modified_at,
+                etag: self.etag.map(str::to_string),
+            })
+        }
+
+        fn fetch_if_changed(
+            &self,
+            url: &str,
+            validators: &Validators,
+        ) -> Result<HttpResponse, HttpError> {
+            let unmodified = matches!(
+                (validators.last_modified, self.modified_at),
+                (Some(since), Some(modified_at)) if since >= modified_at
+            );
+            if unmodified || (self.etag.is_some() && validators.etag.as_deref() == self.etag) {
+                self.not_modified.fetch_add(1, Ordering::SeqCst);
+                return Err(HttpError::NotModified);
+            }
+            self.fetch_response(url)
+        }
+    }
+
+    #[test]
+    fn conditional_latest_reuses_the_unmodified_beacon() {
+        let client = DrandClientBuilder::new()
+            .scheme(&UnchainedScheme {})
+            .base_url("http://relay")
+            .conditional_latest(true)
+            .build_with_transport(ConditionalRelay {
+                relay: mock_relay("pedersen-bls-unchained"),
+                modified_at: Some(UNIX_EPOCH + Duration::from_secs(1_700_000_000)),
+                etag: None,
+                not_modified: AtomicUsize::new(0),
+            })
+            .unwrap();
+        let first = client.latest_randomness().unwrap();
+        assert_eq!(client.latest_randomness().unwrap(), first);
+        assert_eq!(client.latest_randomness().unwrap(), first);
+        assert_eq!(client.transport.not_modified.load(Ordering::SeqCst), 2);
+
+        client.clear_cache();
+        assert_eq!(client.latest_randomness().unwrap(), first);
+        assert_eq!(client.transport.not_modified.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn conditional_latest_reuses_the_beacon_with_an_unchanged_etag() {
+        let client = DrandClientBuilder::new()
+            .scheme(&UnchainedScheme {})
+            .base_url("http://relay")
+            .conditional_latest(true)
+            .build_with_transport(ConditionalRelay {
+                relay: mock_relay("pedersen-bls-unchained"),
+                modified_at: None,
+                etag: Some("\"round-3\""),
+                not_modified: AtomicUsize::new(0),
+            })
+            .unwrap();
+        let first = client.latest_randomness().unwrap();
+        assert_eq!(client.latest_randomness().unwrap(), first);
+        assert_eq!(client.transport.not_modified.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn not_modified_without_a_previous_beacon_is_an_error() {
+        let transport = mock_relay("pedersen-bls-unchained")
+            .with_status("http://relay/public/latest", StatusCode::NOT_MODIFIED);
+        let client = DrandClientBuilder::new()
+            .scheme(&UnchainedScheme {})
+            .base_url("http://relay")
+            .conditional_latest(true)
+            .build_with_transport(transport)
+            .unwrap();
+        assert!(matches!(
+            client.latest_randomness(),
+            Err(DrandClientError::NotResponding(HttpError::NotModified))
+        ));
+    }
+
+    #[test]
+    fn history_runs_from_genesis_to_the_advancing_latest_round() {
+        let relay = (1..=5).fold(mock_relay("pedersen-bls-unchained"), |relay, round| {
+            relay.with_body(
+                &format!("http://relay/public/{}", round),
+                &fixtures::unchained_beacon_json(&fixtures::unchained_beacon(round)),
+            )
+        });
+        let transport = AdvancingRelay {
+            relay,
+            rounds: vec![3, 5],
+            served: AtomicUsize::new(0),
+        };
+        let client =
+            new_client_with_transport(&UnchainedScheme {}, "http://relay", transport).unwrap();
+        let rounds: Vec<u64> = client
+            .history()
+            .map(|beacon| beacon.unwrap().round_number)
+            .collect();
+        assert_eq!(rounds, vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn history_ends_if_the_latest_round_is_unavailable() {
+        let info = fixtures::chain_info("pedersen-bls-unchained");
+        let client = fixtures::unreachable_client(&UnchainedScheme {}, info);
+        let mut history = client.history();
+        assert!(matches!(
+            history.next(),
+            Some(Err(DrandClientError::NotResponding(_)))
+        ));
+        assert!(history.next().is_none());
+    }
+
+    #[test]
+    fn http_statuses_are_reported() {
+        let transport = mock_relay("pedersen-bls-unchained")
+            .with_status("http://relay/public/3", StatusCode::TOO_MANY_REQUESTS)
+            .with_status("http://relay/public/4", StatusCode::SERVICE_UNAVAILABLE);
+        let client =
+            new_client_with_transport(&UnchainedScheme {}, "http://relay", transport).unwrap();
+        assert_eq!(client.randomness(3).unwrap_err().http_status(), Some(429));
+        assert_eq!(client.randomness(4).unwrap_err().http_status(), Some(503));
+        assert_eq!(DrandClientError::InvalidRound.http_status(), None);
+    }
+
+    #[test]
+    fn mock_relay_error_pages_mean_not_responding() {
+        let page = "<html><body>503 Service Temporarily Unavailable</body></html>";
+        let transport = mock_relay("pedersen-bls-unchained")
+            .with_response(
+                "http://relay/public/3",
+                StatusCode::SERVICE_UNAVAILABLE,
+                "text/html",
+                page,
+            )
+            .with_response("http://relay/public/4", StatusCode::OK, "text/html", page)
+            .with_response(
+                "http://relay/public/5",
+                StatusCode::OK,
+                "text/plain; charset=utf-8",
+                "rate limit exceeded",
+            );
+        let client =
+            new_client_with_transport(&UnchainedScheme {}, "http://relay", transport).unwrap();
+        assert!(matches!(
+            client.randomness(3),
+            Err(DrandClientError::NotResponding(HttpError::ServerError(503)))
+        ));
+        assert!(matches!(
+            client.randomness(4),
+            Err(DrandClientError::NotResponding(HttpError::NotJson))
+        ));
+        assert!(matches!(
+            client.randomness(5),
+            Err(DrandClientError::NotResponding(HttpError::NotJson))
+        ));
+    }
+
+    #[derive(Default)]
+    struct CountingMetrics {
+        successes: AtomicUsize,
+        errors: AtomicUsize,
+        verifications: AtomicUsize,
+        cache_hits: AtomicUsize,
+        cache_misses: AtomicUsize,
+    }
+
+    impl Metrics for CountingMetrics {
+        fn on_fetch_success(&self) {
+            self.successes.fetch_add(1, Ordering::SeqCst);
+        }
+
+        fn on_fetch_error(&self, _error: &DrandClientError) {
+            self.errors.fetch_add(1, Ordering::SeqCst);
+        }
+
+        fn on_verify_duration(&self, _duration: Duration) {
+            self.verifications.fetch_add(1, Ordering::SeqCst);
+        }
+
+        fn on_cache_hit(&self) {
+            self.cache_hits.fetch_add(1, Ordering::SeqCst);
+        }
+
+        fn on_cache_miss(&self) {
+            self.cache_misses.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn metrics_observe_fetches_verifications_and_cache_lookups() {
+        let mut forged = fixtures::unchained_beacon(4);
+        forged.round_number = 5;
+        let transport = mock_relay("pedersen-bls-unchained")
+            .with_body(
+                "http://relay/public/4",
+                &fixtures::unchained_beacon_json(&fixtures::unchained_beacon(4)),
+            )
+            .with_body(
+                "http://relay/public/5",
+                &fixtures::unchained_beacon_json(&forged),
+            );
+        let metrics = Arc::new(CountingMetrics::default());
+        let options = ClientOptions {
+            cache_capacity: NonZeroUsize::new(4),
+            metrics: metrics.clone(),
+            ..ClientOptions::default()
+        };
+        let client = new_client_with_transport_and_options(
+            SchemeChoice::Fixed(Arc::new(UnchainedScheme {})),
+            vec!["http://relay".to_string()],
+            transport,
+            options,
+        )
+        .unwrap();
+
+        client.randomness(4).unwrap();
+        client.randomness(4).unwrap();
+        assert!(client.randomness(5).is_err());
+        assert!(client.randomness(6).is_err());
+
+        assert_eq!(metrics.successes.load(Ordering::SeqCst), 1);
+        assert_eq!(metrics.errors.load(Ordering::SeqCst), 2);
+        // round 6 never reached verification
+        assert_eq!(metrics.verifications.load(Ordering::SeqCst), 2);
+        assert_eq!(metrics.cache_hits.load(Ordering::SeqCst), 1);
+        assert_eq!(metrics.cache_misses.load(Ordering::SeqCst), 3);
+    }
+}
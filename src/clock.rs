@@ -0,0 +1,72 @@
+use crate::timer;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::UNIX_EPOCH;
+
+/// Where the clients read the current time from, for `current_round` and for scheduling `watch`.
+/// Implement it, or use [`MockClock`], to test time-based logic against a fixed time.
+pub trait Clock: Send + Sync {
+    /// Seconds since the unix epoch.
+    fn now_unix(&self) -> u64;
+}
+
+/// The clock clients use unless they were constructed with another: the system's, or the
+/// browser's in WASM.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_unix(&self) -> u64 {
+        timer::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0)
+    }
+}
+
+/// A clock that only moves when it's told to.
+#[derive(Debug, Default)]
+pub struct MockClock {
+    now: AtomicU64,
+}
+
+impl MockClock {
+    pub fn new(unix_time: u64) -> Self {
+        MockClock {
+            now: AtomicU64::new(unix_time),
+        }
+    }
+
+    pub fn set(&self, unix_time: u64) {
+        self.now.store(unix_time, Ordering::SeqCst);
+    }
+
+    pub fn advance(&self, seconds: u64) {
+        self.now.fetch_add(seconds, Ordering::SeqCst);
+    }
+}
+
+impl Clock for MockClock {
+    fn now_unix(&self) -> u64 {
+        self.now.load(Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::clock::{Clock, MockClock, SystemClock};
+
+    #[test]
+    fn mock_clock_only_moves_when_told_to() {
+        let clock = MockClock::new(1000);
+        assert_eq!(clock.now_unix(), 1000);
+        clock.advance(30);
+        assert_eq!(clock.now_unix(), 1030);
+        clock.set(5);
+        assert_eq!(clock.now_unix(), 5);
+    }
+
+    #[test]
+    fn system_clock_is_after_drands_launch() {
+        assert!(SystemClock.now_unix() > 1_595_431_050);
+    }
+}
@@ -0,0 +1,114 @@
+use crate::bls::{BlsVerifiable, Ciphersuite};
+use crate::chain_info::ChainInfo;
+#[cfg(feature = "chained")]
+use crate::chained::ChainedScheme;
+#[cfg(feature = "unchained")]
+use crate::unchained::UnchainedScheme;
+#[cfg(feature = "g1")]
+use crate::unchained_g1::{UnchainedSchemeOnG1, UnchainedSchemeOnG1Rfc9380};
+use crate::{bls, Scheme, SchemeError};
+use alloc::vec::Vec;
+
+/// A scheme that hashes messages onto the curve with its own domain separation tag rather than
+/// drand's, for private networks experimenting with other ciphersuites. Created by the schemes'
+/// `with_dst`, e.g. `UnchainedScheme::with_dst("MY_DST_")`; it supports the same scheme ids.
+pub struct CustomDst<S> {
+    scheme: S,
+    dst: Vec<u8>,
+}
+
+impl<S> CustomDst<S> {
+    fn new(scheme: S, dst: &str) -> Self {
+        CustomDst {
+            scheme,
+            dst: dst.as_bytes().to_vec(),
+        }
+    }
+
+    pub fn dst(&self) -> &[u8] {
+        &self.dst
+    }
+}
+
+/// Verifies as the wrapped scheme would, but with this DST.
+impl<S: Scheme<B>, B: BlsVerifiable> Scheme<B> for CustomDst<S> {
+    fn supports(&self, scheme_id: &str) -> bool {
+        self.scheme.supports(scheme_id)
+    }
+
+    fn scheme_id(&self) -> &str {
+        self.scheme.scheme_id()
+    }
+
+    fn verify(&self, info: &ChainInfo, beacon: B) -> Result<B, SchemeError> {
+        if !self.supports(&info.scheme_id) {
+            return Err(SchemeError::InvalidScheme);
+        }
+        let suite = bls::ciphersuite(self.scheme.scheme_id()).ok_or(SchemeError::InvalidScheme)?;
+        bls::bls_verify_with(
+            info,
+            beacon,
+            Ciphersuite {
+                dst: &self.dst,
+                ..suite
+            },
+        )
+    }
+}
+
+#[cfg(feature = "chained")]
+impl ChainedScheme {
+    pub fn with_dst(dst: &str) -> CustomDst<ChainedScheme> {
+        CustomDst::new(ChainedScheme {}, dst)
+    }
+}
+
+#[cfg(feature = "unchained")]
+impl UnchainedScheme {
+    pub fn with_dst(dst: &str) -> CustomDst<UnchainedScheme> {
+        CustomDst::new(UnchainedScheme {}, dst)
+    }
+}
+
+#[cfg(feature = "g1")]
+impl UnchainedSchemeOnG1 {
+    pub fn with_dst(dst: &str) -> CustomDst<UnchainedSchemeOnG1> {
+        CustomDst::new(UnchainedSchemeOnG1 {}, dst)
+    }
+}
+
+#[cfg(feature = "g1")]
+impl UnchainedSchemeOnG1Rfc9380 {
+    pub fn with_dst(dst: &str) -> CustomDst<UnchainedSchemeOnG1Rfc9380> {
+        CustomDst::new(UnchainedSchemeOnG1Rfc9380 {}, dst)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::bls::G1_DOMAIN;
+    use crate::fixtures;
+    use crate::{Scheme, UnchainedScheme, UnchainedSchemeOnG1Rfc9380};
+
+    const EXPERIMENTAL_DST: &str = "BLS_SIG_BLS12381G1_XMD:SHA-256_SSWU_RO_EXPERIMENTAL_";
+
+    #[test]
+    fn beacons_verify_with_the_custom_dst_only() {
+        let info = fixtures::g1_chain_info("bls-unchained-g1-rfc9380");
+        let custom = UnchainedSchemeOnG1Rfc9380::with_dst(EXPERIMENTAL_DST);
+        let beacon = fixtures::unchained_g1_beacon(2, EXPERIMENTAL_DST.as_bytes());
+        assert!(custom.verify(&info, beacon.clone()).is_ok());
+        assert!(UnchainedSchemeOnG1Rfc9380 {}.verify(&info, beacon).is_err());
+
+        let standard = fixtures::unchained_g1_beacon(2, G1_DOMAIN);
+        assert!(custom.verify(&info, standard).is_err());
+    }
+
+    #[test]
+    fn default_dst_behaves_like_the_plain_scheme() {
+        let info = fixtures::chain_info("pedersen-bls-unchained");
+        let custom = UnchainedScheme::with_dst("BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_NUL_");
+        assert!(custom.supports("pedersen-bls-unchained"));
+        assert!(custom.verify(&info, fixtures::unchained_beacon(2)).is_ok());
+    }
+}
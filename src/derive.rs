@@ -0,0 +1,63 @@
+use crate::{Beacon, SchemeError};
+use hkdf::Hkdf;
+use rand_chacha::rand_core::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use sha2::Sha256;
+
+/// The most output HKDF-SHA256 can produce, 255 hash lengths.
+pub const MAX_DERIVED_LEN: usize = 255 * 32;
+
+/// Derives `out_len` bytes of randomness for a particular purpose from a beacon's randomness,
+/// using HKDF-SHA256 with `info` as the label. Different labels give independent outputs, so
+/// one beacon can safely seed many uses.
+///
+/// # Panics
+/// If `out_len` is greater than [MAX_DERIVED_LEN].
+pub fn derive_randomness(beacon_randomness: &[u8], info: &[u8], out_len: usize) -> Vec<u8> {
+    let mut derived = vec![0; out_len];
+    Hkdf::<Sha256>::new(None, beacon_randomness)
+        .expand(info, &mut derived)
+        .expect("output length is at most MAX_DERIVED_LEN");
+    derived
+}
+
+/// A ChaCha20 RNG seeded with the beacon's randomness, so anyone holding the same (verified)
+/// beacon can reproduce its output, e.g. to audit a draw.
+pub fn beacon_rng<B: Beacon>(beacon: &B) -> Result<ChaCha20Rng, SchemeError> {
+    beacon.randomness_bytes().map(ChaCha20Rng::from_seed)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::derive::{beacon_rng, derive_randomness};
+    use crate::fixtures;
+    use rand::Rng;
+
+    #[test]
+    fn matches_rfc5869_test_vector() {
+        let derived = derive_randomness(&[0x0b; 22], &[], 42);
+        assert_eq!(
+            hex::encode(derived),
+            "8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879ec3454e5f3c738d2d9d201395faa4b61a96c8"
+        );
+    }
+
+    #[test]
+    fn labels_separate_outputs() {
+        let randomness = [1; 32];
+        let lottery = derive_randomness(&randomness, b"lottery", 32);
+        assert_eq!(lottery, derive_randomness(&randomness, b"lottery", 32));
+        assert_ne!(lottery, derive_randomness(&randomness, b"shuffle", 32));
+    }
+
+    #[test]
+    fn beacon_rng_is_reproducible() {
+        let beacon = fixtures::unchained_beacon(2);
+        let first: [u64; 4] = beacon_rng(&beacon).unwrap().gen();
+        let second: [u64; 4] = beacon_rng(&beacon).unwrap().gen();
+        assert_eq!(first, second);
+
+        let other: [u64; 4] = beacon_rng(&fixtures::unchained_beacon(3)).unwrap().gen();
+        assert_ne!(first, other);
+    }
+}
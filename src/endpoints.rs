@@ -0,0 +1,364 @@
+use crate::http::HttpError;
+use crate::DrandClientError;
+#[cfg(feature = "async")]
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Which of drand's HTTP path layouts a relay serves. `V1` is the original
+/// `/{chain_hash}/public/{round}` layout; `V2` is the newer one under `/v2/`, reaching chains by
+/// hash via `/v2/chains/{chain_hash}/rounds/{round}`, or the relay's default beacon via
+/// `/v2/beacons/default/rounds/{round}`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ApiVersion {
+    #[default]
+    V1,
+    V2,
+}
+
+impl ApiVersion {
+    pub(crate) fn info_url(self, base_url: &str, chain_hash: Option<&str>) -> String {
+        format!("{}/{}info", base_url, self.chain_prefix(chain_hash))
+    }
+
+    pub(crate) fn beacon_url(self, base_url: &str, chain_hash: Option<&str>, tag: &str) -> String {
+        let route = match self {
+            ApiVersion::V1 => "public",
+            ApiVersion::V2 => "rounds",
+        };
+        format!(
+            "{}/{}{}/{}",
+            base_url,
+            self.chain_prefix(chain_hash),
+            route,
+            tag
+        )
+    }
+
+    fn chain_prefix(self, chain_hash: Option<&str>) -> String {
+        match (self, chain_hash) {
+            (ApiVersion::V1, Some(chain_hash)) => format!("{}/", chain_hash),
+            (ApiVersion::V1, None) => String::new(),
+            (ApiVersion::V2, Some(chain_hash)) => format!("v2/chains/{}/", chain_hash),
+            (ApiVersion::V2, None) => "v2/beacons/default/".to_string(),
+        }
+    }
+}
+
+/// Paths relative to a relay's base URL replacing drand's layouts, for gateways and static
+/// mirrors serving them elsewhere. `{tag}` or `{round}` in the beacon path stands for the round
+/// number or `latest`, and `{chain_hash}` in either for the chain hash the client targets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PathTemplates {
+    pub(crate) info: String,
+    pub(crate) beacon: String,
+}
+
+impl PathTemplates {
+    pub(crate) fn new(info: &str, beacon: &str) -> Self {
+        PathTemplates {
+            info: info.trim_start_matches('/').to_string(),
+            beacon: beacon.trim_start_matches('/').to_string(),
+        }
+    }
+
+    /// Fails unless the beacon path says where the round goes and, if either path has a chain
+    /// hash in it, there's one to put there.
+    pub(crate) fn validate(&self, chain_hash: Option<&str>) -> Result<(), DrandClientError> {
+        let reason = if !self.beacon.contains("{tag}") && !self.beacon.contains("{round}") {
+            "the beacon path has no {tag} or {round} placeholder"
+        } else if chain_hash.is_none()
+            && (self.info.contains("{chain_hash}") || self.beacon.contains("{chain_hash}"))
+        {
+            "the paths have a {chain_hash} placeholder but no chain hash was set"
+        } else {
+            return Ok(());
+        };
+        Err(DrandClientError::InvalidConfig {
+            reason: reason.to_string(),
+        })
+    }
+}
+
+fn fill(template: &str, chain_hash: Option<&str>) -> String {
+    template.replace("{chain_hash}", chain_hash.unwrap_or_default())
+}
+
+/// Where a client finds a relay's chain info and beacons: under drand's `api_version` layout,
+/// unless it was given its own paths.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct Paths {
+    pub(crate) api_version: ApiVersion,
+    pub(crate) templates: Option<PathTemplates>,
+}
+
+impl From<ApiVersion> for Paths {
+    fn from(api_version: ApiVersion) -> Self {
+        Paths {
+            api_version,
+            templates: None,
+        }
+    }
+}
+
+impl Paths {
+    pub(crate) fn info_url(&self, base_url: &str, chain_hash: Option<&str>) -> String {
+        match &self.templates {
+            Some(templates) => format!("{}/{}", base_url, fill(&templates.info, chain_hash)),
+            None => self.api_version.info_url(base_url, chain_hash),
+        }
+    }
+
+    pub(crate) fn beacon_url(&self, base_url: &str, chain_hash: Option<&str>, tag: &str) -> String {
+        match &self.templates {
+            Some(templates) => {
+                let path = fill(&templates.beacon, chain_hash)
+                    .replace("{tag}", tag)
+                    .replace("{round}", tag);
+                format!("{}/{}", base_url, path)
+            }
+            None => self.api_version.beacon_url(base_url, chain_hash, tag),
+        }
+    }
+}
+
+/// Trims surrounding whitespace and trailing slashes so paths can be appended with a single `/`,
+/// and rejects anything that isn't an absolute http(s) URL.
+pub(crate) fn normalize_base_url(base_url: &str) -> Result<String, DrandClientError> {
+    let trimmed = base_url.trim().trim_end_matches('/');
+    let host = ["http://", "https://"].iter().find_map(|scheme| {
+        trimmed
+            .get(..scheme.len())
+            .filter(|prefix| prefix.eq_ignore_ascii_case(scheme))
+            .map(|_| &trimmed[scheme.len()..])
+    });
+    match host {
+        Some(host) if !host.is_empty() && !host.starts_with('/') => Ok(trimmed.to_string()),
+        _ => Err(DrandClientError::InvalidChainInfo {
+            reason: format!("{:?} is not an http(s) url", base_url),
+        }),
+    }
+}
+
+pub(crate) struct Endpoints {
+    base_urls: Vec<String>,
+    preferred: AtomicUsize,
+}
+
+/// Clones start from the endpoint the original currently prefers.
+impl Clone for Endpoints {
+    fn clone(&self) -> Self {
+        Endpoints {
+            base_urls: self.base_urls.clone(),
+            preferred: AtomicUsize::new(self.preferred.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+impl Endpoints {
+    pub(crate) fn new<S: Into<String>>(base_urls: Vec<S>) -> Self {
+        Endpoints {
+            base_urls: base_urls.into_iter().map(Into::into).collect(),
+            preferred: AtomicUsize::new(0),
+        }
+    }
+
+    /// Every endpoint, in the order they were configured.
+    #[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+    pub(crate) fn all(&self) -> &[String] {
+        &self.base_urls
+    }
+
+    /// The endpoint that most recently responded successfully.
+    pub(crate) fn preferred(&self) -> &str {
+        &self.base_urls[self.preferred.load(Ordering::Relaxed)]
+    }
+
+    // starts from the endpoint that last succeeded so a dead first entry isn't retried every call
+    fn ordered(&self) -> impl Iterator<Item = (usize, &str)> + '_ {
+        let start = self.preferred.load(Ordering::Relaxed);
+        let len = self.base_urls.len();
+        (0..len).map(move |offset| {
+            let index = (start + offset) % len;
+            (index, self.base_urls[index].as_str())
+        })
+    }
+
+    #[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+    pub(crate) fn first_success<T, F>(&self, mut operation: F) -> Result<T, HttpError>
+    where
+        F: FnMut(&str) -> Result<T, HttpError>,
+    {
+        let mut last_error = HttpError::Unexpected;
+        for (index, base_url) in self.ordered() {
+            match operation(base_url) {
+                Ok(result) => {
+                    self.preferred.store(index, Ordering::Relaxed);
+                    return Ok(result);
+                }
+                Err(e) => last_error = e,
+            }
+        }
+        Err(last_error)
+    }
+
+    #[cfg(feature = "async")]
+    pub(crate) async fn first_success_async<'s, T, F, Fut>(
+        &'s self,
+        mut operation: F,
+    ) -> Result<T, HttpError>
+    where
+        F: FnMut(&'s str) -> Fut,
+        Fut: Future<Output = Result<T, HttpError>>,
+    {
+        let mut last_error = HttpError::Unexpected;
+        for (index, base_url) in self.ordered() {
+            match operation(base_url).await {
+                Ok(result) => {
+                    self.preferred.store(index, Ordering::Relaxed);
+                    return Ok(result);
+                }
+                Err(e) => last_error = e,
+            }
+        }
+        Err(last_error)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::endpoints::{normalize_base_url, ApiVersion, Endpoints, PathTemplates, Paths};
+    use crate::http::HttpError;
+    use crate::DrandClientError;
+
+    #[test]
+    fn urls_are_scoped_to_the_chain_hash() {
+        assert_eq!(
+            ApiVersion::V1.info_url("https://api.drand.sh", None),
+            "https://api.drand.sh/info"
+        );
+        assert_eq!(
+            ApiVersion::V1.beacon_url("https://api.drand.sh", Some("abcd"), "latest"),
+            "https://api.drand.sh/abcd/public/latest"
+        );
+    }
+
+    #[test]
+    fn v2_urls_route_through_chains_or_the_default_beacon() {
+        assert_eq!(
+            ApiVersion::V2.info_url("https://api.drand.sh", Some("abcd")),
+            "https://api.drand.sh/v2/chains/abcd/info"
+        );
+        assert_eq!(
+            ApiVersion::V2.beacon_url("https://api.drand.sh", Some("abcd"), "1000"),
+            "https://api.drand.sh/v2/chains/abcd/rounds/1000"
+        );
+        assert_eq!(
+            ApiVersion::V2.beacon_url("https://api.drand.sh", None, "latest"),
+            "https://api.drand.sh/v2/beacons/default/rounds/latest"
+        );
+    }
+
+    #[test]
+    fn templates_replace_the_layout() {
+        let paths = Paths {
+            api_version: ApiVersion::V2,
+            templates: Some(PathTemplates::new(
+                "/{chain_hash}/info.json",
+                "rounds/{round}.json",
+            )),
+        };
+        assert_eq!(
+            paths.info_url("https://mirror", Some("abcd")),
+            "https://mirror/abcd/info.json"
+        );
+        assert_eq!(
+            paths.beacon_url("https://mirror", Some("abcd"), "1000"),
+            "https://mirror/rounds/1000.json"
+        );
+        assert_eq!(
+            Paths::from(ApiVersion::V1).beacon_url("https://relay", None, "latest"),
+            "https://relay/public/latest"
+        );
+    }
+
+    #[test]
+    fn templates_need_somewhere_to_put_the_round() {
+        assert!(PathTemplates::new("info", "public/{tag}")
+            .validate(None)
+            .is_ok());
+        assert!(matches!(
+            PathTemplates::new("info", "public/latest").validate(None),
+            Err(DrandClientError::InvalidConfig { .. })
+        ));
+        let hashed = PathTemplates::new("{chain_hash}/info", "{chain_hash}/{round}");
+        assert!(hashed.validate(Some("abcd")).is_ok());
+        assert!(hashed.validate(None).is_err());
+    }
+
+    #[test]
+    fn first_working_endpoint_is_returned() {
+        let endpoints = Endpoints::new(vec!["https://dead", "https://alive"]);
+        let result = endpoints.first_success(|base_url| match base_url {
+            "https://alive" => Ok(base_url.to_string()),
+            _ => Err(HttpError::ServerError(500)),
+        });
+        assert_eq!(result.unwrap(), "https://alive");
+    }
+
+    #[test]
+    fn last_working_endpoint_is_tried_first() {
+        let endpoints = Endpoints::new(vec!["https://dead", "https://alive"]);
+        let _ = endpoints.first_success(|base_url| match base_url {
+            "https://alive" => Ok(()),
+            _ => Err(HttpError::ServerError(500)),
+        });
+
+        let mut attempted = vec![];
+        let _ = endpoints.first_success(|base_url| {
+            attempted.push(base_url.to_string());
+            Ok(())
+        });
+        assert_eq!(attempted, vec!["https://alive"]);
+        assert_eq!(endpoints.preferred(), "https://alive");
+    }
+
+    #[test]
+    fn all_endpoints_failing_returns_error() {
+        let endpoints = Endpoints::new(vec!["https://dead", "https://also-dead"]);
+        let result: Result<(), HttpError> =
+            endpoints.first_success(|_| Err(HttpError::ServerError(500)));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn trailing_slashes_are_stripped() {
+        assert_eq!(
+            normalize_base_url("https://api.drand.sh/").unwrap(),
+            "https://api.drand.sh"
+        );
+        assert_eq!(
+            normalize_base_url(" http://localhost:8080/chain// ").unwrap(),
+            "http://localhost:8080/chain"
+        );
+        assert_eq!(
+            normalize_base_url("HTTPS://api.drand.sh").unwrap(),
+            "HTTPS://api.drand.sh"
+        );
+    }
+
+    #[test]
+    fn urls_without_an_http_scheme_are_rejected() {
+        for base_url in [
+            "api.drand.sh",
+            "ftp://api.drand.sh",
+            "https://",
+            "https:///",
+            "",
+        ] {
+            assert!(matches!(
+                normalize_base_url(base_url),
+                Err(DrandClientError::InvalidChainInfo { .. })
+            ));
+        }
+    }
+}
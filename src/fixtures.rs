@@ -0,0 +1,199 @@
+use crate::chain_info::{ChainInfo, ChainInfoMetadata};
+use crate::chained::ChainedBeacon;
+use crate::clock::SystemClock;
+use crate::endpoints::{Endpoints, Paths};
+use crate::http::HttpTransport;
+use crate::metrics::NoopMetrics;
+use crate::unchained::UnchainedBeacon;
+use crate::{DrandClient, RetryPolicy, Scheme};
+use bls12_381::hash_to_curve::{ExpandMsgXmd, HashToCurve};
+use bls12_381::{G1Affine, G1Projective, G2Affine, Scalar};
+use bls_signatures::{PrivateKey, Serialize};
+use sha2::Sha256;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn private_key() -> PrivateKey {
+    PrivateKey::new([7u8; 32])
+}
+
+fn sign(message: &[u8]) -> Vec<u8> {
+    let digest = hex::decode(sha256::digest(message)).unwrap();
+    private_key().sign(digest).as_bytes()
+}
+
+fn randomness_of(signature: &[u8]) -> Vec<u8> {
+    hex::decode(sha256::digest(signature)).unwrap()
+}
+
+pub(crate) fn chain_info(scheme_id: &str) -> ChainInfo {
+    ChainInfo {
+        scheme_id: scheme_id.to_string(),
+        public_key: private_key().public_key().as_bytes(),
+        chain_hash: vec![1; 32],
+        group_hash: vec![2; 32],
+        genesis_time: 1595431050,
+        period_seconds: 30,
+        metadata: ChainInfoMetadata {
+            beacon_id: "default".to_string(),
+        },
+        parsed_public_key: Default::default(),
+    }
+}
+
+fn g1_secret_key() -> Scalar {
+    Scalar::from(0x5eed_u64)
+}
+
+fn sign_on_g1(message: &[u8], domain: &[u8]) -> Vec<u8> {
+    let digest = hex::decode(sha256::digest(message)).unwrap();
+    let point = <G1Projective as HashToCurve<ExpandMsgXmd<Sha256>>>::hash_to_curve(digest, domain);
+    G1Affine::from(point * g1_secret_key())
+        .to_compressed()
+        .to_vec()
+}
+
+pub(crate) fn g1_chain_info(scheme_id: &str) -> ChainInfo {
+    ChainInfo {
+        public_key: G2Affine::from(G2Affine::generator() * g1_secret_key())
+            .to_compressed()
+            .to_vec(),
+        period_seconds: 3,
+        ..chain_info(scheme_id)
+    }
+}
+
+pub(crate) fn chained_beacon(round_number: u64, previous_signature: Vec<u8>) -> ChainedBeacon {
+    let mut message = previous_signature.clone();
+    message.extend_from_slice(&round_number.to_be_bytes());
+    let signature = sign(&message);
+    ChainedBeacon {
+        round_number,
+        randomness: randomness_of(&signature),
+        signature,
+        previous_signature,
+    }
+}
+
+pub(crate) fn unchained_beacon(round_number: u64) -> UnchainedBeacon {
+    let signature = sign(&round_number.to_be_bytes());
+    UnchainedBeacon {
+        round_number,
+        randomness: randomness_of(&signature),
+        signature,
+    }
+}
+
+pub(crate) fn unchained_g1_beacon(round_number: u64, domain: &[u8]) -> UnchainedBeacon {
+    let signature = sign_on_g1(&round_number.to_be_bytes(), domain);
+    UnchainedBeacon {
+        round_number,
+        randomness: randomness_of(&signature),
+        signature,
+    }
+}
+
+pub(crate) fn chain_info_json(info: &ChainInfo) -> String {
+    serde_json::json!({
+        "public_key": hex::encode(&info.public_key),
+        "period": info.period_seconds,
+        "genesis_time": info.genesis_time,
+        "hash": hex::encode(&info.chain_hash),
+        "groupHash": hex::encode(&info.group_hash),
+        "schemeID": info.scheme_id,
+        "metadata": { "beaconID": info.metadata.beacon_id },
+    })
+    .to_string()
+}
+
+pub(crate) fn chained_beacon_json(beacon: &ChainedBeacon) -> String {
+    serde_json::json!({
+        "round": beacon.round_number,
+        "randomness": hex::encode(&beacon.randomness),
+        "signature": hex::encode(&beacon.signature),
+        "previous_signature": hex::encode(&beacon.previous_signature),
+    })
+    .to_string()
+}
+
+pub(crate) fn unchained_beacon_json(beacon: &UnchainedBeacon) -> String {
+    serde_json::json!({
+        "round": beacon.round_number,
+        "randomness": hex::encode(&beacon.randomness),
+        "signature": hex::encode(&beacon.signature),
+    })
+    .to_string()
+}
+
+/// A client for a relay that refuses every connection, for exercising failure paths offline.
+pub(crate) fn unreachable_client<B>(
+    scheme: &'static dyn Scheme<B>,
+    info: ChainInfo,
+) -> DrandClient<B> {
+    DrandClient {
+        scheme: Arc::new(scheme),
+        transport: HttpTransport::default().with_timeout(Duration::from_millis(500)),
+        endpoints: Endpoints::new(vec!["http://127.0.0.1:1"]),
+        chain_hash: None,
+        paths: Paths::default(),
+        chain_info: info,
+        retry_policy: RetryPolicy::none(),
+        cache: None,
+        latest: None,
+        metrics: Arc::new(NoopMetrics),
+        clock: Arc::new(SystemClock),
+        future_round_tolerance: None,
+        warnings: vec![],
+    }
+}
+
+// Captured from the public networks, for checking the schemes against what drand actually signs
+// rather than against the fixtures' own keys and tags.
+
+/// Mainnet's chain info, as drand's relays and `drand get chain-info` print it.
+pub(crate) const MAINNET_INFO_JSON: &str = r#"{"public_key":"868f005eb8e6e4ca0a47c8a77ceaa5309a47978a7c71bc5cce96366b5d7a569937c529eeda66c7293784a9402801af31","period":30,"genesis_time":1595431050,"hash":"8990e7a9aaed2ffed73dbd7092123d6f289930540d7651336225dc172e51b2ce","groupHash":"176f93498eac9ca337150b46d21dd58673ea4e3581185f869672e59fa4cb390a","schemeID":"pedersen-bls-chained","metadata":{"beaconID":"default"}}"#;
+
+/// Mainnet's round 72785, in the JSON the relays serve from `/public/72785`.
+pub(crate) const MAINNET_ROUND_72785_JSON: &str = r#"{"round":72785,"randomness":"8b676484b5fb1f37f9ec5c413d7d29883504e5b669f604a1ce68b3388e9ae3d9","signature":"82f5d3d2de4db19d40a6980e8aa37842a0e55d1df06bd68bddc8d60002e8e959eb9cfa368b3c1b77d18f02a54fe047b80f0989315f83b12a74fd8679c4f12aae86eaf6ab5690b34f1fddd50ee3cc6f6cdf59e95526d5a5d82aaa84fa6f181e42","previous_signature":"a609e19a03c2fcc559e8dae14900aaefe517cb55c840f6e69bc8e4f66c8d18e8a609685d9917efbfb0c37f058c2de88f13d297c7e19e0ab24813079efe57a182554ff054c7638153f9b26a60e7111f71a0ff63d9571704905d3ca6df0b031747"}"#;
+
+/// Quicknet's round 1000, on `bls-unchained-g1-rfc9380`.
+pub(crate) const QUICKNET_ROUND_1000_JSON: &str = r#"{"round":1000,"randomness":"fe290beca10872ef2fb164d2aa4442de4566183ec51c56ff3cd603d930e54fdd","signature":"b44679b9a59af2ec876b1a6b1ad52ea9b1615fc3982b19576350f93447cb1125e342b73a8dd2bacbe47e4b6b63ed5e39"}"#;
+
+/// Fastnet's round 1, on the original `bls-unchained-on-g1`, before drand retired it for quicknet.
+pub(crate) const FASTNET_ROUND_1_JSON: &str = r#"{"round":1,"randomness":"ef076e4d0b9320bf3f50cb2940777ae6bbee79c3d620d8efc04195bfc0568486","signature":"9544ddce2fdbe8688d6f5b4f98eed5d63eee3902e7e162050ac0f45905a55657714880adabe3c3096b92767d886567d0"}"#;
+
+const QUICKNET_PUBLIC_KEY: &str = "83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a";
+const FASTNET_PUBLIC_KEY: &str = "a0b862a7527fee3a731bcb59280ab6abd62d5c0b6ea03dc4ddf6612fdfc9d01f01c31542541771903475eb1ec6615f8d0df0b8b6dce385811d6dcf8cbefb8759e5e616a3dfd054c928940766d9a5b9db91e3b697e5d70a975181e007f87fca5e";
+
+pub(crate) fn mainnet_chain_info() -> ChainInfo {
+    serde_json::from_str(MAINNET_INFO_JSON).unwrap()
+}
+
+/// Quicknet's group public key; the other parameters are the fixtures', as verifying only needs
+/// the key.
+pub(crate) fn quicknet_chain_info() -> ChainInfo {
+    ChainInfo {
+        public_key: hex::decode(QUICKNET_PUBLIC_KEY).unwrap(),
+        ..g1_chain_info("bls-unchained-g1-rfc9380")
+    }
+}
+
+/// Fastnet's group public key, with the fixtures' other parameters as for quicknet.
+pub(crate) fn fastnet_chain_info() -> ChainInfo {
+    ChainInfo {
+        public_key: hex::decode(FASTNET_PUBLIC_KEY).unwrap(),
+        ..g1_chain_info("bls-unchained-on-g1")
+    }
+}
+
+pub(crate) fn mainnet_beacon() -> ChainedBeacon {
+    serde_json::from_str(MAINNET_ROUND_72785_JSON).unwrap()
+}
+
+pub(crate) fn quicknet_beacon() -> UnchainedBeacon {
+    serde_json::from_str(QUICKNET_ROUND_1000_JSON).unwrap()
+}
+
+pub(crate) fn fastnet_beacon() -> UnchainedBeacon {
+    serde_json::from_str(FASTNET_ROUND_1_JSON).unwrap()
+}
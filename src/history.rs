@@ -0,0 +1,63 @@
+use crate::{Beacon, DrandClient, DrandClientError, HttpTransport, Transport};
+use serde::de::DeserializeOwned;
+
+/// Yields every beacon from round 1 up to the latest one, fetching and verifying each as it's
+/// reached. The latest round is re-fetched on reaching it, so rounds emitted in the meantime
+/// are included. Created by `DrandClient::history`.
+pub struct History<'c, B, T = HttpTransport> {
+    client: &'c DrandClient<B, T>,
+    next_round: u64,
+    latest_round: u64,
+    done: bool,
+}
+
+impl<'c, B, T> History<'c, B, T>
+where
+    B: DeserializeOwned + Clone + Beacon,
+    T: Transport,
+{
+    pub(crate) fn new(client: &'c DrandClient<B, T>) -> Self {
+        History {
+            client,
+            next_round: 1,
+            latest_round: 0,
+            done: false,
+        }
+    }
+}
+
+impl<'c, B, T> Iterator for History<'c, B, T>
+where
+    B: DeserializeOwned + Clone + Beacon,
+    T: Transport,
+{
+    type Item = Result<B, DrandClientError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        if self.next_round > self.latest_round {
+            let latest = match self.client.latest_randomness() {
+                Ok(latest) => latest,
+                // without the latest round there's no telling where the history ends
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            };
+            self.latest_round = latest.round_number();
+            if self.next_round > self.latest_round {
+                self.done = true;
+                return None;
+            }
+            if self.next_round == self.latest_round {
+                self.next_round += 1;
+                return Some(Ok(latest));
+            }
+        }
+        let round = self.next_round;
+        self.next_round += 1;
+        Some(self.client.randomness(round))
+    }
+}
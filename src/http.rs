@@ -1,33 +1,859 @@
+#[cfg(any(feature = "async", feature = "blocking"))]
+use crate::rate_limit::RateLimiter;
+#[cfg(any(feature = "async", feature = "blocking"))]
+use crate::timer;
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
 use reqwest::blocking::Client;
+#[cfg(any(feature = "async", feature = "blocking"))]
+use reqwest::header::{
+    HeaderMap, HeaderName, CACHE_CONTROL, CONTENT_TYPE, ETAG, EXPIRES, IF_MODIFIED_SINCE,
+    IF_NONE_MATCH, LAST_MODIFIED, USER_AGENT,
+};
 use reqwest::StatusCode;
+#[cfg(any(feature = "async", feature = "blocking"))]
+use std::collections::HashMap;
+use std::future::Future;
+#[cfg(any(feature = "async", feature = "blocking"))]
+use std::sync::Arc;
+#[cfg(any(feature = "async", feature = "blocking"))]
+use std::time::Duration;
+use std::time::SystemTime;
 use thiserror::Error;
 
+#[cfg(any(feature = "async", feature = "blocking"))]
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[cfg(any(feature = "async", feature = "blocking"))]
+pub const DEFAULT_USER_AGENT: &str = concat!("drand-client-rs/", env!("CARGO_PKG_VERSION"));
+
+/// How many idle connections to each relay built clients keep for reuse: reqwest's default, no
+/// limit, so a client polling and backfilling at once doesn't keep reconnecting.
+#[cfg(any(feature = "async", feature = "blocking"))]
+pub const DEFAULT_POOL_MAX_IDLE_PER_HOST: usize = usize::MAX;
+
+/// How often built clients probe idle connections, so long-lived ones, like those between
+/// `watch`'s rounds, aren't silently dropped by NATs and load balancers.
+#[cfg(any(feature = "async", feature = "blocking"))]
+pub const DEFAULT_TCP_KEEPALIVE: Duration = Duration::from_secs(60);
+
+/// The most of a response body transports read: far more than a beacon or chain info, but
+/// little enough that a relay streaming without end can't exhaust the client's memory.
+#[cfg(any(feature = "async", feature = "blocking"))]
+pub const DEFAULT_MAX_BODY_SIZE: usize = 1024 * 1024;
+
+// not among the `http` crate's named constants
+const TOO_EARLY: u16 = 425;
+
 #[derive(Error, Debug)]
 pub enum HttpError {
     #[error("not found")]
     NotFound,
+    #[error("too early")]
+    TooEarly,
+    /// The answer to a conditional request for something that hasn't changed since.
+    #[error("not modified")]
+    NotModified,
+    #[error("server error: {0}")]
+    ServerError(u16),
+    #[error("connection failed")]
+    ConnectionFailed(#[from] reqwest::Error),
+    #[error("response was not json")]
+    NotJson,
+    /// The response body was longer than the transport reads, at most `limit` bytes.
+    #[error("response body was larger than {limit} bytes")]
+    BodyTooLarge { limit: usize },
+    #[error("response body was not valid UTF-8")]
+    NotUtf8,
+    #[error("unexpected status: {0}")]
+    UnexpectedStatus(u16),
     #[error("unexpected")]
     Unexpected,
 }
 
+impl HttpError {
+    pub fn is_not_available(&self) -> bool {
+        matches!(self, HttpError::NotFound | HttpError::TooEarly)
+    }
+
+    /// The error for a response that wasn't a `200 OK`.
+    pub(crate) fn from_status(status: StatusCode) -> HttpError {
+        match status {
+            StatusCode::NOT_FOUND => HttpError::NotFound,
+            StatusCode::NOT_MODIFIED => HttpError::NotModified,
+            status if status.as_u16() == TOO_EARLY => HttpError::TooEarly,
+            status if status.is_server_error() => HttpError::ServerError(status.as_u16()),
+            status => HttpError::UnexpectedStatus(status.as_u16()),
+        }
+    }
+
+    /// The HTTP status the relay responded with, e.g. to tell a `429` from a `503`. `None` if
+    /// there was no response, as when the connection was refused or timed out.
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            HttpError::NotFound => Some(StatusCode::NOT_FOUND.as_u16()),
+            HttpError::TooEarly => Some(TOO_EARLY),
+            HttpError::NotModified => Some(StatusCode::NOT_MODIFIED.as_u16()),
+            HttpError::ServerError(status) | HttpError::UnexpectedStatus(status) => Some(*status),
+            HttpError::ConnectionFailed(e) => e.status().map(|status| status.as_u16()),
+            HttpError::NotJson
+            | HttpError::BodyTooLarge { .. }
+            | HttpError::NotUtf8
+            | HttpError::Unexpected => None,
+        }
+    }
+
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            HttpError::ServerError(_) | HttpError::ConnectionFailed(_) | HttpError::NotJson
+        )
+    }
+}
+
+/// Fetches the body of a URL, mapping failures onto [HttpError]. Implemented by [HttpTransport],
+/// and by anything else the client should make its requests through.
+pub trait Transport {
+    fn fetch(&self, url: &str) -> Result<String, HttpError>;
+
+    /// Like `fetch`, but also reports when the response goes stale, for transports that know.
+    fn fetch_response(&self, url: &str) -> Result<HttpResponse, HttpError> {
+        self.fetch(url).map(|body| HttpResponse {
+            body,
+            valid_until: None,
+            content_type: None,
+            last_modified: None,
+            etag: None,
+        })
+    }
+
+    /// Like `fetch_response`, but failing with [`HttpError::NotModified`] if the response hasn't
+    /// changed since `since`, for transports that can make conditional requests. Others just
+    /// fetch it, which is always correct if not as cheap.
+    fn fetch_if_modified_since(
+        &self,
+        url: &str,
+        since: SystemTime,
+    ) -> Result<HttpResponse, HttpError> {
+        let _ = since;
+        self.fetch_response(url)
+    }
+
+    /// Like `fetch_if_modified_since`, but also failing with [`HttpError::NotModified`] if the
+    /// response still has the ETag in `validators`, for transports that can send `If-None-Match`.
+    /// Others only ask by date, if there is one.
+    fn fetch_if_changed(
+        &self,
+        url: &str,
+        validators: &Validators,
+    ) -> Result<HttpResponse, HttpError> {
+        match validators.last_modified {
+            Some(since) => self.fetch_if_modified_since(url, since),
+            None => self.fetch_response(url),
+        }
+    }
+}
+
+/// The async counterpart of [Transport], implemented by [AsyncHttpTransport]. Its futures
+/// needn't be `Send`, so it can be implemented over the browser's fetch API.
+pub trait AsyncTransport {
+    fn fetch(&self, url: &str) -> impl Future<Output = Result<String, HttpError>>;
+
+    fn fetch_response(&self, url: &str) -> impl Future<Output = Result<HttpResponse, HttpError>> {
+        async move {
+            self.fetch(url).await.map(|body| HttpResponse {
+                body,
+                valid_until: None,
+                content_type: None,
+                last_modified: None,
+                etag: None,
+            })
+        }
+    }
+
+    fn fetch_if_modified_since(
+        &self,
+        url: &str,
+        since: SystemTime,
+    ) -> impl Future<Output = Result<HttpResponse, HttpError>> {
+        let _ = since;
+        self.fetch_response(url)
+    }
+
+    fn fetch_if_changed(
+        &self,
+        url: &str,
+        validators: &Validators,
+    ) -> impl Future<Output = Result<HttpResponse, HttpError>> {
+        async move {
+            match validators.last_modified {
+                Some(since) => self.fetch_if_modified_since(url, since).await,
+                None => self.fetch_response(url).await,
+            }
+        }
+    }
+}
+
+/// A successful response, along with when it goes stale according to its caching headers.
+pub struct HttpResponse {
+    pub body: String,
+    pub valid_until: Option<SystemTime>,
+    pub content_type: Option<String>,
+    /// When the relay says the response last changed, to make conditional requests with.
+    pub last_modified: Option<SystemTime>,
+    /// The relay's tag for this version of the response, to make conditional requests with.
+    pub etag: Option<String>,
+}
+
+/// What a response was served with to tell whether it's since changed, its `Last-Modified` date
+/// and `ETag`, for asking for it again only if it has.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Validators {
+    pub last_modified: Option<SystemTime>,
+    pub etag: Option<String>,
+}
+
+#[cfg(any(feature = "async", feature = "blocking"))]
+impl Validators {
+    pub(crate) fn of(res: &HttpResponse) -> Self {
+        Validators {
+            last_modified: res.last_modified,
+            etag: res.etag.clone(),
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.last_modified.is_none() && self.etag.is_none()
+    }
+}
+
+impl HttpResponse {
+    /// Passes the response on only if it's JSON, going by its `Content-Type` or, without one,
+    /// its body. Overloaded relays and the proxies in front of them can answer `200 OK` with an
+    /// HTML or plain text error page, which is no more a beacon than a `503` is.
+    #[cfg(any(feature = "async", feature = "blocking"))]
+    pub(crate) fn json(self) -> Result<HttpResponse, HttpError> {
+        let is_json = match &self.content_type {
+            Some(content_type) => content_type.to_ascii_lowercase().contains("json"),
+            None => self.body.trim_start().starts_with('{'),
+        };
+        if is_json {
+            Ok(self)
+        } else {
+            Err(HttpError::NotJson)
+        }
+    }
+}
+
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+#[derive(Clone)]
 pub struct HttpTransport {
     pub client: Client,
+    pub timeout: Duration,
+    pub user_agent: String,
+    /// Extra headers sent with every request, e.g. credentials for an auth gateway.
+    pub headers: HashMap<String, String>,
+    /// Throttles requests to the limiter's budget, if there is one.
+    pub rate_limiter: Option<Arc<RateLimiter>>,
+    /// Responses with longer bodies, once decompressed, fail with [`HttpError::BodyTooLarge`].
+    pub max_body_size: usize,
 }
 
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+impl Default for HttpTransport {
+    /// Negotiates and transparently decompresses gzip and brotli responses.
+    fn default() -> Self {
+        HttpTransport::with_client(Client::new())
+    }
+}
+
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
 impl HttpTransport {
-    pub fn fetch(&self, url: &str) -> Result<String, HttpError> {
-        let res = self
+    /// A transport making requests through `client`, e.g. one configured with a proxy or
+    /// custom TLS roots, with the default timeout.
+    pub fn with_client(client: Client) -> Self {
+        HttpTransport {
+            client,
+            timeout: DEFAULT_TIMEOUT,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            headers: HashMap::new(),
+            rate_limiter: None,
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = user_agent.to_string();
+        self
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    /// Sends requests no faster than `rate_limiter` allows.
+    pub fn with_rate_limiter(mut self, rate_limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    pub fn with_max_body_size(mut self, max_body_size: usize) -> Self {
+        self.max_body_size = max_body_size;
+        self
+    }
+}
+
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+impl Transport for HttpTransport {
+    fn fetch(&self, url: &str) -> Result<String, HttpError> {
+        self.fetch_response(url).map(|res| res.body)
+    }
+
+    fn fetch_response(&self, url: &str) -> Result<HttpResponse, HttpError> {
+        self.send(url, &Validators::default())
+    }
+
+    fn fetch_if_modified_since(
+        &self,
+        url: &str,
+        since: SystemTime,
+    ) -> Result<HttpResponse, HttpError> {
+        let validators = Validators {
+            last_modified: Some(since),
+            etag: None,
+        };
+        self.send(url, &validators)
+    }
+
+    fn fetch_if_changed(
+        &self,
+        url: &str,
+        validators: &Validators,
+    ) -> Result<HttpResponse, HttpError> {
+        self.send(url, validators)
+    }
+}
+
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+impl HttpTransport {
+    fn send(&self, url: &str, validators: &Validators) -> Result<HttpResponse, HttpError> {
+        let mut req = self
             .client
             .get(url)
-            .send()
-            .map_err(|_| HttpError::Unexpected)?;
+            .timeout(self.timeout)
+            .header(USER_AGENT, &self.user_agent);
+        for (name, value) in &self.headers {
+            req = req.header(name, value);
+        }
+        if let Some(since) = validators.last_modified {
+            req = req.header(IF_MODIFIED_SINCE, httpdate::fmt_http_date(since));
+        }
+        if let Some(etag) = &validators.etag {
+            req = req.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.wait();
+        }
+        let res = req.send()?;
+
+        match res.status() {
+            StatusCode::OK => {
+                let valid_until = valid_until(res.headers(), timer::now());
+                let content_type = header(res.headers(), CONTENT_TYPE).map(str::to_string);
+                let last_modified = last_modified(res.headers());
+                let etag = header(res.headers(), ETAG).map(str::to_string);
+                let body = read_body(res, self.max_body_size)?;
+                Ok(HttpResponse {
+                    body,
+                    valid_until,
+                    content_type,
+                    last_modified,
+                    etag,
+                })
+            }
+
+            status => Err(HttpError::from_status(status)),
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+#[derive(Clone)]
+pub struct AsyncHttpTransport {
+    pub client: reqwest::Client,
+    pub timeout: Duration,
+    pub user_agent: String,
+    /// Extra headers sent with every request, e.g. credentials for an auth gateway.
+    pub headers: HashMap<String, String>,
+    /// Throttles requests to the limiter's budget, if there is one.
+    pub rate_limiter: Option<Arc<RateLimiter>>,
+    /// Responses with longer bodies, once decompressed, fail with [`HttpError::BodyTooLarge`].
+    pub max_body_size: usize,
+}
+
+#[cfg(feature = "async")]
+impl Default for AsyncHttpTransport {
+    /// Negotiates and transparently decompresses gzip and brotli responses.
+    fn default() -> Self {
+        AsyncHttpTransport::with_client(reqwest::Client::new())
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncHttpTransport {
+    /// A transport making requests through `client`, e.g. one configured with a proxy or
+    /// custom TLS roots, with the default timeout.
+    pub fn with_client(client: reqwest::Client) -> Self {
+        AsyncHttpTransport {
+            client,
+            timeout: DEFAULT_TIMEOUT,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            headers: HashMap::new(),
+            rate_limiter: None,
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = user_agent.to_string();
+        self
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    /// Sends requests no faster than `rate_limiter` allows.
+    pub fn with_rate_limiter(mut self, rate_limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    pub fn with_max_body_size(mut self, max_body_size: usize) -> Self {
+        self.max_body_size = max_body_size;
+        self
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncTransport for AsyncHttpTransport {
+    async fn fetch(&self, url: &str) -> Result<String, HttpError> {
+        self.fetch_response(url).await.map(|res| res.body)
+    }
+
+    async fn fetch_response(&self, url: &str) -> Result<HttpResponse, HttpError> {
+        self.send(url, &Validators::default()).await
+    }
+
+    async fn fetch_if_modified_since(
+        &self,
+        url: &str,
+        since: SystemTime,
+    ) -> Result<HttpResponse, HttpError> {
+        let validators = Validators {
+            last_modified: Some(since),
+            etag: None,
+        };
+        self.send(url, &validators).await
+    }
+
+    async fn fetch_if_changed(
+        &self,
+        url: &str,
+        validators: &Validators,
+    ) -> Result<HttpResponse, HttpError> {
+        self.send(url, validators).await
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncHttpTransport {
+    async fn send(&self, url: &str, validators: &Validators) -> Result<HttpResponse, HttpError> {
+        let mut req = self.client.get(url).header(USER_AGENT, &self.user_agent);
+        // reqwest can't time out requests made through the browser's fetch API
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            req = req.timeout(self.timeout);
+        }
+        for (name, value) in &self.headers {
+            req = req.header(name, value);
+        }
+        if let Some(since) = validators.last_modified {
+            req = req.header(IF_MODIFIED_SINCE, httpdate::fmt_http_date(since));
+        }
+        if let Some(etag) = &validators.etag {
+            req = req.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.wait_async().await;
+        }
+        let res = req.send().await?;
 
         match res.status() {
-            StatusCode::OK => res.text().map_err(|_| HttpError::Unexpected),
+            StatusCode::OK => {
+                let valid_until = valid_until(res.headers(), timer::now());
+                let content_type = header(res.headers(), CONTENT_TYPE).map(str::to_string);
+                let last_modified = last_modified(res.headers());
+                let etag = header(res.headers(), ETAG).map(str::to_string);
+                let body = read_body_async(res, self.max_body_size).await?;
+                Ok(HttpResponse {
+                    body,
+                    valid_until,
+                    content_type,
+                    last_modified,
+                    etag,
+                })
+            }
+
+            status => Err(HttpError::from_status(status)),
+        }
+    }
+}
+
+/// Reads at most `max_body_size` bytes of the decompressed body, rather than however much the
+/// relay sends.
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+fn read_body(res: reqwest::blocking::Response, max_body_size: usize) -> Result<String, HttpError> {
+    use std::io::Read;
+    let mut body = Vec::new();
+    res.take((max_body_size as u64).saturating_add(1))
+        .read_to_end(&mut body)
+        .map_err(|e| {
+            // reading fails with the underlying reqwest error, e.g. on timing out
+            match e.into_inner().map(|e| e.downcast::<reqwest::Error>()) {
+                Some(Ok(e)) => HttpError::ConnectionFailed(*e),
+                _ => HttpError::Unexpected,
+            }
+        })?;
+    body_text(body, max_body_size)
+}
+
+/// Reads the body a chunk at a time, giving up once it's longer than `max_body_size`. In WASM the
+/// browser has already read it, but it's still rejected.
+#[cfg(feature = "async")]
+async fn read_body_async(
+    res: reqwest::Response,
+    max_body_size: usize,
+) -> Result<String, HttpError> {
+    let too_large = HttpError::BodyTooLarge {
+        limit: max_body_size,
+    };
+    #[cfg(not(target_arch = "wasm32"))]
+    let body = {
+        let mut res = res;
+        let mut body = Vec::new();
+        while let Some(chunk) = res.chunk().await? {
+            if body.len() + chunk.len() > max_body_size {
+                return Err(too_large);
+            }
+            body.extend_from_slice(&chunk);
+        }
+        body
+    };
+    #[cfg(target_arch = "wasm32")]
+    let body = {
+        if res.content_length().unwrap_or(0) > max_body_size as u64 {
+            return Err(too_large);
+        }
+        res.bytes().await?.to_vec()
+    };
+    body_text(body, max_body_size)
+}
+
+#[cfg(any(feature = "async", feature = "blocking"))]
+fn body_text(body: Vec<u8>, max_body_size: usize) -> Result<String, HttpError> {
+    if body.len() > max_body_size {
+        return Err(HttpError::BodyTooLarge {
+            limit: max_body_size,
+        });
+    }
+    String::from_utf8(body).map_err(|_| HttpError::NotUtf8)
+}
+
+#[cfg(any(feature = "async", feature = "blocking"))]
+fn header(headers: &HeaderMap, name: HeaderName) -> Option<&str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+#[cfg(any(feature = "async", feature = "blocking"))]
+fn last_modified(headers: &HeaderMap) -> Option<SystemTime> {
+    header(headers, LAST_MODIFIED).and_then(|date| httpdate::parse_http_date(date).ok())
+}
+
+/// `Cache-Control: max-age` takes precedence over `Expires`, as in RFC 9111. A `max-age` too far in
+/// the future for a `SystemTime` is no expiry at all.
+#[cfg(any(feature = "async", feature = "blocking"))]
+fn valid_until(headers: &HeaderMap, now: SystemTime) -> Option<SystemTime> {
+    let max_age = header(headers, CACHE_CONTROL).and_then(|directives| {
+        directives
+            .split(',')
+            .find_map(|directive| directive.trim().strip_prefix("max-age=")?.parse().ok())
+    });
+    match max_age {
+        Some(seconds) => now.checked_add(Duration::from_secs(seconds)),
+        None => header(headers, EXPIRES).and_then(|date| httpdate::parse_http_date(date).ok()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::http::{
+        valid_until, HttpError, HttpResponse, HttpTransport, Transport, Validators,
+        DEFAULT_USER_AGENT,
+    };
+    use crate::RateLimiter;
+    use reqwest::header::{HeaderMap, HeaderValue, CACHE_CONTROL, EXPIRES};
+    use reqwest::StatusCode;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::num::NonZeroU32;
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::{Duration, Instant, SystemTime};
+
+    #[test]
+    fn max_age_takes_precedence_over_expires() {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let mut headers = HeaderMap::new();
+        assert_eq!(valid_until(&headers, now), None);
+
+        headers.insert(
+            EXPIRES,
+            HeaderValue::from_static("Thu, 01 Jan 1970 00:01:00 GMT"),
+        );
+        assert_eq!(
+            valid_until(&headers, now),
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(60))
+        );
+
+        headers.insert(
+            CACHE_CONTROL,
+            HeaderValue::from_static("public, max-age=25"),
+        );
+        assert_eq!(
+            valid_until(&headers, now),
+            Some(now + Duration::from_secs(25))
+        );
+
+        headers.insert(
+            CACHE_CONTROL,
+            HeaderValue::from_static("max-age=18446744073709551615"),
+        );
+        assert_eq!(valid_until(&headers, now), None);
+    }
+
+    #[test]
+    fn only_json_responses_are_passed_on() {
+        let response = |content_type: Option<&str>, body: &str| HttpResponse {
+            body: body.to_string(),
+            valid_until: None,
+            content_type: content_type.map(str::to_string),
+            last_modified: None,
+            etag: None,
+        };
+        assert!(response(Some("application/json; charset=utf-8"), "{}")
+            .json()
+            .is_ok());
+        assert!(response(None, "  {\"round\": 1}").json().is_ok());
+        assert!(matches!(
+            response(Some("text/html"), "{}").json(),
+            Err(HttpError::NotJson)
+        ));
+        assert!(matches!(
+            response(None, "<html></html>").json(),
+            Err(HttpError::NotJson)
+        ));
+    }
+
+    #[test]
+    fn transport_builder_sets_identity_and_headers() {
+        let transport = HttpTransport::default()
+            .with_timeout(Duration::from_secs(1))
+            .with_user_agent("my-app/1.0")
+            .with_header("Authorization", "Bearer token");
+        assert_eq!(transport.timeout, Duration::from_secs(1));
+        assert_eq!(transport.user_agent, "my-app/1.0");
+        assert_eq!(transport.headers["Authorization"], "Bearer token");
+        assert!(DEFAULT_USER_AGENT.starts_with("drand-client-rs/"));
+    }
 
-            StatusCode::NOT_FOUND => Err(HttpError::NotFound),
+    #[test]
+    fn gzipped_responses_are_decompressed() {
+        // `{"round":1}`, gzipped
+        let body =
+            hex::decode("1f8b0800000000000203ab562aca2fcd4b51b232ac050019a380010b000000").unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/public/1", listener.local_addr().unwrap());
+        let relay = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0; 4096];
+            let len = stream.read(&mut request).unwrap();
+            let head = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Encoding: gzip\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            stream.write_all(head.as_bytes()).unwrap();
+            stream.write_all(&body).unwrap();
+            String::from_utf8_lossy(&request[..len]).to_ascii_lowercase()
+        });
 
-            _ => Err(HttpError::Unexpected),
+        let res = HttpTransport::default().fetch_response(&url).unwrap();
+        assert_eq!(res.body, r#"{"round":1}"#);
+        let request = relay.join().unwrap();
+        let accept_encoding = request
+            .lines()
+            .find(|line| line.starts_with("accept-encoding:"))
+            .unwrap();
+        assert!(accept_encoding.contains("gzip") && accept_encoding.contains("br"));
+    }
+
+    #[test]
+    fn conditional_requests_send_if_modified_since() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/public/latest", listener.local_addr().unwrap());
+        let relay = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0; 4096];
+            let len = stream.read(&mut request).unwrap();
+            stream
+                .write_all(b"HTTP/1.1 304 Not Modified\r\nConnection: close\r\n\r\n")
+                .unwrap();
+            String::from_utf8_lossy(&request[..len]).to_ascii_lowercase()
+        });
+
+        let since = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let res = HttpTransport::default().fetch_if_modified_since(&url, since);
+        assert!(matches!(res, Err(HttpError::NotModified)));
+        let request = relay.join().unwrap();
+        assert!(request
+            .lines()
+            .any(|line| line == "if-modified-since: tue, 14 nov 2023 22:13:20 gmt"));
+    }
+
+    #[test]
+    fn etags_are_kept_and_sent_back_as_if_none_match() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/public/latest", listener.local_addr().unwrap());
+        let relay = thread::spawn(move || {
+            let mut requests = Vec::new();
+            for head in [
+                "HTTP/1.1 200 OK\r\nETag: \"round-3\"\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{}",
+                "HTTP/1.1 304 Not Modified\r\nConnection: close\r\n\r\n",
+            ] {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = [0; 4096];
+                let len = stream.read(&mut request).unwrap();
+                stream.write_all(head.as_bytes()).unwrap();
+                requests.push(String::from_utf8_lossy(&request[..len]).to_ascii_lowercase());
+            }
+            requests
+        });
+
+        let transport = HttpTransport::default();
+        let res = transport.fetch_response(&url).unwrap();
+        assert_eq!(res.etag.as_deref(), Some("\"round-3\""));
+        let res = transport.fetch_if_changed(&url, &Validators::of(&res));
+        assert!(matches!(res, Err(HttpError::NotModified)));
+        let requests = relay.join().unwrap();
+        assert!(!requests[0].contains("if-none-match"));
+        assert!(requests[1]
+            .lines()
+            .any(|line| line == "if-none-match: \"round-3\""));
+    }
+
+    /// Serves `body` as JSON to a single request to the returned URL.
+    fn serve_once(body: &'static [u8]) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/public/latest", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0; 4096];
+            let _ = stream.read(&mut request).unwrap();
+            let head = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            stream.write_all(head.as_bytes()).unwrap();
+            let _ = stream.write_all(body);
+        });
+        url
+    }
+
+    #[test]
+    fn bodies_longer_than_the_limit_are_rejected() {
+        let transport = HttpTransport::default().with_max_body_size(8);
+        let url = serve_once(br#"{"round":1}"#);
+        assert!(matches!(
+            transport.fetch(&url),
+            Err(HttpError::BodyTooLarge { limit: 8 })
+        ));
+
+        let transport = HttpTransport::default().with_max_body_size(11);
+        let url = serve_once(br#"{"round":1}"#);
+        assert_eq!(transport.fetch(&url).unwrap(), r#"{"round":1}"#);
+    }
+
+    #[test]
+    fn bodies_must_be_utf8() {
+        let url = serve_once(b"{\"round\":\xff}");
+        assert!(matches!(
+            HttpTransport::default().fetch(&url),
+            Err(HttpError::NotUtf8)
+        ));
+    }
+
+    #[test]
+    fn rate_limited_requests_wait_their_turn() {
+        let limiter = RateLimiter::per_second(NonZeroU32::new(20).unwrap())
+            .with_burst(NonZeroU32::new(1).unwrap());
+        let transport = HttpTransport::default().with_rate_limiter(Arc::new(limiter));
+        let started = Instant::now();
+        for _ in 0..3 {
+            assert!(transport.fetch("http://127.0.0.1:1/info").is_err());
         }
+        // the first request is sent at once, and each after it 50ms after the one before
+        assert!(started.elapsed() >= Duration::from_millis(100));
+    }
+
+    #[test]
+    fn missing_rounds_are_not_available_rather_than_transient() {
+        assert!(HttpError::NotFound.is_not_available());
+        assert!(HttpError::TooEarly.is_not_available());
+        assert!(!HttpError::NotFound.is_transient());
+        assert!(!HttpError::TooEarly.is_transient());
+    }
+
+    #[test]
+    fn server_errors_are_transient() {
+        assert!(HttpError::ServerError(503).is_transient());
+        assert!(!HttpError::ServerError(503).is_not_available());
+    }
+
+    #[test]
+    fn statuses_are_kept() {
+        assert_eq!(
+            HttpError::from_status(StatusCode::TOO_MANY_REQUESTS).status(),
+            Some(429)
+        );
+        assert_eq!(
+            HttpError::from_status(StatusCode::SERVICE_UNAVAILABLE).status(),
+            Some(503)
+        );
+        assert_eq!(
+            HttpError::from_status(StatusCode::NOT_FOUND).status(),
+            Some(404)
+        );
+        assert_eq!(HttpError::Unexpected.status(), None);
     }
 }
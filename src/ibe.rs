@@ -0,0 +1,278 @@
+use crate::bls::{self, Group, G2_DOMAIN};
+use bls12_381::{pairing, G1Affine, G2Affine, Gt, Scalar};
+use sha2::{Digest, Sha256};
+
+/// The length of the messages drand's identity-based encryption wraps; age file keys are 16 bytes.
+pub(crate) const MESSAGE_LEN: usize = 16;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub(crate) enum SignatureGroup {
+    /// Signatures (and so identities) on G1, with the group public key on G2.
+    G1 { domain: &'static [u8] },
+    /// Signatures on G2, with the group public key on G1.
+    G2,
+}
+
+impl SignatureGroup {
+    /// The group layout of the schemes that can be encrypted to; chained beacons sign over the
+    /// previous signature, which isn't known ahead of time, so they can't be.
+    pub(crate) fn for_scheme(scheme_id: &str) -> Option<SignatureGroup> {
+        if scheme_id.eq_ignore_ascii_case("pedersen-bls-chained") {
+            return None;
+        }
+        bls::ciphersuite(scheme_id).map(|suite| match suite.signature_group {
+            Group::G1 => SignatureGroup::G1 { domain: suite.dst },
+            Group::G2 => SignatureGroup::G2,
+        })
+    }
+}
+
+/// Boneh-Franklin CCA encryption of `message` to `identity`, as implemented by drand's kyber.
+/// Returns `U || V || W`, or `None` if the public key isn't a valid point.
+pub(crate) fn encrypt(
+    group: SignatureGroup,
+    public_key: &[u8],
+    identity: &[u8],
+    message: &[u8; MESSAGE_LEN],
+    sigma: &[u8; MESSAGE_LEN],
+) -> Option<Vec<u8>> {
+    let r = h3(sigma, message);
+
+    let gid = match group {
+        SignatureGroup::G1 { domain } => {
+            let public_key = bls::g2_from_slice(public_key)?;
+            pairing(&bls::hash_to_g1(identity, domain), &public_key)
+        }
+        SignatureGroup::G2 => {
+            let public_key = bls::g1_from_slice(public_key)?;
+            pairing(&public_key, &bls::hash_to_g2(identity, G2_DOMAIN))
+        }
+    };
+
+    let mut ciphertext = commitment(group, &r);
+    ciphertext.extend(xor(sigma, &h2(&(gid * r))));
+    ciphertext.extend(xor(message, &h4(sigma)));
+    Some(ciphertext)
+}
+
+/// Recovers the message from `U || V || W` using the identity's private key, i.e. the round's
+/// signature. Returns `None` if either is malformed or the ciphertext fails its integrity check.
+pub(crate) fn decrypt(
+    group: SignatureGroup,
+    signature: &[u8],
+    ciphertext: &[u8],
+) -> Option<[u8; MESSAGE_LEN]> {
+    let u_len = commitment_len(group);
+    if ciphertext.len() != u_len + 2 * MESSAGE_LEN {
+        return None;
+    }
+    let (u, rest) = ciphertext.split_at(u_len);
+    let (v, w) = rest.split_at(MESSAGE_LEN);
+
+    let rgid = match group {
+        SignatureGroup::G1 { .. } => {
+            pairing(&bls::g1_from_slice(signature)?, &bls::g2_from_slice(u)?)
+        }
+        SignatureGroup::G2 => pairing(&bls::g1_from_slice(u)?, &bls::g2_from_slice(signature)?),
+    };
+
+    let sigma = xor(v, &h2(&rgid));
+    let message = xor(w, &h4(&sigma));
+
+    // U commits to sigma and the message, so a ciphertext that was tampered with won't match
+    if commitment(group, &h3(&sigma, &message)) != u {
+        return None;
+    }
+    message.try_into().ok()
+}
+
+fn commitment(group: SignatureGroup, r: &Scalar) -> Vec<u8> {
+    match group {
+        SignatureGroup::G1 { .. } => G2Affine::from(G2Affine::generator() * r)
+            .to_compressed()
+            .to_vec(),
+        SignatureGroup::G2 => G1Affine::from(G1Affine::generator() * r)
+            .to_compressed()
+            .to_vec(),
+    }
+}
+
+fn commitment_len(group: SignatureGroup) -> usize {
+    match group {
+        SignatureGroup::G1 { .. } => 96,
+        SignatureGroup::G2 => 48,
+    }
+}
+
+fn h2(gid: &Gt) -> Vec<u8> {
+    tagged_hash(b"IBE-H2", &gt_to_bytes(gid))
+}
+
+fn h3(sigma: &[u8], message: &[u8]) -> Scalar {
+    let buffer = Sha256::new()
+        .chain(b"IBE-H3")
+        .chain(sigma)
+        .chain(message)
+        .finalize();
+
+    // rejection sampling, matching kyber: mask the top bit and parse as a big-endian scalar
+    (1..=u16::MAX)
+        .find_map(|i| {
+            let mut hashed: [u8; 32] = Sha256::new()
+                .chain(i.to_le_bytes())
+                .chain(buffer)
+                .finalize()
+                .into();
+            hashed[0] >>= 1;
+            hashed.reverse();
+            Option::from(Scalar::from_bytes(&hashed))
+        })
+        .expect("a masked hash is below the scalar modulus about half the time")
+}
+
+fn h4(sigma: &[u8]) -> Vec<u8> {
+    tagged_hash(b"IBE-H4", sigma)
+}
+
+fn tagged_hash(tag: &[u8], data: &[u8]) -> Vec<u8> {
+    Sha256::new()
+        .chain(tag)
+        .chain(data)
+        .finalize()
+        .iter()
+        .take(MESSAGE_LEN)
+        .copied()
+        .collect()
+}
+
+/// Serialises a target group element the way kyber does: its twelve base field coefficients,
+/// highest first. bls12_381 keeps the coefficients private, but its debug output lists all of
+/// them as big-endian hex, lowest first. That output isn't a stable API, so the dependency is
+/// pinned to an exact version and the tests check the bytes of a known element.
+fn gt_to_bytes(gt: &Gt) -> Vec<u8> {
+    let mut coefficients: Vec<Vec<u8>> = format!("{:?}", gt)
+        .split("0x")
+        .skip(1)
+        .map(|coefficient| {
+            coefficient
+                .get(..2 * FP_LEN)
+                .and_then(|hex| hex::decode(hex).ok())
+        })
+        .collect::<Option<Vec<_>>>()
+        .filter(|coefficients| coefficients.len() == 12)
+        .expect("bls12_381's debug output for Gt is twelve hex coefficients");
+    coefficients.reverse();
+    coefficients.concat()
+}
+
+const FP_LEN: usize = 48;
+
+fn xor(a: &[u8], b: &[u8]) -> Vec<u8> {
+    a.iter().zip(b).map(|(x, y)| x ^ y).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::bls::{round_digest, G1_DOMAIN, G2_DOMAIN};
+    use crate::fixtures;
+    use crate::ibe::{decrypt, encrypt, gt_to_bytes, SignatureGroup};
+    use bls12_381::{pairing, G1Affine, G2Affine, Gt};
+
+    #[test]
+    fn messages_decrypt_with_the_identity_signature() {
+        let message = [3; 16];
+        let cases = [
+            (
+                SignatureGroup::G1 { domain: G1_DOMAIN },
+                fixtures::g1_chain_info("bls-unchained-g1-rfc9380"),
+                fixtures::unchained_g1_beacon(5, G1_DOMAIN),
+            ),
+            (
+                SignatureGroup::G1 { domain: G2_DOMAIN },
+                fixtures::g1_chain_info("bls-unchained-on-g1"),
+                fixtures::unchained_g1_beacon(5, G2_DOMAIN),
+            ),
+            (
+                SignatureGroup::G2,
+                fixtures::chain_info("pedersen-bls-unchained"),
+                fixtures::unchained_beacon(5),
+            ),
+        ];
+
+        for (group, info, beacon) in cases {
+            let ciphertext = encrypt(
+                group,
+                &info.public_key,
+                &round_digest(5),
+                &message,
+                &[4; 16],
+            )
+            .unwrap();
+            assert_eq!(
+                decrypt(group, &beacon.signature, &ciphertext),
+                Some(message)
+            );
+        }
+    }
+
+    #[test]
+    fn another_identity_cannot_decrypt() {
+        let group = SignatureGroup::G1 { domain: G1_DOMAIN };
+        let info = fixtures::g1_chain_info("bls-unchained-g1-rfc9380");
+        let ciphertext = encrypt(
+            group,
+            &info.public_key,
+            &round_digest(5),
+            &[3; 16],
+            &[4; 16],
+        )
+        .unwrap();
+        let beacon = fixtures::unchained_g1_beacon(6, G1_DOMAIN);
+        assert_eq!(decrypt(group, &beacon.signature, &ciphertext), None);
+    }
+
+    #[test]
+    fn target_group_elements_serialise_to_twelve_coefficients() {
+        let identity = gt_to_bytes(&Gt::identity());
+        assert_eq!(identity.len(), 12 * 48);
+        // the identity is one in the lowest coefficient, which kyber writes last
+        assert!(identity[..11 * 48].iter().all(|b| *b == 0));
+        assert_eq!(identity[12 * 48 - 1], 1);
+    }
+
+    // e(G1, G2) for the generators, whose lowest coefficient is the widely published 0x1250eb..
+    const GENERATOR: [&str; 12] = [
+        "0f41e58663bf08cf068672cbd01a7ec73baca4d72ca93544deff686bfd6df543d48eaa24afe47e1efde449383b676631",
+        "04c581234d086a9902249b64728ffd21a189e87935a954051c7cdba7b3872629a4fafc05066245cb9108f0242d0fe3ef",
+        "03350f55a7aefcd3c31b4fcb6ce5771cc6a0e9786ab5973320c806ad360829107ba810c5a09ffdd9be2291a0c25a99a2",
+        "11b8b424cd48bf38fcef68083b0b0ec5c81a93b330ee1a677d0d15ff7b984e8978ef48881e32fac91b93b47333e2ba57",
+        "06fba23eb7c5af0d9f80940ca771b6ffd5857baaf222eb95a7d2809d61bfe02e1bfd1b68ff02f0b8102ae1c2d5d5ab1a",
+        "19f26337d205fb469cd6bd15c3d5a04dc88784fbb3d0b2dbdea54d43b2b73f2cbb12d58386a8703e0f948226e47ee89d",
+        "018107154f25a764bd3c79937a45b84546da634b8f6be14a8061e55cceba478b23f7dacaa35c8ca78beae9624045b4b6",
+        "01b2f522473d171391125ba84dc4007cfbf2f8da752f7c74185203fcca589ac719c34dffbbaad8431dad1c1fb597aaa5",
+        "193502b86edb8857c273fa075a50512937e0794e1e65a7617c90d8bd66065b1fffe51d7a579973b1315021ec3c19934f",
+        "1368bb445c7c2d209703f239689ce34c0378a68e72a6b3b216da0e22a5031b54ddff57309396b38c881c4c849ec23e87",
+        "089a1c5b46e5110b86750ec6a532348868a84045483c92b7af5af689452eafabf1a8943e50439f1d59882a98eaa0170f",
+        "1250ebd871fc0a92a7b2d83168d0d727272d441befa15c503dd8e90ce98db3e7b6d194f60839c508a84305aaca1789b6",
+    ];
+
+    #[test]
+    fn target_group_elements_serialise_as_kyber_does() {
+        let generator = pairing(&G1Affine::generator(), &G2Affine::generator());
+        assert_eq!(hex::encode(gt_to_bytes(&generator)), GENERATOR.concat());
+
+        // negating conjugates, flipping the sign of the six coefficients kyber writes first
+        let negated = gt_to_bytes(&-generator);
+        assert_ne!(negated[..6 * 48], gt_to_bytes(&generator)[..6 * 48]);
+        assert_eq!(negated[6 * 48..], gt_to_bytes(&generator)[6 * 48..]);
+    }
+
+    #[test]
+    fn chained_schemes_cannot_be_encrypted_to() {
+        assert_eq!(SignatureGroup::for_scheme("pedersen-bls-chained"), None);
+        assert_eq!(
+            SignatureGroup::for_scheme("pedersen-bls-unchained"),
+            Some(SignatureGroup::G2)
+        );
+    }
+}
@@ -1,172 +1,695 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
 extern crate core;
 
-mod bls;
+#[cfg(all(feature = "chained", feature = "unchained"))]
+mod any;
+#[cfg(feature = "async")]
+pub mod async_client;
+#[cfg(all(feature = "chained", feature = "unchained"))]
+mod auto;
+pub mod bls;
+#[cfg(any(feature = "async", feature = "blocking"))]
+mod builder;
+#[cfg(any(feature = "async", feature = "blocking"))]
+mod cache;
 mod chain_info;
+#[cfg(feature = "chained")]
 mod chained;
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+mod client;
+#[cfg(any(feature = "async", feature = "blocking"))]
+mod clock;
+#[cfg(any(feature = "chained", feature = "unchained"))]
+mod custom_dst;
+#[cfg(feature = "std")]
+mod derive;
+#[cfg(any(feature = "async", feature = "blocking"))]
+mod endpoints;
+#[cfg(test)]
+mod fixtures;
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+mod history;
+#[cfg(feature = "std")]
 mod http;
+#[cfg(feature = "tlock")]
+mod ibe;
+#[cfg(feature = "std")]
+mod metrics;
+#[cfg(feature = "std")]
+mod mock;
+#[cfg(any(feature = "async", feature = "blocking"))]
+pub mod networks;
+#[cfg(any(feature = "async", feature = "blocking"))]
+mod options;
+#[cfg(any(feature = "async", feature = "blocking"))]
+mod rate_limit;
+#[cfg(feature = "std")]
+mod retry;
+#[cfg(feature = "std")]
+mod round;
+#[cfg(any(feature = "async", feature = "blocking"))]
+mod timer;
+#[cfg(feature = "tlock")]
+pub mod tlock;
+#[cfg(feature = "unchained")]
 mod unchained;
+#[cfg(feature = "g1")]
+mod unchained_g1;
+#[cfg(any(feature = "async", feature = "blocking"))]
+mod watch;
 
-use crate::chain_info::ChainInfo;
-use crate::chained::{ChainedBeacon, ChainedScheme};
-use crate::http::HttpTransport;
-use crate::unchained::{UnchainedBeacon, UnchainedScheme};
-use crate::DrandClientError::{InvalidChainInfo, InvalidRound};
-use reqwest::blocking::Client;
+#[cfg(all(feature = "chained", feature = "unchained"))]
+pub use crate::any::{AnyBeacon, AnyScheme};
+use crate::bls::PointError;
+#[cfg(any(feature = "async", feature = "blocking"))]
+pub use crate::builder::{ConfigError, DrandClientBuilder};
+#[cfg(any(feature = "async", feature = "blocking"))]
+use crate::chain_info::BEACON_ID_FIELDS;
+pub use crate::chain_info::{ChainInfo, ChainInfoMetadata, NetworkSummary};
+#[cfg(feature = "chained")]
+pub use crate::chained::{ChainedBeacon, ChainedScheme};
+#[cfg(all(
+    feature = "blocking",
+    not(target_arch = "wasm32"),
+    feature = "chained",
+    feature = "unchained"
+))]
+pub use crate::client::new_auto_client;
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32"), feature = "chained"))]
+pub use crate::client::new_chained_client;
+#[cfg(all(
+    feature = "blocking",
+    not(target_arch = "wasm32"),
+    feature = "unchained"
+))]
+pub use crate::client::new_unchained_client;
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+pub use crate::client::{
+    new_client, new_client_for_chain, new_client_with_cache, new_client_with_chain_hash,
+    new_client_with_failover, new_client_with_metrics, new_client_with_public_key,
+    new_client_with_retry, new_client_with_timeout, new_client_with_transport, DrandClient,
+};
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32"), feature = "g1"))]
+pub use crate::client::{new_unchained_g1_client, new_unchained_g1_rfc9380_client};
+#[cfg(any(feature = "async", feature = "blocking"))]
+pub use crate::clock::{Clock, MockClock, SystemClock};
+#[cfg(any(feature = "chained", feature = "unchained"))]
+pub use crate::custom_dst::CustomDst;
+#[cfg(feature = "std")]
+pub use crate::derive::{beacon_rng, derive_randomness, MAX_DERIVED_LEN};
+#[cfg(any(feature = "async", feature = "blocking"))]
+pub use crate::endpoints::ApiVersion;
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+pub use crate::history::History;
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+pub use crate::http::HttpTransport;
+#[cfg(feature = "std")]
+pub use crate::http::{AsyncTransport, HttpError, HttpResponse, Transport, Validators};
+#[cfg(any(feature = "async", feature = "blocking"))]
+pub use crate::http::{
+    DEFAULT_MAX_BODY_SIZE, DEFAULT_POOL_MAX_IDLE_PER_HOST, DEFAULT_TCP_KEEPALIVE,
+};
+#[cfg(feature = "std")]
+pub use crate::metrics::{Metrics, NoopMetrics};
+#[cfg(feature = "std")]
+pub use crate::mock::MockTransport;
+#[cfg(any(feature = "async", feature = "blocking"))]
+pub use crate::rate_limit::RateLimiter;
+#[cfg(feature = "std")]
+pub use crate::retry::RetryPolicy;
+#[cfg(feature = "std")]
+pub use crate::round::{round_at, time_of_round, Round};
+#[cfg(feature = "unchained")]
+pub use crate::unchained::{UnchainedBeacon, UnchainedScheme};
+#[cfg(feature = "g1")]
+pub use crate::unchained_g1::{UnchainedSchemeOnG1, UnchainedSchemeOnG1Rfc9380};
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+pub use crate::watch::Watch;
+use core::fmt;
+#[cfg(feature = "std")]
 use serde::de::DeserializeOwned;
+#[cfg(feature = "std")]
+use std::time::{Duration, SystemTime};
+#[cfg(feature = "std")]
 use thiserror::Error;
 
-pub struct DrandClient<'a, B> {
-    scheme: &'a dyn Scheme<B>,
-    transport: HttpTransport,
-    base_url: &'a str,
-    chain_info: ChainInfo,
+pub const RANGE_CONCURRENCY: usize = 8;
+
+/// The most rounds a range can be fetched in, as every result is held until they all are.
+pub const MAX_RANGE_LEN: u64 = 100_000;
+
+#[cfg(feature = "std")]
+#[derive(Error, Debug)]
+pub enum DrandClientError {
+    #[error("invalid round")]
+    InvalidRound,
+    #[error("invalid beacon: {reason}")]
+    InvalidBeacon { reason: String },
+    #[error("invalid chain info: {reason}")]
+    InvalidChainInfo { reason: String },
+    #[error("not responding")]
+    NotResponding(#[from] HttpError),
+    #[error("unsupported scheme")]
+    UnsupportedScheme,
+    #[error("the client's scheme is {expected}, but the chain's is {found}")]
+    SchemeMismatch { expected: String, found: String },
+    #[error("round not yet available")]
+    RoundNotAvailable,
+    #[error("the clock is set before the chain's genesis")]
+    ClockBeforeGenesis,
+    #[error("invalid client configuration: {reason}")]
+    InvalidConfig { reason: String },
+    /// Fewer relays than required served the same verified beacon. `disagreeing` lists those
+    /// that served other verified randomness than the most agreed on, and `failed` those that
+    /// didn't answer or served a beacon that failed verification.
+    #[error("only {agreeing} relays agreed on the beacon, but {required} were required")]
+    NoQuorum {
+        agreeing: usize,
+        required: usize,
+        disagreeing: Vec<String>,
+        failed: Vec<String>,
+    },
 }
 
-pub fn new_chained_client(base_url: &str) -> Result<DrandClient<ChainedBeacon>, DrandClientError> {
-    return new_client(&ChainedScheme {}, base_url);
+/// A failed request, as [`DrandClientError::NotResponding`], so `?` works on reqwest's results
+/// in code that also uses the client.
+#[cfg(feature = "std")]
+impl From<reqwest::Error> for DrandClientError {
+    fn from(e: reqwest::Error) -> Self {
+        DrandClientError::NotResponding(HttpError::ConnectionFailed(e))
+    }
 }
 
-pub fn new_unchained_client(
-    base_url: &str,
-) -> Result<DrandClient<UnchainedBeacon>, DrandClientError> {
-    return new_client(&UnchainedScheme {}, base_url);
+#[cfg(feature = "std")]
+impl DrandClientError {
+    /// The HTTP status of the relay's response that caused the error, if it responded at all.
+    pub fn http_status(&self) -> Option<u16> {
+        match self {
+            DrandClientError::NotResponding(e) => e.status(),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn parse_chain_info(body: &str) -> Result<ChainInfo, DrandClientError> {
+    let chain_info: ChainInfo =
+        serde_json::from_str(body).map_err(|e| DrandClientError::InvalidChainInfo {
+            reason: e.to_string(),
+        })?;
+    chain_info.validate()?;
+    Ok(chain_info)
 }
 
-pub fn new_client<'a, S: Scheme<B>, B>(
-    scheme: &'a S,
-    base_url: &'a str,
-) -> Result<DrandClient<'a, B>, DrandClientError> {
-    let http_transport = HttpTransport {
-        client: Client::new(),
+/// The fields of the chain info's `metadata` in `body` the client doesn't know and ignores.
+#[cfg(any(feature = "async", feature = "blocking"))]
+pub(crate) fn unknown_metadata(body: &str) -> Vec<String> {
+    let Ok(info) = serde_json::from_str::<serde_json::Value>(body) else {
+        return vec![];
     };
-    let chain_info = fetch_chain_info(&http_transport, base_url)?;
-    let client = DrandClient {
-        transport: http_transport,
-        chain_info,
-        scheme,
-        base_url,
+    match info.get("metadata") {
+        Some(serde_json::Value::Object(metadata)) => metadata
+            .keys()
+            .filter(|field| !BEACON_ID_FIELDS.contains(&field.as_str()))
+            .cloned()
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// The warnings for a client built for `chain_info`, given the metadata fields it didn't know
+/// and whether the caller picked the chain, by its hash or its info, rather than taking the
+/// relay's default.
+#[cfg(any(feature = "async", feature = "blocking"))]
+pub(crate) fn chain_info_warnings(
+    chain_info: &ChainInfo,
+    unknown_metadata: Vec<String>,
+    chain_picked: bool,
+) -> Vec<Warning> {
+    let mut warnings: Vec<Warning> = unknown_metadata
+        .into_iter()
+        .map(|field| Warning::UnknownMetadata { field })
+        .collect();
+    if !chain_picked && chain_info.metadata.beacon_id != "default" {
+        warnings.push(Warning::NonDefaultBeacon {
+            beacon_id: chain_info.metadata.beacon_id.clone(),
+        });
+    }
+    #[cfg(feature = "tracing")]
+    for warning in &warnings {
+        tracing::warn!(%warning, "unexpected chain info");
+    }
+    warnings
+}
+
+/// Fails a client's construction if its scheme can't verify the chain's beacons, rather than
+/// leaving every beacon to fail verification later.
+#[cfg(any(
+    feature = "async",
+    feature = "blocking",
+    all(feature = "std", feature = "chained", feature = "unchained")
+))]
+pub(crate) fn check_scheme<B>(
+    scheme: &dyn Scheme<B>,
+    chain_info: &ChainInfo,
+) -> Result<(), DrandClientError> {
+    if scheme.supports(&chain_info.scheme_id) {
+        Ok(())
+    } else {
+        Err(DrandClientError::SchemeMismatch {
+            expected: scheme.scheme_id().to_string(),
+            found: chain_info.scheme_id.clone(),
+        })
+    }
+}
+
+/// Checks that chain info re-fetched from a relay is what the client was built with, as the
+/// relay could have been repointed at another chain since.
+#[cfg(any(feature = "async", feature = "blocking"))]
+pub(crate) fn check_same_chain(
+    expected: &ChainInfo,
+    fetched: &ChainInfo,
+) -> Result<(), DrandClientError> {
+    let reason = if fetched.chain_hash != expected.chain_hash {
+        "the relay is serving a different chain"
+    } else if fetched != expected {
+        "the relay's chain info has changed"
+    } else {
+        return Ok(());
     };
+    Err(DrandClientError::InvalidChainInfo {
+        reason: reason.to_string(),
+    })
+}
 
-    Ok(client)
+/// Checks that a beacon fetched by round number is that round, as a relay could otherwise answer
+/// with some other validly signed round. Beacons fetched as `latest` can be any round.
+#[cfg(any(feature = "async", feature = "blocking"))]
+pub(crate) fn check_round<B: Beacon>(tag: &str, beacon: B) -> Result<B, DrandClientError> {
+    match tag.parse::<u64>() {
+        Ok(requested) if requested != beacon.round_number() => {
+            Err(DrandClientError::InvalidBeacon {
+                reason: format!(
+                    "requested round {} but the relay returned round {}",
+                    requested,
+                    beacon.round_number()
+                ),
+            })
+        }
+        _ => Ok(beacon),
+    }
 }
 
-#[derive(Error, Debug, PartialEq)]
-pub enum DrandClientError {
-    #[error("invalid round")]
-    InvalidRound,
-    #[error("invalid beacon")]
-    InvalidBeacon,
-    #[error("invalid chain info")]
-    InvalidChainInfo,
-    #[error("not responding")]
-    NotResponding,
+/// The error for a beacon that couldn't be downloaded, telling rounds the relay doesn't have
+/// from relays that aren't working.
+#[cfg(any(feature = "async", feature = "blocking"))]
+pub(crate) fn fetch_error(e: HttpError) -> DrandClientError {
+    if e.is_not_available() {
+        DrandClientError::RoundNotAvailable
+    } else {
+        DrandClientError::NotResponding(e)
+    }
+}
+
+/// Parses a beacon downloaded from `public/{tag}` and checks it's the round that was asked for.
+#[cfg(any(feature = "async", feature = "blocking"))]
+pub(crate) fn parse_beacon<B: DeserializeOwned + Beacon>(
+    tag: &str,
+    res: HttpResponse,
+) -> Result<FetchedBeacon<B>, DrandClientError> {
+    serde_json::from_str(&res.body)
+        .map_err(|e| DrandClientError::InvalidBeacon {
+            reason: e.to_string(),
+        })
+        .and_then(|beacon| check_round(tag, beacon))
+        .map(|beacon| FetchedBeacon {
+            beacon,
+            valid_until: res.valid_until,
+        })
 }
 
-pub fn fetch_chain_info(
-    transport: &HttpTransport,
+#[cfg(feature = "std")]
+pub fn fetch_chain_info<T: Transport>(
+    transport: &T,
     base_url: &str,
 ) -> Result<ChainInfo, DrandClientError> {
     let url = format!("{}/info", base_url);
     match transport.fetch(&url) {
-        Err(_) => Err(DrandClientError::NotResponding),
-        Ok(body) => serde_json::from_str(&body).map_err(|_| InvalidChainInfo),
+        Err(e) => Err(DrandClientError::NotResponding(e)),
+        Ok(body) => parse_chain_info(&body),
     }
 }
 
-impl<'a, B> DrandClient<'a, B>
-where
-    B: DeserializeOwned,
-{
-    pub fn latest_randomness(&self) -> Result<B, DrandClientError> {
-        self.fetch_beacon_tag("latest")
-    }
-
-    pub fn randomness(&self, round_number: u64) -> Result<B, DrandClientError> {
-        if round_number == 0 {
-            return Err(InvalidRound);
-        }
-        self.fetch_beacon_tag(&format!("{}", round_number))
+/// Lists the hashes of every chain served by the relay at `base_url`.
+#[cfg(feature = "std")]
+pub fn list_chains<T: Transport>(
+    transport: &T,
+    base_url: &str,
+) -> Result<Vec<String>, DrandClientError> {
+    let url = format!("{}/chains", base_url);
+    match transport.fetch(&url) {
+        Err(e) => Err(DrandClientError::NotResponding(e)),
+        Ok(body) => parse_chains(&body),
     }
+}
 
-    fn fetch_beacon_tag(&self, tag: &str) -> Result<B, DrandClientError> {
-        let url = format!("{}/public/{}", self.base_url, tag);
-        match self.transport.fetch(&url) {
-            Err(_) => Err(DrandClientError::NotResponding),
-
-            Ok(body) => match serde_json::from_str(&body) {
-                Ok(json) => self
-                    .scheme
-                    .verify(&self.chain_info, json)
-                    .map_err(|_| DrandClientError::InvalidBeacon),
-                Err(_) => Err(DrandClientError::InvalidBeacon),
-            },
-        }
-    }
+#[cfg(feature = "std")]
+pub(crate) fn parse_chains(body: &str) -> Result<Vec<String>, DrandClientError> {
+    serde_json::from_str(body).map_err(|e| DrandClientError::InvalidChainInfo {
+        reason: e.to_string(),
+    })
 }
 
-#[derive(Error, Debug)]
+#[derive(Debug)]
 pub enum SchemeError {
-    #[error("invalid beacon")]
     InvalidBeacon,
-    #[error("invalid scheme")]
     InvalidScheme,
-    #[error("invalid chain info")]
     InvalidChainInfo,
+    /// The beacon's signature isn't a valid point, for the reason given.
+    InvalidSignature(PointError),
+    /// The chain's public key isn't a valid point, for the reason given.
+    InvalidPublicKey(PointError),
 }
 
-pub trait Scheme<B> {
+impl fmt::Display for SchemeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemeError::InvalidBeacon => f.write_str("invalid beacon"),
+            SchemeError::InvalidScheme => f.write_str("invalid scheme"),
+            SchemeError::InvalidChainInfo => f.write_str("invalid chain info"),
+            SchemeError::InvalidSignature(e) => write!(f, "invalid signature: {}", e),
+            SchemeError::InvalidPublicKey(e) => write!(f, "invalid public key: {}", e),
+        }
+    }
+}
+
+// implemented by hand rather than by `thiserror`, which needs `std`
+#[cfg(feature = "std")]
+impl std::error::Error for SchemeError {}
+
+pub trait Scheme<B>: Send + Sync {
     fn supports(&self, scheme_id: &str) -> bool;
     fn verify(&self, info: &ChainInfo, beacon: B) -> Result<B, SchemeError>;
+
+    /// The scheme id this scheme verifies, for describing a mismatch with the chain's.
+    fn scheme_id(&self) -> &str {
+        "unknown"
+    }
+}
+
+/// Lets clients own a reference to a scheme, e.g. `&ChainedScheme {}`, which is `'static`.
+impl<B, S: Scheme<B> + ?Sized> Scheme<B> for &S {
+    fn supports(&self, scheme_id: &str) -> bool {
+        (**self).supports(scheme_id)
+    }
+
+    fn verify(&self, info: &ChainInfo, beacon: B) -> Result<B, SchemeError> {
+        (**self).verify(info, beacon)
+    }
+
+    fn scheme_id(&self) -> &str {
+        (**self).scheme_id()
+    }
+}
+
+/// Verifies a beacon obtained out-of-band against known chain info, without any network access.
+pub fn verify_beacon<S: Scheme<B>, B>(
+    scheme: &S,
+    chain_info: &ChainInfo,
+    beacon: B,
+) -> Result<B, SchemeError> {
+    scheme.verify(chain_info, beacon)
+}
+
+/// Reads a beacon in the JSON shape relays serve it in from `reader`, e.g. a file saved earlier,
+/// for verifying with [`verify_beacon`] without any network access. It isn't verified here.
+#[cfg(feature = "std")]
+pub fn beacon_from_reader<R: std::io::Read, B: DeserializeOwned>(
+    reader: R,
+) -> Result<B, DrandClientError> {
+    serde_json::from_reader(reader).map_err(|e| DrandClientError::InvalidBeacon {
+        reason: e.to_string(),
+    })
+}
+
+/// Verifies a beacon against chain info, both in the JSON shape relays serve them in, with the
+/// scheme for `scheme_id`, for bindings to other languages that can't name the crate's types.
+/// `Ok` means the beacon is valid; otherwise the error says why it isn't.
+#[cfg(all(feature = "std", feature = "chained", feature = "unchained"))]
+pub fn verify_json(
+    scheme_id: &str,
+    chain_info_json: &str,
+    beacon_json: &str,
+) -> Result<(), DrandClientError> {
+    let scheme: AnyScheme = scheme_id
+        .parse()
+        .map_err(|_| DrandClientError::UnsupportedScheme)?;
+    let chain_info = parse_chain_info(chain_info_json)?;
+    check_scheme(&scheme, &chain_info)?;
+    let beacon: AnyBeacon =
+        serde_json::from_str(beacon_json).map_err(|e| DrandClientError::InvalidBeacon {
+            reason: e.to_string(),
+        })?;
+    match scheme.verify(&chain_info, beacon) {
+        Ok(_) => Ok(()),
+        Err(e) => Err(DrandClientError::InvalidBeacon {
+            reason: e.to_string(),
+        }),
+    }
+}
+
+pub const RANDOMNESS_LEN: usize = 32;
+
+pub trait Beacon {
+    fn round_number(&self) -> u64;
+
+    /// The beacon's randomness as a fixed-size array, e.g. for seeding an RNG.
+    /// Errors if the beacon doesn't carry exactly [RANDOMNESS_LEN] bytes of randomness.
+    fn randomness_bytes(&self) -> Result<[u8; RANDOMNESS_LEN], SchemeError>;
+
+    fn signature_bytes(&self) -> &[u8];
+}
+
+/// A verified beacon, and when the relay that served it says it goes stale, if it said so.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct FetchedBeacon<B> {
+    pub beacon: B,
+    pub valid_until: Option<SystemTime>,
+}
+
+/// A beacon enough relays agreed on, from `DrandClient::randomness_quorum`: the base URLs of
+/// those that served it, of those that served other verified randomness, and of those that
+/// failed to answer or served a beacon that failed verification.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Quorum<B> {
+    pub beacon: B,
+    pub agreeing: Vec<String>,
+    pub disagreeing: Vec<String>,
+    pub failed: Vec<String>,
+}
+
+/// Something unexpected about the chain a client was built for that isn't reason enough to fail
+/// building it, but could be a sign it's not the chain that was meant, from `warnings`.
+#[cfg(any(feature = "async", feature = "blocking"))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Warning {
+    /// The chain info's metadata has a field the client doesn't know, e.g. from a newer drand.
+    UnknownMetadata { field: String },
+    /// The relay's chain is a beacon other than `default`, though the client didn't pick it.
+    NonDefaultBeacon { beacon_id: String },
+}
+
+#[cfg(any(feature = "async", feature = "blocking"))]
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Warning::UnknownMetadata { field } => {
+                write!(f, "the chain info has unknown metadata field {:?}", field)
+            }
+            Warning::NonDefaultBeacon { beacon_id } => write!(
+                f,
+                "the relay's chain is beacon {:?}, not the default, but no chain hash was given",
+                beacon_id
+            ),
+        }
+    }
+}
+
+/// A verified beacon along with the chain it's from, for passing on to code that needs both, e.g.
+/// `tlock::decrypt`.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct BeaconWithContext<'c, B> {
+    pub beacon: B,
+    pub chain_info: &'c ChainInfo,
+}
+
+/// A verified beacon, along with where the time went fetching it: downloading it, parsing it
+/// (hex decoding included), and verifying its signature. For profiling; see the clients'
+/// `randomness_timed`.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerifiedBeacon<B> {
+    pub beacon: B,
+    pub fetch_time: Duration,
+    pub parse_time: Duration,
+    pub verify_time: Duration,
 }
 
 #[cfg(test)]
 mod test {
-    use crate::DrandClientError::InvalidRound;
-    use crate::{new_chained_client, new_unchained_client, DrandClientError};
+    use crate::fixtures;
+    use crate::http::HttpTransport;
+    use crate::{
+        beacon_from_reader, fetch_chain_info, list_chains, parse_chains, unknown_metadata,
+        verify_beacon, verify_json, ChainInfo, DrandClientError, HttpError, MockTransport,
+        UnchainedBeacon, UnchainedScheme, BEACON_ID_FIELDS,
+    };
+    use std::error::Error;
 
     #[test]
-    fn request_chained_randomness_success() -> Result<(), DrandClientError> {
-        let chained_url = "https://api.drand.sh";
-        let client = new_chained_client(chained_url)?;
-        let randomness = client.latest_randomness()?;
-        assert!(randomness.round_number > 0);
-        return Ok(());
+    fn every_casing_of_the_beacon_id_is_known_metadata() {
+        for field in BEACON_ID_FIELDS {
+            let mut json: serde_json::Value = serde_json::from_str(&fixtures::chain_info_json(
+                &fixtures::chain_info("pedersen-bls-unchained"),
+            ))
+            .unwrap();
+            json["metadata"] = serde_json::json!({ field: "evmnet" });
+            let info: ChainInfo = serde_json::from_value(json.clone()).unwrap();
+            assert_eq!(info.metadata.beacon_id, "evmnet");
+            assert!(unknown_metadata(&json.to_string()).is_empty());
+        }
     }
 
     #[test]
-    fn request_unchained_randomness_success() -> Result<(), DrandClientError> {
-        let unchained_url = "https://pl-eu.testnet.drand.sh/7672797f548f3f4748ac4bf3352fc6c6b6468c9ad40ad456a397545c6e2df5bf";
-        let client = new_unchained_client(unchained_url)?;
-        let randomness = client.latest_randomness()?;
-        assert!(randomness.round_number > 0);
-        return Ok(());
+    fn reqwest_errors_keep_their_source_chain() {
+        let refused = reqwest::blocking::get("http://127.0.0.1:1").unwrap_err();
+        let e = DrandClientError::from(refused);
+        assert!(matches!(
+            e,
+            DrandClientError::NotResponding(HttpError::ConnectionFailed(_))
+        ));
+        let http_error = e.source().unwrap();
+        assert_eq!(http_error.to_string(), "connection failed");
+        assert!(http_error
+            .source()
+            .unwrap()
+            .downcast_ref::<reqwest::Error>()
+            .is_some());
     }
 
     #[test]
-    fn request_unchained_randomness_wrong_client_error() -> Result<(), DrandClientError> {
-        let unchained_url = "https://pl-eu.testnet.drand.sh/7672797f548f3f4748ac4bf3352fc6c6b6468c9ad40ad456a397545c6e2df5bf";
-        let client = new_chained_client(unchained_url)?;
-        let result = client.latest_randomness();
-        assert!(result.is_err());
-        return Ok(());
+    fn request_chains_success() -> Result<(), DrandClientError> {
+        let transport = HttpTransport::default();
+        let chains = list_chains(&transport, "https://api.drand.sh")?;
+        assert!(chains.contains(
+            &"8990e7a9aaed2ffed73dbd7092123d6f289930540d7651336225dc172e51b2ce".to_string()
+        ));
+        Ok(())
     }
 
     #[test]
-    fn request_chained_randomness_wrong_client_error() -> Result<(), DrandClientError> {
-        let chained_url = "https://api.drand.sh";
-        let client = new_unchained_client(chained_url)?;
-        let result = client.latest_randomness();
-        assert!(result.is_err());
-        return Ok(());
+    fn chains_are_parsed_from_json_array() {
+        let chains = parse_chains(r#"["8990e7a9", "52db9ba7"]"#).unwrap();
+        assert_eq!(chains, vec!["8990e7a9", "52db9ba7"]);
+        assert!(parse_chains("<html>").is_err());
     }
 
     #[test]
-    fn request_genesis_returns_error() -> Result<(), DrandClientError> {
-        let chained_url = "https://api.drand.sh";
-        let client = new_chained_client(chained_url);
-        let result = client?.randomness(0);
-        assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), InvalidRound);
-        return Ok(());
+    fn fetched_chain_info_with_zero_period_is_rejected() {
+        let mut info = fixtures::chain_info("pedersen-bls-unchained");
+        info.period_seconds = 0;
+        let transport =
+            MockTransport::new().with_body("http://relay/info", &fixtures::chain_info_json(&info));
+        assert!(matches!(
+            fetch_chain_info(&transport, "http://relay"),
+            Err(DrandClientError::InvalidChainInfo { .. })
+        ));
+    }
+
+    #[test]
+    fn beacon_loaded_from_json_verifies_offline() {
+        let info_json = serde_json::to_string(&serde_json::json!({
+            "public_key": hex::encode(fixtures::chain_info("pedersen-bls-unchained").public_key),
+            "period": 30,
+            "genesis_time": 1595431050,
+            "hash": "01",
+            "groupHash": "02",
+            "schemeID": "pedersen-bls-unchained",
+            "metadata": { "beaconID": "default" },
+        }))
+        .unwrap();
+        let beacon = fixtures::unchained_beacon(7);
+        let beacon_json = serde_json::to_string(&serde_json::json!({
+            "round": beacon.round_number,
+            "randomness": hex::encode(&beacon.randomness),
+            "signature": hex::encode(&beacon.signature),
+        }))
+        .unwrap();
+
+        let info: ChainInfo = serde_json::from_str(&info_json).unwrap();
+        let beacon: UnchainedBeacon = serde_json::from_str(&beacon_json).unwrap();
+        let verified = verify_beacon(&UnchainedScheme {}, &info, beacon).unwrap();
+        assert_eq!(verified.round_number, 7);
+    }
+
+    #[test]
+    fn beacons_read_from_a_file_verify_offline() {
+        let info = fixtures::chain_info("pedersen-bls-unchained");
+        let beacon = fixtures::unchained_beacon(7);
+        let path = std::env::temp_dir().join(format!("drand-beacon-{}.json", std::process::id()));
+        std::fs::write(&path, fixtures::unchained_beacon_json(&beacon)).unwrap();
+        let read: Result<UnchainedBeacon, _> =
+            beacon_from_reader(std::fs::File::open(&path).unwrap());
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            verify_beacon(&UnchainedScheme {}, &info, read.unwrap()).unwrap(),
+            beacon
+        );
+
+        let truncated: Result<UnchainedBeacon, _> = beacon_from_reader(&b"{\"round\": 7"[..]);
+        assert!(matches!(
+            truncated,
+            Err(DrandClientError::InvalidBeacon { .. })
+        ));
+    }
+
+    #[test]
+    fn json_beacons_verify_against_json_chain_info() {
+        let info = fixtures::chain_info_json(&fixtures::chain_info("pedersen-bls-unchained"));
+        let mut beacon = fixtures::unchained_beacon(7);
+        let json = fixtures::unchained_beacon_json(&beacon);
+        assert!(verify_json("pedersen-bls-unchained", &info, &json).is_ok());
+
+        beacon.round_number = 8;
+        let tampered = fixtures::unchained_beacon_json(&beacon);
+        assert!(matches!(
+            verify_json("pedersen-bls-unchained", &info, &tampered),
+            Err(DrandClientError::InvalidBeacon { .. })
+        ));
+        assert!(matches!(
+            verify_json("pedersen-bls-chained", &info, &json),
+            Err(DrandClientError::SchemeMismatch { .. })
+        ));
+        assert!(matches!(
+            verify_json("not-a-scheme", &info, &json),
+            Err(DrandClientError::UnsupportedScheme)
+        ));
+        assert!(matches!(
+            verify_json("pedersen-bls-unchained", "{}", &json),
+            Err(DrandClientError::InvalidChainInfo { .. })
+        ));
+    }
+
+    #[test]
+    fn tampered_beacon_fails_offline_verification() {
+        let info = fixtures::chain_info("pedersen-bls-unchained");
+        let mut beacon = fixtures::unchained_beacon(7);
+        beacon.round_number = 8;
+        assert!(verify_beacon(&UnchainedScheme {}, &info, beacon).is_err());
     }
 }
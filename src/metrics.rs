@@ -0,0 +1,28 @@
+use crate::DrandClientError;
+use std::time::Duration;
+
+/// Callbacks the clients invoke as they fetch and verify beacons, for wiring up counters and
+/// histograms in prometheus, `metrics` or similar without this crate depending on them.
+/// Every method defaults to doing nothing, so implementations only override what they record.
+pub trait Metrics: Send + Sync {
+    /// A beacon was fetched from a relay and verified.
+    fn on_fetch_success(&self) {}
+
+    /// A beacon couldn't be fetched, or a relay served one that failed verification.
+    fn on_fetch_error(&self, _error: &DrandClientError) {}
+
+    /// How long verifying a fetched beacon took, whether or not it verified.
+    fn on_verify_duration(&self, _duration: Duration) {}
+
+    /// A round was served from the client's cache without a request to the relay.
+    fn on_cache_hit(&self) {}
+
+    /// A round wasn't in the client's cache, so it was fetched from the relay.
+    fn on_cache_miss(&self) {}
+}
+
+/// The metrics clients report to unless they were constructed with others: records nothing.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoopMetrics;
+
+impl Metrics for NoopMetrics {}
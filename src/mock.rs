@@ -0,0 +1,117 @@
+use crate::http::{AsyncTransport, HttpError, HttpResponse, Transport};
+use reqwest::StatusCode;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// A transport serving canned responses, for testing code built on the clients without a relay.
+/// URLs without a canned response are answered with a 404. Clones share the request log.
+#[derive(Default, Clone)]
+pub struct MockTransport {
+    responses: HashMap<String, MockResponse>,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+#[derive(Clone)]
+struct MockResponse {
+    status: StatusCode,
+    content_type: Option<String>,
+    body: String,
+}
+
+impl MockTransport {
+    pub fn new() -> Self {
+        MockTransport::default()
+    }
+
+    /// Answers requests for `url` with a `200 OK` and `body`.
+    pub fn with_body(self, url: &str, body: &str) -> Self {
+        self.with_response(url, StatusCode::OK, "application/json", body)
+    }
+
+    /// Answers requests for `url` with an empty response of the given status.
+    pub fn with_status(self, url: &str, status: StatusCode) -> Self {
+        self.with_response(url, status, "text/plain", "")
+    }
+
+    /// Answers requests for `url` with the given status, `Content-Type` and body.
+    pub fn with_response(
+        mut self,
+        url: &str,
+        status: StatusCode,
+        content_type: &str,
+        body: &str,
+    ) -> Self {
+        self.responses.insert(
+            url.to_string(),
+            MockResponse {
+                status,
+                content_type: Some(content_type.to_string()),
+                body: body.to_string(),
+            },
+        );
+        self
+    }
+
+    /// Every URL requested so far, in order.
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().expect("mock lock poisoned").clone()
+    }
+}
+
+impl Transport for MockTransport {
+    fn fetch(&self, url: &str) -> Result<String, HttpError> {
+        Transport::fetch_response(self, url).map(|res| res.body)
+    }
+
+    fn fetch_response(&self, url: &str) -> Result<HttpResponse, HttpError> {
+        self.requests
+            .lock()
+            .expect("mock lock poisoned")
+            .push(url.to_string());
+        match self.responses.get(url) {
+            Some(res) if res.status == StatusCode::OK => Ok(HttpResponse {
+                body: res.body.clone(),
+                valid_until: None,
+                content_type: res.content_type.clone(),
+                last_modified: None,
+                etag: None,
+            }),
+            Some(res) => Err(HttpError::from_status(res.status)),
+            None => Err(HttpError::NotFound),
+        }
+    }
+}
+
+impl AsyncTransport for MockTransport {
+    async fn fetch(&self, url: &str) -> Result<String, HttpError> {
+        Transport::fetch(self, url)
+    }
+
+    async fn fetch_response(&self, url: &str) -> Result<HttpResponse, HttpError> {
+        Transport::fetch_response(self, url)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::http::{HttpError, Transport};
+    use crate::mock::MockTransport;
+    use reqwest::StatusCode;
+
+    #[test]
+    fn unknown_urls_are_not_found() {
+        let transport = MockTransport::new()
+            .with_body("http://relay/info", "{}")
+            .with_status("http://relay/public/1", StatusCode::BAD_GATEWAY);
+        assert_eq!(transport.fetch("http://relay/info").unwrap(), "{}");
+        assert!(matches!(
+            transport.fetch("http://relay/public/1"),
+            Err(HttpError::ServerError(502))
+        ));
+        assert!(matches!(
+            transport.fetch("http://relay/public/2"),
+            Err(HttpError::NotFound)
+        ));
+        assert_eq!(transport.requests().len(), 3);
+    }
+}
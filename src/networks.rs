@@ -0,0 +1,52 @@
+//! The public drand networks, for connecting without copying their chain hashes around.
+//! `DrandClient::mainnet()` and friends use these, and check the fetched chain info against them.
+
+/// Where a network is served and how its beacons are signed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Network {
+    pub base_url: &'static str,
+    pub chain_hash: &'static str,
+    pub scheme_id: &'static str,
+}
+
+/// The League of Entropy's original chained network, emitting a beacon every 30 seconds.
+pub const MAINNET: Network = Network {
+    base_url: "https://api.drand.sh",
+    chain_hash: "8990e7a9aaed2ffed73dbd7092123d6f289930540d7651336225dc172e51b2ce",
+    scheme_id: "pedersen-bls-chained",
+};
+
+/// The League of Entropy's unchained network on G1, emitting a beacon every 3 seconds and
+/// supporting timelock encryption.
+pub const QUICKNET: Network = Network {
+    base_url: "https://api.drand.sh",
+    chain_hash: "52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971",
+    scheme_id: "bls-unchained-g1-rfc9380",
+};
+
+/// The unchained testnet, emitting a beacon every 3 seconds.
+pub const TESTNET_UNCHAINED: Network = Network {
+    base_url: "https://pl-eu.testnet.drand.sh",
+    chain_hash: "7672797f548f3f4748ac4bf3352fc6c6b6468c9ad40ad456a397545c6e2df5bf",
+    scheme_id: "pedersen-bls-unchained",
+};
+
+#[cfg(test)]
+mod test {
+    use crate::networks::{MAINNET, QUICKNET, TESTNET_UNCHAINED};
+    use crate::{ChainedScheme, Scheme, UnchainedScheme, UnchainedSchemeOnG1Rfc9380};
+
+    #[test]
+    fn chain_hashes_are_sha256_digests() {
+        for network in [MAINNET, QUICKNET, TESTNET_UNCHAINED] {
+            assert_eq!(hex::decode(network.chain_hash).unwrap().len(), 32);
+        }
+    }
+
+    #[test]
+    fn schemes_match_the_networks() {
+        assert!(ChainedScheme {}.supports(MAINNET.scheme_id));
+        assert!(UnchainedSchemeOnG1Rfc9380 {}.supports(QUICKNET.scheme_id));
+        assert!(UnchainedScheme {}.supports(TESTNET_UNCHAINED.scheme_id));
+    }
+}
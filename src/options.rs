@@ -0,0 +1,199 @@
+use crate::bls::compressed_point;
+use crate::chain_info::ChainInfo;
+use crate::clock::{Clock, SystemClock};
+use crate::endpoints::{ApiVersion, PathTemplates};
+use crate::http::{
+    DEFAULT_MAX_BODY_SIZE, DEFAULT_POOL_MAX_IDLE_PER_HOST, DEFAULT_TCP_KEEPALIVE, DEFAULT_TIMEOUT,
+};
+use crate::metrics::{Metrics, NoopMetrics};
+use crate::{DrandClientError, RateLimiter, RetryPolicy, Scheme};
+use std::net::{IpAddr, SocketAddr};
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use std::time::Duration;
+use subtle::ConstantTimeEq;
+
+/// The scheme a client verifies beacons with: the one it was given, or for auto clients whichever
+/// supports the scheme id in the chain info.
+pub(crate) enum SchemeChoice<B> {
+    Fixed(Arc<dyn Scheme<B>>),
+    // only auto clients choose by scheme id
+    #[cfg_attr(not(all(feature = "chained", feature = "unchained")), allow(dead_code))]
+    BySchemeId(fn(&str) -> Option<Arc<dyn Scheme<B>>>),
+}
+
+impl<B> SchemeChoice<B> {
+    pub(crate) fn for_chain(
+        &self,
+        chain_info: &ChainInfo,
+    ) -> Result<Arc<dyn Scheme<B>>, DrandClientError> {
+        match self {
+            SchemeChoice::Fixed(scheme) => Ok(scheme.clone()),
+            SchemeChoice::BySchemeId(scheme_for) => {
+                scheme_for(&chain_info.scheme_id).ok_or(DrandClientError::UnsupportedScheme)
+            }
+        }
+    }
+}
+
+pub(crate) struct ClientOptions {
+    pub(crate) timeout: Duration,
+    pub(crate) connect_timeout: Option<Duration>,
+    pub(crate) pool_max_idle_per_host: usize,
+    pub(crate) tcp_keepalive: Option<Duration>,
+    pub(crate) local_address: Option<IpAddr>,
+    pub(crate) resolve: Vec<(String, SocketAddr)>,
+    // PEM encoded, parsed when the client is built
+    pub(crate) root_certificates: Vec<Vec<u8>>,
+    #[cfg(feature = "danger-accept-invalid-certs")]
+    pub(crate) danger_accept_invalid_certs: bool,
+    pub(crate) retry_policy: RetryPolicy,
+    pub(crate) rate_limiter: Option<Arc<RateLimiter>>,
+    pub(crate) max_body_size: usize,
+    pub(crate) expected_chain_hash: Option<Vec<u8>>,
+    pub(crate) expected_public_key: Option<Vec<u8>>,
+    pub(crate) chain_info: Option<ChainInfo>,
+    pub(crate) chain_hash_path: Option<String>,
+    // `None` detects it while fetching the chain info
+    pub(crate) api_version: Option<ApiVersion>,
+    pub(crate) path_templates: Option<PathTemplates>,
+    pub(crate) cache_capacity: Option<NonZeroUsize>,
+    pub(crate) conditional_latest: bool,
+    pub(crate) future_round_tolerance: Option<Duration>,
+    pub(crate) metrics: Arc<dyn Metrics>,
+    pub(crate) clock: Arc<dyn Clock>,
+}
+
+impl ClientOptions {
+    pub(crate) fn validate(&self, chain_info: &ChainInfo) -> Result<(), DrandClientError> {
+        if let Some(expected_chain_hash) = &self.expected_chain_hash {
+            // in constant time, like the other security-sensitive comparisons
+            let matches = chain_info.chain_hash.ct_eq(expected_chain_hash)
+                & chain_info.compute_hash().ct_eq(expected_chain_hash);
+            if !bool::from(matches) {
+                return Err(DrandClientError::InvalidChainInfo {
+                    reason: "chain hash does not match the expected chain hash".to_string(),
+                });
+            }
+        }
+        if let Some(expected_public_key) = &self.expected_public_key {
+            // in either encoding, as tooling disagrees on which to pin keys in
+            let public_key = compressed_point(&chain_info.public_key);
+            if !bool::from(public_key.ct_eq(&compressed_point(expected_public_key))) {
+                return Err(DrandClientError::InvalidChainInfo {
+                    reason: "public key does not match the pinned public key".to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for ClientOptions {
+    fn default() -> Self {
+        ClientOptions {
+            timeout: DEFAULT_TIMEOUT,
+            connect_timeout: None,
+            pool_max_idle_per_host: DEFAULT_POOL_MAX_IDLE_PER_HOST,
+            tcp_keepalive: Some(DEFAULT_TCP_KEEPALIVE),
+            local_address: None,
+            resolve: vec![],
+            root_certificates: vec![],
+            #[cfg(feature = "danger-accept-invalid-certs")]
+            danger_accept_invalid_certs: false,
+            retry_policy: RetryPolicy::none(),
+            rate_limiter: None,
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+            expected_chain_hash: None,
+            expected_public_key: None,
+            chain_info: None,
+            chain_hash_path: None,
+            api_version: Some(ApiVersion::V1),
+            path_templates: None,
+            cache_capacity: None,
+            conditional_latest: false,
+            future_round_tolerance: None,
+            metrics: Arc::new(NoopMetrics),
+            clock: Arc::new(SystemClock),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::bls::{reencode_point, PointEncoding};
+    use crate::fixtures;
+    use crate::options::ClientOptions;
+    use crate::ChainInfo;
+
+    #[test]
+    fn matching_chain_hash_is_accepted() {
+        let mut info = fixtures::chain_info("pedersen-bls-chained");
+        info.chain_hash = info.compute_hash();
+        let options = ClientOptions {
+            expected_chain_hash: Some(info.chain_hash.clone()),
+            ..ClientOptions::default()
+        };
+        assert!(options.validate(&info).is_ok());
+    }
+
+    #[test]
+    fn mismatched_chain_hash_is_rejected() {
+        let mut info = fixtures::chain_info("pedersen-bls-chained");
+        info.chain_hash = info.compute_hash();
+        let options = ClientOptions {
+            expected_chain_hash: Some(vec![0; 32]),
+            ..ClientOptions::default()
+        };
+        assert!(options.validate(&info).is_err());
+    }
+
+    #[test]
+    fn pinned_public_key_must_match() {
+        let info = fixtures::chain_info("pedersen-bls-chained");
+        let pinned = ClientOptions {
+            expected_public_key: Some(info.public_key.clone()),
+            ..ClientOptions::default()
+        };
+        assert!(pinned.validate(&info).is_ok());
+
+        let mut other_key = info.public_key.clone();
+        other_key[1] ^= 0x01;
+        let mismatched = ClientOptions {
+            expected_public_key: Some(other_key),
+            ..ClientOptions::default()
+        };
+        assert!(mismatched.validate(&info).is_err());
+    }
+
+    #[test]
+    fn uncompressed_public_key_pin_matches() {
+        let info = fixtures::chain_info("pedersen-bls-chained");
+        let uncompressed = reencode_point(&info.public_key, PointEncoding::Uncompressed).unwrap();
+        let pinned = ClientOptions {
+            expected_public_key: Some(uncompressed.clone()),
+            ..ClientOptions::default()
+        };
+        assert!(pinned.validate(&info).is_ok());
+
+        let served_uncompressed = ChainInfo {
+            public_key: uncompressed,
+            ..info.clone()
+        };
+        let pinned = ClientOptions {
+            expected_public_key: Some(info.public_key.clone()),
+            ..ClientOptions::default()
+        };
+        assert!(pinned.validate(&served_uncompressed).is_ok());
+    }
+
+    #[test]
+    fn chain_hash_inconsistent_with_parameters_is_rejected() {
+        let info = fixtures::chain_info("pedersen-bls-chained");
+        let options = ClientOptions {
+            expected_chain_hash: Some(info.chain_hash.clone()),
+            ..ClientOptions::default()
+        };
+        assert!(options.validate(&info).is_err());
+    }
+}
@@ -0,0 +1,118 @@
+use crate::timer;
+use std::num::NonZeroU32;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+/// A token bucket capping how many requests a transport sends per second, so backfills stay
+/// within a public relay's budget rather than getting rate limited. Requests beyond the budget
+/// wait their turn rather than failing. Transports' clones share their limiter.
+#[derive(Debug)]
+pub struct RateLimiter {
+    per_second: f64,
+    burst: f64,
+    bucket: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    // negative when requests are already waiting for tokens
+    tokens: f64,
+    updated: SystemTime,
+}
+
+impl RateLimiter {
+    /// Allows `requests` per second, up to a second's worth of them at once.
+    pub fn per_second(requests: NonZeroU32) -> Self {
+        let per_second = f64::from(requests.get());
+        RateLimiter {
+            per_second,
+            burst: per_second,
+            bucket: Mutex::new(Bucket {
+                tokens: per_second,
+                updated: timer::now(),
+            }),
+        }
+    }
+
+    /// Allows at most `burst` requests at once after an idle spell, rather than a second's worth.
+    pub fn with_burst(self, burst: NonZeroU32) -> Self {
+        let burst = f64::from(burst.get());
+        let mut bucket = self
+            .bucket
+            .into_inner()
+            .expect("rate limiter lock poisoned");
+        bucket.tokens = bucket.tokens.min(burst);
+        RateLimiter {
+            per_second: self.per_second,
+            burst,
+            bucket: Mutex::new(bucket),
+        }
+    }
+
+    /// Blocks until a request may be sent.
+    #[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+    pub(crate) fn wait(&self) {
+        let delay = self.reserve(timer::now());
+        if !delay.is_zero() {
+            std::thread::sleep(delay);
+        }
+    }
+
+    /// Waits until a request may be sent, without blocking the executor.
+    #[cfg(feature = "async")]
+    pub(crate) async fn wait_async(&self) {
+        let delay = self.reserve(timer::now());
+        if !delay.is_zero() {
+            timer::sleep(delay).await;
+        }
+    }
+
+    /// Takes a token, returning how long until it's available.
+    fn reserve(&self, now: SystemTime) -> Duration {
+        let mut bucket = self.bucket.lock().expect("rate limiter lock poisoned");
+        let elapsed = now.duration_since(bucket.updated).unwrap_or_default();
+        bucket.tokens = (bucket.tokens + elapsed.as_secs_f64() * self.per_second).min(self.burst);
+        bucket.updated = now;
+        bucket.tokens -= 1.0;
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / self.per_second)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::rate_limit::RateLimiter;
+    use std::num::NonZeroU32;
+    use std::time::{Duration, SystemTime};
+
+    fn limiter(per_second: u32, burst: u32) -> (RateLimiter, SystemTime) {
+        let limiter = RateLimiter::per_second(NonZeroU32::new(per_second).unwrap())
+            .with_burst(NonZeroU32::new(burst).unwrap());
+        let start = limiter.bucket.lock().unwrap().updated;
+        (limiter, start)
+    }
+
+    #[test]
+    fn requests_beyond_the_burst_wait_their_turn() {
+        let (limiter, start) = limiter(10, 2);
+        assert_eq!(limiter.reserve(start), Duration::ZERO);
+        assert_eq!(limiter.reserve(start), Duration::ZERO);
+        assert_eq!(limiter.reserve(start), Duration::from_millis(100));
+        assert_eq!(limiter.reserve(start), Duration::from_millis(200));
+    }
+
+    #[test]
+    fn idle_time_refills_the_bucket_up_to_the_burst() {
+        let (limiter, start) = limiter(10, 2);
+        for _ in 0..2 {
+            limiter.reserve(start);
+        }
+        let later = start + Duration::from_secs(60);
+        assert_eq!(limiter.reserve(later), Duration::ZERO);
+        assert_eq!(limiter.reserve(later), Duration::ZERO);
+        assert_eq!(limiter.reserve(later), Duration::from_millis(100));
+    }
+}
@@ -0,0 +1,163 @@
+#[cfg(any(feature = "async", feature = "blocking"))]
+use crate::http::HttpError;
+#[cfg(feature = "async")]
+use crate::timer;
+#[cfg(feature = "async")]
+use std::future::Future;
+use std::time::Duration;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub multiplier: f64,
+    pub max_delay: Duration,
+}
+
+impl RetryPolicy {
+    pub fn none() -> Self {
+        RetryPolicy {
+            max_attempts: 1,
+            base_delay: Duration::ZERO,
+            multiplier: 1.0,
+            max_delay: Duration::ZERO,
+        }
+    }
+
+    pub fn delay_for_attempt(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1) as i32;
+        let delay = self.base_delay.as_secs_f64() * self.multiplier.powi(exponent);
+        if !delay.is_finite() || delay >= self.max_delay.as_secs_f64() {
+            self.max_delay
+        } else if delay <= 0.0 {
+            // a negative multiplier makes every other delay negative, which is no delay at all
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(delay)
+        }
+    }
+
+    #[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+    pub(crate) fn retry<T, F>(&self, mut operation: F) -> Result<T, HttpError>
+    where
+        F: FnMut() -> Result<T, HttpError>,
+    {
+        let mut attempt = 1;
+        loop {
+            match operation() {
+                Err(e) if e.is_transient() && attempt < self.max_attempts => {
+                    std::thread::sleep(self.delay_for_attempt(attempt));
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    #[cfg(feature = "async")]
+    pub(crate) async fn retry_async<T, F, Fut>(&self, mut operation: F) -> Result<T, HttpError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, HttpError>>,
+    {
+        let mut attempt = 1;
+        loop {
+            match operation().await {
+                Err(e) if e.is_transient() && attempt < self.max_attempts => {
+                    timer::sleep(self.delay_for_attempt(attempt)).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(250),
+            multiplier: 2.0,
+            max_delay: Duration::from_secs(5),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::http::HttpError;
+    use crate::retry::RetryPolicy;
+    use std::time::Duration;
+
+    fn instant_policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            base_delay: Duration::ZERO,
+            multiplier: 2.0,
+            max_delay: Duration::ZERO,
+        }
+    }
+
+    #[test]
+    fn delay_grows_exponentially_up_to_max() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            base_delay: Duration::from_millis(100),
+            multiplier: 2.0,
+            max_delay: Duration::from_millis(500),
+        };
+        assert_eq!(policy.delay_for_attempt(1), Duration::from_millis(100));
+        assert_eq!(policy.delay_for_attempt(2), Duration::from_millis(200));
+        assert_eq!(policy.delay_for_attempt(3), Duration::from_millis(400));
+        assert_eq!(policy.delay_for_attempt(4), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn negative_delays_are_no_delay() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            base_delay: Duration::from_millis(100),
+            multiplier: -2.0,
+            max_delay: Duration::from_millis(500),
+        };
+        assert_eq!(policy.delay_for_attempt(2), Duration::ZERO);
+        assert_eq!(policy.delay_for_attempt(3), Duration::from_millis(400));
+    }
+
+    #[test]
+    fn transient_errors_are_retried_until_success() {
+        let mut calls = 0;
+        let result = instant_policy(3).retry(|| {
+            calls += 1;
+            if calls < 3 {
+                Err(HttpError::ServerError(500))
+            } else {
+                Ok(calls)
+            }
+        });
+        assert_eq!(result.unwrap(), 3);
+    }
+
+    #[test]
+    fn retries_stop_after_max_attempts() {
+        let mut calls = 0;
+        let result: Result<(), HttpError> = instant_policy(2).retry(|| {
+            calls += 1;
+            Err(HttpError::ServerError(500))
+        });
+        assert!(result.is_err());
+        assert_eq!(calls, 2);
+    }
+
+    #[test]
+    fn not_found_is_never_retried() {
+        let mut calls = 0;
+        let result: Result<(), HttpError> = instant_policy(5).retry(|| {
+            calls += 1;
+            Err(HttpError::NotFound)
+        });
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
+}
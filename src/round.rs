@@ -0,0 +1,193 @@
+use crate::chain_info::ChainInfo;
+use crate::DrandClientError;
+use core::fmt;
+use core::num::NonZeroU64;
+#[cfg(any(feature = "async", feature = "blocking"))]
+use core::time::Duration;
+
+/// A round number, which can't be 0: rounds start at 1, emitted at the chain's genesis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Round(NonZeroU64);
+
+impl Round {
+    /// The round emitted at the chain's genesis.
+    pub const FIRST: Round = Round(NonZeroU64::MIN);
+
+    /// `None` for round 0.
+    pub fn new(round: u64) -> Option<Round> {
+        NonZeroU64::new(round).map(Round)
+    }
+
+    pub fn get(self) -> u64 {
+        self.0.get()
+    }
+
+    /// The round `rounds` after this one, or `None` if that would overflow.
+    pub fn checked_add(self, rounds: u64) -> Option<Round> {
+        self.0.checked_add(rounds).map(Round)
+    }
+
+    pub fn next(self) -> Option<Round> {
+        self.checked_add(1)
+    }
+}
+
+impl From<Round> for u64 {
+    fn from(round: Round) -> u64 {
+        round.get()
+    }
+}
+
+impl TryFrom<u64> for Round {
+    type Error = DrandClientError;
+
+    fn try_from(round: u64) -> Result<Round, DrandClientError> {
+        Round::new(round).ok_or(DrandClientError::InvalidRound)
+    }
+}
+
+impl fmt::Display for Round {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Returns the round that is expected to have been emitted at `unix_time`.
+/// Times before the chain's genesis are treated as round 1, being the first round the chain will produce.
+pub fn round_at(chain_info: &ChainInfo, unix_time: u64) -> Round {
+    if unix_time < chain_info.genesis_time {
+        return Round::FIRST;
+    }
+    let elapsed = unix_time - chain_info.genesis_time;
+    Round(NonZeroU64::MIN.saturating_add(elapsed / chain_info.period_seconds as u64))
+}
+
+/// Returns the unix time at which `round` is emitted.
+/// Rounds whose emission time would overflow a `u64` are rejected.
+pub fn time_of_round(chain_info: &ChainInfo, round: Round) -> Result<u64, DrandClientError> {
+    (round.get() - 1)
+        .checked_mul(chain_info.period_seconds as u64)
+        .and_then(|offset| offset.checked_add(chain_info.genesis_time))
+        .ok_or(DrandClientError::InvalidRound)
+}
+
+/// Fails with [`DrandClientError::InvalidBeacon`] if `round` isn't emitted until more than
+/// `tolerance` after `now`, a unix time, as no honest relay could have served it yet.
+#[cfg(any(feature = "async", feature = "blocking"))]
+pub(crate) fn reject_future_round(
+    chain_info: &ChainInfo,
+    round: u64,
+    now: u64,
+    tolerance: Duration,
+) -> Result<(), DrandClientError> {
+    let Some(round) = Round::new(round) else {
+        return Ok(());
+    };
+    // a round too far off to have an emission time is certainly in the future
+    let emitted_at = time_of_round(chain_info, round).unwrap_or(u64::MAX);
+    if emitted_at > now.saturating_add(tolerance.as_secs()) {
+        return Err(DrandClientError::InvalidBeacon {
+            reason: format!("round {round} is not emitted until {emitted_at}, in the future"),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::chain_info::{ChainInfo, ChainInfoMetadata};
+    use crate::round::{reject_future_round, round_at, time_of_round, Round};
+    use crate::DrandClientError;
+    use std::time::Duration;
+
+    fn chain_info(genesis_time: u64, period_seconds: usize) -> ChainInfo {
+        ChainInfo {
+            scheme_id: "pedersen-bls-chained".to_string(),
+            public_key: vec![],
+            chain_hash: vec![],
+            group_hash: vec![],
+            genesis_time,
+            period_seconds,
+            metadata: ChainInfoMetadata {
+                beacon_id: "default".to_string(),
+            },
+            parsed_public_key: Default::default(),
+        }
+    }
+
+    #[test]
+    fn genesis_time_is_round_one() {
+        let info = chain_info(1000, 30);
+        assert_eq!(round_at(&info, 1000), Round::FIRST);
+        assert_eq!(round_at(&info, 1029), Round::FIRST);
+    }
+
+    #[test]
+    fn each_period_advances_one_round() {
+        let info = chain_info(1000, 30);
+        assert_eq!(round_at(&info, 1030).get(), 2);
+        assert_eq!(round_at(&info, 1000 + 30 * 100).get(), 101);
+    }
+
+    #[test]
+    fn time_before_genesis_is_round_one() {
+        let info = chain_info(1000, 30);
+        assert_eq!(round_at(&info, 0), Round::FIRST);
+        assert_eq!(round_at(&info, 999), Round::FIRST);
+    }
+
+    #[test]
+    fn round_one_is_emitted_at_genesis() {
+        let info = chain_info(1000, 30);
+        assert_eq!(time_of_round(&info, Round::FIRST).unwrap(), 1000);
+        let round = Round::new(101).unwrap();
+        assert_eq!(time_of_round(&info, round).unwrap(), 1000 + 30 * 100);
+    }
+
+    #[test]
+    fn time_of_round_is_inverse_of_round_at() {
+        let info = chain_info(1595431050, 30);
+        let round = Round::new(2_500_000).unwrap();
+        let time = time_of_round(&info, round).unwrap();
+        assert_eq!(round_at(&info, time), round);
+    }
+
+    #[test]
+    fn round_zero_does_not_exist() {
+        assert_eq!(Round::new(0), None);
+        assert!(matches!(
+            Round::try_from(0),
+            Err(DrandClientError::InvalidRound)
+        ));
+        assert_eq!(u64::from(Round::try_from(7).unwrap()), 7);
+        assert_eq!(Round::new(u64::MAX).unwrap().next(), None);
+    }
+
+    #[test]
+    fn time_of_huge_round_does_not_overflow() {
+        let info = chain_info(1000, 30);
+        assert!(matches!(
+            time_of_round(&info, Round::new(u64::MAX).unwrap()),
+            Err(DrandClientError::InvalidRound)
+        ));
+    }
+
+    #[test]
+    fn times_near_the_end_of_time_saturate() {
+        let info = chain_info(0, 1);
+        assert_eq!(round_at(&info, u64::MAX).get(), u64::MAX);
+    }
+
+    #[test]
+    fn rounds_emitted_beyond_the_tolerance_are_rejected() {
+        let info = chain_info(1000, 30);
+        let tolerance = Duration::from_secs(10);
+        assert!(reject_future_round(&info, 2, 1030, Duration::ZERO).is_ok());
+        assert!(reject_future_round(&info, 3, 1050, tolerance).is_ok());
+        assert!(matches!(
+            reject_future_round(&info, 3, 1049, tolerance),
+            Err(DrandClientError::InvalidBeacon { .. })
+        ));
+        assert!(reject_future_round(&info, u64::MAX, 1030, tolerance).is_err());
+    }
+}
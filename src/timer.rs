@@ -0,0 +1,28 @@
+use std::time::{Duration, SystemTime};
+
+/// Sleeps without blocking the executor: on tokio natively, and on the browser's timers in WASM.
+#[cfg(all(feature = "async", not(target_arch = "wasm32")))]
+pub(crate) async fn sleep(duration: Duration) {
+    tokio::time::sleep(duration).await
+}
+
+#[cfg(all(feature = "async", target_arch = "wasm32"))]
+pub(crate) async fn sleep(duration: Duration) {
+    gloo_timers::future::sleep(duration).await
+}
+
+/// `SystemTime::now`, which panics when there's no OS clock, as on `wasm32-unknown-unknown`.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn now() -> SystemTime {
+    SystemTime::now()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn now() -> SystemTime {
+    SystemTime::UNIX_EPOCH + Duration::from_millis(js_sys::Date::now() as u64)
+}
+
+/// The time since `start`, which was read from [`now`]; zero if the clock went backwards.
+pub(crate) fn elapsed(start: SystemTime) -> Duration {
+    now().duration_since(start).unwrap_or_default()
+}
@@ -0,0 +1,446 @@
+//! Timelock encryption to future drand rounds, in the age format used by drand's `tlock` tools.
+//!
+//! A ciphertext is encrypted to a round's identity, and can only be opened with that round's
+//! signature, i.e. once the network has published the round:
+//!
+//! ```no_run
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use drand_client_rs::{new_unchained_g1_rfc9380_client, tlock};
+//!
+//! let client = new_unchained_g1_rfc9380_client(
+//!     "https://api.drand.sh/52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971",
+//! )?;
+//! let round = client.current_round()?.get() + 10;
+//! let ciphertext = tlock::encrypt(client.chain_info(), round, b"see you in 30 seconds")?;
+//!
+//! // ...once the round has been published
+//! let beacon = client.randomness(round)?;
+//! let plaintext = tlock::decrypt(client.chain_info(), &beacon, &ciphertext)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::bls;
+use crate::chain_info::ChainInfo;
+use crate::ibe::{self, SignatureGroup};
+use crate::unchained::UnchainedBeacon;
+use base64::engine::general_purpose::{STANDARD, STANDARD_NO_PAD};
+use base64::Engine;
+use chacha20poly1305::aead::Aead;
+use chacha20poly1305::{ChaCha20Poly1305, KeyInit};
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac, NewMac};
+use sha2::Sha256;
+use subtle::ConstantTimeEq;
+use thiserror::Error;
+
+const VERSION_LINE: &str = "age-encryption.org/v1";
+const STANZA_TYPE: &str = "tlock";
+const ARMOR_BEGIN: &str = "-----BEGIN AGE ENCRYPTED FILE-----";
+const ARMOR_END: &str = "-----END AGE ENCRYPTED FILE-----";
+const COLUMNS: usize = 64;
+const CHUNK_SIZE: usize = 64 * 1024;
+const NONCE_LEN: usize = 16;
+const TAG_LEN: usize = 16;
+
+#[derive(Error, Debug)]
+pub enum TlockError {
+    #[error("scheme cannot be used for timelock encryption")]
+    UnsupportedScheme,
+    #[error("invalid chain info")]
+    InvalidChainInfo,
+    #[error("invalid ciphertext")]
+    InvalidCiphertext,
+    #[error("ciphertext is for round {expected} but the beacon is for round {actual}")]
+    RoundMismatch { expected: u64, actual: u64 },
+    #[error("ciphertext is for another chain")]
+    ChainHashMismatch,
+    #[error("decryption failed")]
+    DecryptionFailed,
+}
+
+/// Encrypts `plaintext` so that it can only be decrypted with the beacon for `round`,
+/// returning an ASCII-armored age file.
+pub fn encrypt(
+    chain_info: &ChainInfo,
+    round: u64,
+    plaintext: &[u8],
+) -> Result<Vec<u8>, TlockError> {
+    let group =
+        SignatureGroup::for_scheme(&chain_info.scheme_id).ok_or(TlockError::UnsupportedScheme)?;
+    let file_key: [u8; ibe::MESSAGE_LEN] = rand::random();
+    let sigma: [u8; ibe::MESSAGE_LEN] = rand::random();
+
+    let body = ibe::encrypt(
+        group,
+        &chain_info.public_key,
+        &bls::round_digest(round),
+        &file_key,
+        &sigma,
+    )
+    .ok_or(TlockError::InvalidChainInfo)?;
+
+    let mut header = format!(
+        "{}\n-> {} {} {}\n",
+        VERSION_LINE,
+        STANZA_TYPE,
+        round,
+        hex::encode(&chain_info.chain_hash)
+    );
+    for line in wrap(&STANDARD_NO_PAD.encode(body)) {
+        header.push_str(line);
+        header.push('\n');
+    }
+    header.push_str("---");
+    let mac = STANDARD_NO_PAD.encode(header_mac(&file_key, &header));
+
+    let mut file = format!("{} {}\n", header, mac).into_bytes();
+    let nonce: [u8; NONCE_LEN] = rand::random();
+    file.extend_from_slice(&nonce);
+    file.extend(seal_payload(&file_key, &nonce, plaintext));
+
+    Ok(armor(&file).into_bytes())
+}
+
+/// Decrypts a ciphertext produced by [encrypt], armored or not, using the beacon for the round
+/// it was encrypted to.
+pub fn decrypt(
+    chain_info: &ChainInfo,
+    beacon: &UnchainedBeacon,
+    ciphertext: &[u8],
+) -> Result<Vec<u8>, TlockError> {
+    let group =
+        SignatureGroup::for_scheme(&chain_info.scheme_id).ok_or(TlockError::UnsupportedScheme)?;
+    let file = if ciphertext.starts_with(ARMOR_BEGIN.as_bytes()) {
+        dearmor(ciphertext)?
+    } else {
+        ciphertext.to_vec()
+    };
+
+    let (header, mac, payload) = split_file(&file)?;
+    let stanza = parse_stanza(header)?;
+    if stanza.round != beacon.round_number {
+        return Err(TlockError::RoundMismatch {
+            expected: stanza.round,
+            actual: beacon.round_number,
+        });
+    }
+    if stanza.chain_hash != chain_info.chain_hash {
+        return Err(TlockError::ChainHashMismatch);
+    }
+
+    let file_key =
+        ibe::decrypt(group, &beacon.signature, &stanza.body).ok_or(TlockError::DecryptionFailed)?;
+    // in constant time, so how much of a forged mac matched can't be timed
+    if !bool::from(header_mac(&file_key, header).ct_eq(&mac)) {
+        return Err(TlockError::DecryptionFailed);
+    }
+
+    if payload.len() < NONCE_LEN {
+        return Err(TlockError::InvalidCiphertext);
+    }
+    let (nonce, sealed) = payload.split_at(NONCE_LEN);
+    open_payload(&file_key, nonce, sealed).ok_or(TlockError::DecryptionFailed)
+}
+
+struct Stanza {
+    round: u64,
+    chain_hash: Vec<u8>,
+    body: Vec<u8>,
+}
+
+/// Splits an age file into its header (up to and including the `---` the MAC covers),
+/// the decoded MAC and the binary payload.
+fn split_file(file: &[u8]) -> Result<(&str, Vec<u8>, &[u8]), TlockError> {
+    let mac_marker = b"\n--- ";
+    let header_len = file
+        .windows(mac_marker.len())
+        .position(|window| window == mac_marker)
+        .ok_or(TlockError::InvalidCiphertext)?
+        + mac_marker.len()
+        - 1;
+    let mac_len = file[header_len..]
+        .iter()
+        .position(|b| *b == b'\n')
+        .ok_or(TlockError::InvalidCiphertext)?;
+
+    let header =
+        std::str::from_utf8(&file[..header_len]).map_err(|_| TlockError::InvalidCiphertext)?;
+    let mac = STANDARD_NO_PAD
+        .decode(&file[header_len + 1..header_len + mac_len])
+        .map_err(|_| TlockError::InvalidCiphertext)?;
+    Ok((header, mac, &file[header_len + mac_len + 1..]))
+}
+
+/// Parses the header's single `tlock` recipient stanza.
+fn parse_stanza(header: &str) -> Result<Stanza, TlockError> {
+    let mut lines = header.lines();
+    if lines.next() != Some(VERSION_LINE) {
+        return Err(TlockError::InvalidCiphertext);
+    }
+
+    let args: Vec<&str> = lines
+        .next()
+        .and_then(|line| line.strip_prefix("-> "))
+        .ok_or(TlockError::InvalidCiphertext)?
+        .split(' ')
+        .collect();
+    let (round, chain_hash) = match args.as_slice() {
+        [STANZA_TYPE, round, chain_hash] => (round, chain_hash),
+        _ => return Err(TlockError::InvalidCiphertext),
+    };
+
+    let mut body = String::new();
+    for line in lines.by_ref() {
+        body.push_str(line);
+        if line.len() < COLUMNS {
+            break;
+        }
+    }
+    if lines.next() != Some("---") {
+        return Err(TlockError::InvalidCiphertext);
+    }
+
+    Ok(Stanza {
+        round: round.parse().map_err(|_| TlockError::InvalidCiphertext)?,
+        chain_hash: hex::decode(chain_hash).map_err(|_| TlockError::InvalidCiphertext)?,
+        body: STANDARD_NO_PAD
+            .decode(body)
+            .map_err(|_| TlockError::InvalidCiphertext)?,
+    })
+}
+
+/// Splits base64 into age's 64 column lines; the final line is always short, even if empty.
+fn wrap(encoded: &str) -> Vec<&str> {
+    let mut lines: Vec<&str> = encoded
+        .as_bytes()
+        .chunks(COLUMNS)
+        .map(|line| std::str::from_utf8(line).expect("base64 is ascii"))
+        .collect();
+    if lines.last().is_none_or(|line| line.len() == COLUMNS) {
+        lines.push("");
+    }
+    lines
+}
+
+fn header_mac(file_key: &[u8], header: &str) -> Vec<u8> {
+    let key = hkdf(&[], file_key, b"header");
+    let mut mac = Hmac::<Sha256>::new_from_slice(&key).expect("hmac accepts any key length");
+    mac.update(header.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hkdf(salt: &[u8], ikm: &[u8], info: &[u8]) -> [u8; 32] {
+    let mut key = [0; 32];
+    Hkdf::<Sha256>::new(Some(salt), ikm)
+        .expand(info, &mut key)
+        .expect("32 bytes is a valid hkdf output length");
+    key
+}
+
+/// Encrypts the payload with age's STREAM construction: ChaCha20-Poly1305 over 64KiB chunks,
+/// each with a nonce of its big-endian counter and a flag marking the last chunk.
+fn seal_payload(file_key: &[u8], nonce: &[u8], plaintext: &[u8]) -> Vec<u8> {
+    let cipher = ChaCha20Poly1305::new(&hkdf(nonce, file_key, b"payload").into());
+    let chunks: Vec<&[u8]> = if plaintext.is_empty() {
+        vec![plaintext]
+    } else {
+        plaintext.chunks(CHUNK_SIZE).collect()
+    };
+
+    let mut sealed = vec![];
+    for (counter, chunk) in chunks.iter().enumerate() {
+        let nonce = stream_nonce(counter as u64, counter == chunks.len() - 1);
+        sealed.extend(
+            cipher
+                .encrypt(&nonce.into(), *chunk)
+                .expect("chunks are well within chacha20poly1305's limits"),
+        );
+    }
+    sealed
+}
+
+fn open_payload(file_key: &[u8], nonce: &[u8], sealed: &[u8]) -> Option<Vec<u8>> {
+    let cipher = ChaCha20Poly1305::new(&hkdf(nonce, file_key, b"payload").into());
+    let chunks: Vec<&[u8]> = sealed.chunks(CHUNK_SIZE + TAG_LEN).collect();
+    if chunks.is_empty() {
+        return None;
+    }
+
+    let mut plaintext = vec![];
+    for (counter, chunk) in chunks.iter().enumerate() {
+        let nonce = stream_nonce(counter as u64, counter == chunks.len() - 1);
+        plaintext.extend(cipher.decrypt(&nonce.into(), *chunk).ok()?);
+    }
+    Some(plaintext)
+}
+
+fn stream_nonce(counter: u64, last: bool) -> [u8; 12] {
+    let mut nonce = [0; 12];
+    nonce[3..11].copy_from_slice(&counter.to_be_bytes());
+    nonce[11] = last as u8;
+    nonce
+}
+
+fn armor(file: &[u8]) -> String {
+    let mut armored = format!("{}\n", ARMOR_BEGIN);
+    for line in STANDARD.encode(file).as_bytes().chunks(COLUMNS) {
+        armored.push_str(std::str::from_utf8(line).expect("base64 is ascii"));
+        armored.push('\n');
+    }
+    armored.push_str(ARMOR_END);
+    armored.push('\n');
+    armored
+}
+
+fn dearmor(armored: &[u8]) -> Result<Vec<u8>, TlockError> {
+    let text = std::str::from_utf8(armored).map_err(|_| TlockError::InvalidCiphertext)?;
+    let encoded: String = text
+        .trim()
+        .strip_prefix(ARMOR_BEGIN)
+        .and_then(|rest| rest.strip_suffix(ARMOR_END))
+        .ok_or(TlockError::InvalidCiphertext)?
+        .lines()
+        .map(str::trim)
+        .collect();
+    STANDARD
+        .decode(encoded)
+        .map_err(|_| TlockError::InvalidCiphertext)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::bls::{G1_DOMAIN, G2_DOMAIN};
+    use crate::tlock::{
+        dearmor, decrypt, encrypt, wrap, TlockError, ARMOR_BEGIN, ARMOR_END, CHUNK_SIZE,
+    };
+    use crate::{fixtures, new_unchained_g1_rfc9380_client};
+
+    #[test]
+    fn request_quicknet_tlock_round_trip_success() {
+        let quicknet_url =
+            "https://api.drand.sh/52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971";
+        let client = new_unchained_g1_rfc9380_client(quicknet_url).unwrap();
+        let round = client.latest_randomness().unwrap().round_number;
+        let ciphertext = encrypt(client.chain_info(), round, b"hello").unwrap();
+
+        let beacon = client.randomness(round).unwrap();
+        let plaintext = decrypt(client.chain_info(), &beacon, &ciphertext).unwrap();
+        assert_eq!(plaintext, b"hello");
+    }
+
+    #[test]
+    fn plaintext_round_trips_through_each_scheme() {
+        let cases = [
+            (
+                fixtures::g1_chain_info("bls-unchained-g1-rfc9380"),
+                fixtures::unchained_g1_beacon(1000, G1_DOMAIN),
+            ),
+            (
+                fixtures::g1_chain_info("bls-unchained-on-g1"),
+                fixtures::unchained_g1_beacon(1000, G2_DOMAIN),
+            ),
+            (
+                fixtures::chain_info("pedersen-bls-unchained"),
+                fixtures::unchained_beacon(1000),
+            ),
+        ];
+
+        for (info, beacon) in cases {
+            let ciphertext = encrypt(&info, 1000, b"hello").unwrap();
+            assert_eq!(decrypt(&info, &beacon, &ciphertext).unwrap(), b"hello");
+        }
+    }
+
+    #[test]
+    fn captured_rounds_open_ciphertexts_to_them() {
+        // encrypting hashes the round onto the curve as the network's signers did, or the real
+        // signature wouldn't open it
+        let cases = [
+            (fixtures::quicknet_chain_info(), fixtures::quicknet_beacon()),
+            (fixtures::fastnet_chain_info(), fixtures::fastnet_beacon()),
+        ];
+        for (info, beacon) in cases {
+            let ciphertext = encrypt(&info, beacon.round_number, b"hello").unwrap();
+            assert_eq!(decrypt(&info, &beacon, &ciphertext).unwrap(), b"hello");
+        }
+    }
+
+    #[test]
+    fn unarmored_and_multi_chunk_files_decrypt() {
+        let info = fixtures::g1_chain_info("bls-unchained-g1-rfc9380");
+        let beacon = fixtures::unchained_g1_beacon(1000, G1_DOMAIN);
+        for plaintext in [vec![], vec![7; CHUNK_SIZE], vec![7; CHUNK_SIZE * 2 + 1]] {
+            let armored = encrypt(&info, 1000, &plaintext).unwrap();
+            let binary = dearmor(&armored).unwrap();
+            assert_eq!(decrypt(&info, &beacon, &binary).unwrap(), plaintext);
+        }
+    }
+
+    #[test]
+    fn beacon_for_another_round_is_rejected() {
+        let info = fixtures::g1_chain_info("bls-unchained-g1-rfc9380");
+        let ciphertext = encrypt(&info, 1000, b"hello").unwrap();
+        let beacon = fixtures::unchained_g1_beacon(999, G1_DOMAIN);
+        assert!(matches!(
+            decrypt(&info, &beacon, &ciphertext),
+            Err(TlockError::RoundMismatch {
+                expected: 1000,
+                actual: 999
+            })
+        ));
+    }
+
+    #[test]
+    fn tampered_payload_fails_to_decrypt() {
+        let info = fixtures::g1_chain_info("bls-unchained-g1-rfc9380");
+        let beacon = fixtures::unchained_g1_beacon(1000, G1_DOMAIN);
+        let mut binary = dearmor(&encrypt(&info, 1000, b"hello").unwrap()).unwrap();
+        let last = binary.len() - 1;
+        binary[last] ^= 0x01;
+        assert!(matches!(
+            decrypt(&info, &beacon, &binary),
+            Err(TlockError::DecryptionFailed)
+        ));
+    }
+
+    #[test]
+    fn tampered_header_mac_fails_to_decrypt() {
+        let info = fixtures::g1_chain_info("bls-unchained-g1-rfc9380");
+        let beacon = fixtures::unchained_g1_beacon(1000, G1_DOMAIN);
+        let mut binary = dearmor(&encrypt(&info, 1000, b"hello").unwrap()).unwrap();
+        let mac_start = binary.windows(5).position(|w| w == b"\n--- ").unwrap() + 5;
+        binary[mac_start] = if binary[mac_start] == b'A' {
+            b'B'
+        } else {
+            b'A'
+        };
+        assert!(matches!(
+            decrypt(&info, &beacon, &binary),
+            Err(TlockError::DecryptionFailed)
+        ));
+    }
+
+    #[test]
+    fn ciphertext_is_an_armored_age_file() {
+        let info = fixtures::g1_chain_info("bls-unchained-g1-rfc9380");
+        let armored = String::from_utf8(encrypt(&info, 1000, b"hello").unwrap()).unwrap();
+        assert!(armored.starts_with(ARMOR_BEGIN));
+        assert!(armored.trim_end().ends_with(ARMOR_END));
+    }
+
+    #[test]
+    fn chained_schemes_are_rejected() {
+        let info = fixtures::chain_info("pedersen-bls-chained");
+        assert!(matches!(
+            encrypt(&info, 1000, b"hello"),
+            Err(TlockError::UnsupportedScheme)
+        ));
+    }
+
+    #[test]
+    fn stanza_bodies_always_end_with_a_short_line() {
+        assert_eq!(wrap(&"a".repeat(64)), vec!["a".repeat(64).as_str(), ""]);
+        assert_eq!(wrap("abc"), vec!["abc"]);
+    }
+}
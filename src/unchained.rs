@@ -1,12 +1,14 @@
 use crate::bls::BlsVerifiable;
 use crate::chain_info::ChainInfo;
-use crate::{bls, Scheme, SchemeError};
-use serde::Deserialize;
-use std::io::Write;
+use crate::{bls, Beacon, Scheme, SchemeError, RANDOMNESS_LEN};
+#[cfg(feature = "std")]
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Debug, PartialEq, Clone)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct UnchainedBeacon {
-    #[serde(alias = "round")]
+    #[serde(rename(serialize = "round"), alias = "round")]
     pub round_number: u64,
     #[serde(with = "hex")]
     pub randomness: Vec<u8>,
@@ -16,11 +18,33 @@ pub struct UnchainedBeacon {
 
 pub struct UnchainedScheme {}
 
+impl UnchainedScheme {
+    /// Verifies every beacon in `beacons` at once, which is much faster than verifying each in
+    /// turn as they share a single multi-pairing. It only reports whether they're all valid;
+    /// verify them individually to find the invalid ones.
+    #[cfg(feature = "std")]
+    pub fn verify_batch(
+        &self,
+        info: &ChainInfo,
+        beacons: &[UnchainedBeacon],
+    ) -> Result<(), SchemeError> {
+        if !self.supports(&info.scheme_id) {
+            Err(SchemeError::InvalidScheme)
+        } else {
+            bls::bls_verify_batch(info, beacons)
+        }
+    }
+}
+
 impl Scheme<UnchainedBeacon> for UnchainedScheme {
     fn supports(&self, scheme_id: &str) -> bool {
         scheme_id.eq_ignore_ascii_case("pedersen-bls-unchained")
     }
 
+    fn scheme_id(&self) -> &str {
+        "pedersen-bls-unchained"
+    }
+
     fn verify(
         &self,
         info: &ChainInfo,
@@ -29,23 +53,144 @@ impl Scheme<UnchainedBeacon> for UnchainedScheme {
         if !self.supports(&info.scheme_id) {
             Err(SchemeError::InvalidScheme)
         } else {
-            bls::bls_verify(info, beacon)
+            bls::bls_verify_scheme(self.scheme_id(), info, beacon)
         }
     }
 }
 
+impl UnchainedBeacon {
+    /// The beacon in the JSON shape drand's relays serve it in.
+    #[cfg(feature = "std")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("beacons always serialise")
+    }
+}
+
+impl Beacon for UnchainedBeacon {
+    fn round_number(&self) -> u64 {
+        self.round_number
+    }
+
+    fn randomness_bytes(&self) -> Result<[u8; RANDOMNESS_LEN], SchemeError> {
+        self.randomness
+            .as_slice()
+            .try_into()
+            .map_err(|_| SchemeError::InvalidBeacon)
+    }
+
+    fn signature_bytes(&self) -> &[u8] {
+        &self.signature
+    }
+}
+
 impl BlsVerifiable for UnchainedBeacon {
     fn signature(&self) -> &Vec<u8> {
         &self.signature
     }
 
+    fn randomness(&self) -> &Vec<u8> {
+        &self.randomness
+    }
+
     fn to_message(&self) -> Result<Vec<u8>, SchemeError> {
-        let mut bytes: Vec<u8> = vec![];
+        Ok(self.round_number.to_be_bytes().to_vec())
+    }
+}
 
-        if bytes.write_all(&self.round_number.to_be_bytes()).is_err() {
-            Err(SchemeError::InvalidBeacon)
-        } else {
-            Ok(bytes)
-        }
+#[cfg(test)]
+mod test {
+    use crate::fixtures;
+    use crate::unchained::{UnchainedBeacon, UnchainedScheme};
+    use crate::{Beacon, Scheme};
+
+    #[test]
+    fn valid_beacon_verifies() {
+        let info = fixtures::chain_info("pedersen-bls-unchained");
+        let beacon = fixtures::unchained_beacon(2);
+        assert!(UnchainedScheme {}.verify(&info, beacon).is_ok());
+    }
+
+    #[test]
+    fn randomness_bytes_are_a_fixed_size_array() {
+        let mut beacon = fixtures::unchained_beacon(2);
+        assert_eq!(
+            beacon.randomness_bytes().unwrap().as_slice(),
+            beacon.randomness.as_slice()
+        );
+        assert_eq!(beacon.signature_bytes(), beacon.signature.as_slice());
+
+        beacon.randomness.pop();
+        assert!(beacon.randomness_bytes().is_err());
+    }
+
+    #[test]
+    fn tampered_randomness_is_rejected() {
+        let info = fixtures::chain_info("pedersen-bls-unchained");
+        let mut beacon = fixtures::unchained_beacon(2);
+        beacon.randomness[0] ^= 0x01;
+        assert!(UnchainedScheme {}.verify(&info, beacon).is_err());
+    }
+
+    #[test]
+    fn truncated_randomness_is_rejected() {
+        let info = fixtures::chain_info("pedersen-bls-unchained");
+        let mut beacon = fixtures::unchained_beacon(2);
+        beacon.randomness.pop();
+        assert!(UnchainedScheme {}.verify(&info, beacon).is_err());
+    }
+
+    #[test]
+    fn valid_beacons_verify_as_a_batch() {
+        let info = fixtures::chain_info("pedersen-bls-unchained");
+        let beacons: Vec<_> = (1..=8).map(fixtures::unchained_beacon).collect();
+        assert!(UnchainedScheme {}.verify_batch(&info, &beacons).is_ok());
+        assert!(UnchainedScheme {}.verify_batch(&info, &[]).is_ok());
+    }
+
+    #[test]
+    fn one_invalid_beacon_fails_the_batch() {
+        let info = fixtures::chain_info("pedersen-bls-unchained");
+        let mut beacons: Vec<_> = (1..=8).map(fixtures::unchained_beacon).collect();
+        beacons[4].round_number = 50;
+        assert!(UnchainedScheme {}.verify_batch(&info, &beacons).is_err());
+    }
+
+    #[test]
+    fn swapped_signatures_fail_the_batch() {
+        // the sums of the signatures and of the messages are unchanged by swapping, so only the
+        // random weighting catches this
+        let info = fixtures::chain_info("pedersen-bls-unchained");
+        let mut beacons: Vec<_> = (1..=2).map(fixtures::unchained_beacon).collect();
+        beacons.swap(0, 1);
+        let (first, second) = beacons.split_at_mut(1);
+        std::mem::swap(&mut first[0].round_number, &mut second[0].round_number);
+        assert!(UnchainedScheme {}.verify_batch(&info, &beacons).is_err());
+    }
+
+    #[test]
+    fn beacon_serialises_to_drand_json() {
+        let beacon = fixtures::unchained_beacon(2);
+        let json: serde_json::Value = serde_json::from_str(&beacon.to_json()).unwrap();
+        let expected: serde_json::Value =
+            serde_json::from_str(&fixtures::unchained_beacon_json(&beacon)).unwrap();
+        assert_eq!(json, expected);
+        assert_eq!(
+            serde_json::from_str::<UnchainedBeacon>(&beacon.to_json()).unwrap(),
+            beacon
+        );
+    }
+
+    #[test]
+    fn hex_fields_are_decoded_whatever_their_case() {
+        let beacon = fixtures::unchained_beacon(2);
+        let shouting = serde_json::json!({
+            "round": beacon.round_number,
+            "randomness": hex::encode_upper(&beacon.randomness),
+            "signature": hex::encode_upper(&beacon.signature),
+        });
+        let parsed: UnchainedBeacon = serde_json::from_value(shouting).unwrap();
+        assert_eq!(parsed, beacon);
+        assert_eq!(parsed.to_json(), beacon.to_json());
+        assert!(!parsed.to_json().chars().any(|c| c.is_ascii_uppercase()));
     }
 }
@@ -0,0 +1,120 @@
+use crate::chain_info::ChainInfo;
+use crate::unchained::UnchainedBeacon;
+use crate::{bls, Scheme, SchemeError};
+
+/// Unchained beacons with short signatures on G1 and the group public key on G2.
+pub struct UnchainedSchemeOnG1 {}
+
+impl Scheme<UnchainedBeacon> for UnchainedSchemeOnG1 {
+    fn supports(&self, scheme_id: &str) -> bool {
+        scheme_id.eq_ignore_ascii_case("bls-unchained-on-g1")
+    }
+
+    fn scheme_id(&self) -> &str {
+        "bls-unchained-on-g1"
+    }
+
+    fn verify(
+        &self,
+        info: &ChainInfo,
+        beacon: UnchainedBeacon,
+    ) -> Result<UnchainedBeacon, SchemeError> {
+        if !self.supports(&info.scheme_id) {
+            Err(SchemeError::InvalidScheme)
+        } else {
+            bls::bls_verify_scheme(self.scheme_id(), info, beacon)
+        }
+    }
+}
+
+/// Unchained beacons with signatures on G1, hashed onto the curve as specified by RFC 9380.
+/// This is the scheme used by quicknet.
+pub struct UnchainedSchemeOnG1Rfc9380 {}
+
+impl Scheme<UnchainedBeacon> for UnchainedSchemeOnG1Rfc9380 {
+    fn supports(&self, scheme_id: &str) -> bool {
+        scheme_id.eq_ignore_ascii_case("bls-unchained-g1-rfc9380")
+    }
+
+    fn scheme_id(&self) -> &str {
+        "bls-unchained-g1-rfc9380"
+    }
+
+    fn verify(
+        &self,
+        info: &ChainInfo,
+        beacon: UnchainedBeacon,
+    ) -> Result<UnchainedBeacon, SchemeError> {
+        if !self.supports(&info.scheme_id) {
+            Err(SchemeError::InvalidScheme)
+        } else {
+            bls::bls_verify_scheme(self.scheme_id(), info, beacon)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::bls::{G1_DOMAIN, G2_DOMAIN};
+    use crate::fixtures;
+    use crate::unchained_g1::{UnchainedSchemeOnG1, UnchainedSchemeOnG1Rfc9380};
+    use crate::{ChainInfo, Scheme};
+
+    #[test]
+    fn captured_g1_beacon_verifies_with_the_g2_tag() {
+        let info = fixtures::fastnet_chain_info();
+        let beacon = fixtures::fastnet_beacon();
+        assert!(UnchainedSchemeOnG1 {}.verify(&info, beacon.clone()).is_ok());
+        // signed with drand's G2 tag, so the rfc9380 scheme's G1 tag rejects it
+        let rfc9380 = ChainInfo {
+            scheme_id: "bls-unchained-g1-rfc9380".to_string(),
+            ..info
+        };
+        assert!(UnchainedSchemeOnG1Rfc9380 {}
+            .verify(&rfc9380, beacon)
+            .is_err());
+    }
+
+    #[test]
+    fn valid_beacon_verifies() {
+        let info = fixtures::g1_chain_info("bls-unchained-on-g1");
+        let beacon = fixtures::unchained_g1_beacon(2, G2_DOMAIN);
+        assert!(UnchainedSchemeOnG1 {}.verify(&info, beacon).is_ok());
+    }
+
+    #[test]
+    fn beacon_for_another_round_is_rejected() {
+        let info = fixtures::g1_chain_info("bls-unchained-on-g1");
+        let mut beacon = fixtures::unchained_g1_beacon(2, G2_DOMAIN);
+        beacon.round_number = 3;
+        assert!(UnchainedSchemeOnG1 {}.verify(&info, beacon).is_err());
+    }
+
+    #[test]
+    fn g2_beacon_is_rejected() {
+        let info = fixtures::g1_chain_info("bls-unchained-on-g1");
+        let beacon = fixtures::unchained_beacon(2);
+        assert!(UnchainedSchemeOnG1 {}.verify(&info, beacon).is_err());
+    }
+
+    #[test]
+    fn wrong_scheme_id_is_rejected() {
+        let info = fixtures::g1_chain_info("pedersen-bls-unchained");
+        let beacon = fixtures::unchained_g1_beacon(2, G2_DOMAIN);
+        assert!(UnchainedSchemeOnG1 {}.verify(&info, beacon).is_err());
+    }
+
+    #[test]
+    fn valid_rfc9380_beacon_verifies() {
+        let info = fixtures::g1_chain_info("bls-unchained-g1-rfc9380");
+        let beacon = fixtures::unchained_g1_beacon(2, G1_DOMAIN);
+        assert!(UnchainedSchemeOnG1Rfc9380 {}.verify(&info, beacon).is_ok());
+    }
+
+    #[test]
+    fn beacon_hashed_with_g2_domain_is_rejected_by_rfc9380() {
+        let info = fixtures::g1_chain_info("bls-unchained-g1-rfc9380");
+        let beacon = fixtures::unchained_g1_beacon(2, G2_DOMAIN);
+        assert!(UnchainedSchemeOnG1Rfc9380 {}.verify(&info, beacon).is_err());
+    }
+}
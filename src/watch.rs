@@ -0,0 +1,99 @@
+use crate::clock::Clock;
+use std::time::Duration;
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+use {
+    crate::{Beacon, DrandClient, DrandClientError, HttpTransport, Round, Transport},
+    serde::de::DeserializeOwned,
+    std::thread,
+};
+
+pub(crate) const RETRY_INTERVAL: Duration = Duration::from_secs(1);
+pub(crate) const MAX_ATTEMPTS_PER_ROUND: u32 = 5;
+
+/// Yields each new beacon as it is emitted, starting with the latest one.
+/// Created by `DrandClient::watch`.
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+pub struct Watch<'c, B, T = HttpTransport> {
+    client: &'c DrandClient<B, T>,
+    next_round: Option<u64>,
+}
+
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+impl<'c, B, T> Watch<'c, B, T>
+where
+    B: DeserializeOwned + Clone + Beacon,
+    T: Transport,
+{
+    pub(crate) fn new(client: &'c DrandClient<B, T>) -> Self {
+        Watch {
+            client,
+            next_round: None,
+        }
+    }
+
+    fn await_round(&self, round: u64) -> Result<B, DrandClientError> {
+        if let Ok(emitted_at) =
+            Round::try_from(round).and_then(|round| self.client.time_of_round(round))
+        {
+            thread::sleep(delay_until(&*self.client.clock, emitted_at));
+        }
+
+        let mut attempt = 1;
+        loop {
+            match self.client.randomness(round) {
+                Ok(beacon) if beacon.round_number() == round => return Ok(beacon),
+                // the local clock has drifted from the relay's, so resynchronise from its latest round
+                Ok(_) => return self.client.latest_randomness(),
+                Err(e) if attempt >= MAX_ATTEMPTS_PER_ROUND => return Err(e),
+                Err(_) => {
+                    thread::sleep(RETRY_INTERVAL);
+                    attempt += 1;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+impl<'c, B, T> Iterator for Watch<'c, B, T>
+where
+    B: DeserializeOwned + Clone + Beacon,
+    T: Transport,
+{
+    type Item = Result<B, DrandClientError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = match self.next_round {
+            None => self.client.latest_randomness(),
+            Some(round) => self.await_round(round),
+        };
+        if let Ok(beacon) = &result {
+            self.next_round = Some(beacon.round_number() + 1);
+        }
+        Some(result)
+    }
+}
+
+pub(crate) fn delay_until(clock: &dyn Clock, unix_time: u64) -> Duration {
+    Duration::from_secs(unix_time.saturating_sub(clock.now_unix()))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::clock::MockClock;
+    use crate::watch::delay_until;
+    use std::time::Duration;
+
+    #[test]
+    fn past_times_need_no_delay() {
+        let clock = MockClock::new(1000);
+        assert_eq!(delay_until(&clock, 0), Duration::ZERO);
+        assert_eq!(delay_until(&clock, 1000), Duration::ZERO);
+    }
+
+    #[test]
+    fn future_times_are_delayed_until() {
+        let clock = MockClock::new(1000);
+        assert_eq!(delay_until(&clock, 1060), Duration::from_secs(60));
+    }
+}